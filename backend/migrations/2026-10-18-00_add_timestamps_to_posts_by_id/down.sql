@@ -0,0 +1,6 @@
+DROP INDEX posts_by_id_created_at_idx;
+DROP INDEX posts_by_id_author_id_idx;
+
+ALTER TABLE posts_by_id
+    DROP COLUMN updated_at,
+    DROP COLUMN created_at;
\ No newline at end of file
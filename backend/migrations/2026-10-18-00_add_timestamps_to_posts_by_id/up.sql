@@ -0,0 +1,7 @@
+ALTER TABLE posts_by_id
+    ADD COLUMN created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT now();
+
+-- used to filter Posts by author and by creation date
+CREATE INDEX posts_by_id_author_id_idx ON posts_by_id (author_id);
+CREATE INDEX posts_by_id_created_at_idx ON posts_by_id (created_at);
\ No newline at end of file
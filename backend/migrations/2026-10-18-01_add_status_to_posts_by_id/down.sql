@@ -0,0 +1,5 @@
+DROP INDEX posts_by_id_status_idx;
+
+ALTER TABLE posts_by_id
+    DROP COLUMN published_at,
+    DROP COLUMN status;
\ No newline at end of file
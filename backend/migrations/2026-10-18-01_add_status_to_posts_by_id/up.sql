@@ -0,0 +1,11 @@
+-- Posts which already exist were visible to everyone, so they are marked as published. New Posts
+-- are drafts until they are published.
+ALTER TABLE posts_by_id
+    ADD COLUMN status VARCHAR NOT NULL DEFAULT 'published' CHECK (status IN ('draft', 'published', 'archived')),
+    ADD COLUMN published_at TIMESTAMPTZ;
+
+UPDATE posts_by_id SET published_at = created_at;
+
+ALTER TABLE posts_by_id ALTER COLUMN status SET DEFAULT 'draft';
+
+CREATE INDEX posts_by_id_status_idx ON posts_by_id (status);
\ No newline at end of file
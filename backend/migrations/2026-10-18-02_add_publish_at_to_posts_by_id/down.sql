@@ -0,0 +1,3 @@
+DROP INDEX posts_by_id_publish_at_idx;
+
+ALTER TABLE posts_by_id DROP COLUMN publish_at;
\ No newline at end of file
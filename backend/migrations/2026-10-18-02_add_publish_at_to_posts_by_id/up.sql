@@ -0,0 +1,4 @@
+-- only a few Posts are ever scheduled at once, so the index only includes those
+ALTER TABLE posts_by_id ADD COLUMN publish_at TIMESTAMPTZ;
+
+CREATE INDEX posts_by_id_publish_at_idx ON posts_by_id (publish_at) WHERE publish_at IS NOT NULL;
\ No newline at end of file
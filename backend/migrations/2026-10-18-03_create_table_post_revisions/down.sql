@@ -0,0 +1,3 @@
+DROP TABLE post_revisions;
+
+ALTER TABLE posts_by_id DROP COLUMN revision;
\ No newline at end of file
@@ -0,0 +1,15 @@
+-- Every Post which already exists gets its first revision. Later revisions are added by the
+-- application, in the same transaction as the change to the Post.
+ALTER TABLE posts_by_id ADD COLUMN revision INTEGER NOT NULL DEFAULT 1;
+
+CREATE TABLE post_revisions (
+    post_id UUID NOT NULL REFERENCES posts_by_id (post_id) ON DELETE CASCADE,
+    revision INTEGER NOT NULL,
+    title VARCHAR NOT NULL,
+    body VARCHAR NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (post_id, revision)
+);
+
+INSERT INTO post_revisions (post_id, revision, title, body, created_at)
+SELECT post_id, revision, title, body, updated_at FROM posts_by_id;
\ No newline at end of file
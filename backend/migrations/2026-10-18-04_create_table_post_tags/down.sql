@@ -0,0 +1 @@
+DROP TABLE post_tags;
\ No newline at end of file
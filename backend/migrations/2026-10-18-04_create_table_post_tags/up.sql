@@ -0,0 +1,9 @@
+-- Tags are slugs, normalized by the application before they are stored. The primary key already
+-- indexes the Tags of each Post, so another index is only needed to find the Posts with each Tag.
+CREATE TABLE post_tags (
+    post_id UUID NOT NULL REFERENCES posts_by_id (post_id) ON DELETE CASCADE,
+    tag VARCHAR NOT NULL,
+    PRIMARY KEY (post_id, tag)
+);
+
+CREATE INDEX post_tags_tag_idx ON post_tags (tag);
\ No newline at end of file
@@ -0,0 +1,3 @@
+DROP INDEX posts_by_id_search_vector_idx;
+
+ALTER TABLE posts_by_id DROP COLUMN search_vector;
\ No newline at end of file
@@ -0,0 +1,7 @@
+-- Matches in titles (weight 'A') rank above matches in bodies (weight 'B'). The column is generated,
+-- so it is kept up to date by Postgres itself, and it is not part of PostsByIdTableRow.
+ALTER TABLE posts_by_id ADD COLUMN search_vector TSVECTOR GENERATED ALWAYS AS (
+    setweight(to_tsvector('english', title), 'A') || setweight(to_tsvector('english', body), 'B')
+) STORED;
+
+CREATE INDEX posts_by_id_search_vector_idx ON posts_by_id USING GIN (search_vector);
\ No newline at end of file
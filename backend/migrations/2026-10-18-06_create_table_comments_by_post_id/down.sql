@@ -0,0 +1 @@
+DROP TABLE comments_by_post_id;
\ No newline at end of file
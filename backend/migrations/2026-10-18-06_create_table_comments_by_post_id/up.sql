@@ -0,0 +1,14 @@
+-- Comments are always listed by Post, so they are keyed by (post_id, comment_id). Replies must be on
+-- the same Post as their parent, and are deleted along with it.
+CREATE TABLE comments_by_post_id (
+    post_id UUID NOT NULL REFERENCES posts_by_id (post_id) ON DELETE CASCADE,
+    comment_id UUID NOT NULL,
+    author_id UUID NOT NULL,
+    parent_id UUID,
+    body TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (post_id, comment_id),
+    FOREIGN KEY (post_id, parent_id) REFERENCES comments_by_post_id (post_id, comment_id) ON DELETE CASCADE
+);
+
+CREATE INDEX comments_by_post_id_parent_id_idx ON comments_by_post_id (post_id, parent_id);
\ No newline at end of file
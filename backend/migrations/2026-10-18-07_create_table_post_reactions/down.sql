@@ -0,0 +1 @@
+DROP TABLE post_reactions;
\ No newline at end of file
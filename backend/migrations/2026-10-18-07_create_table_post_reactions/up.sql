@@ -0,0 +1,9 @@
+-- Each user can add at most one reaction of each kind to each Post. Reactions are always counted by
+-- Post, which the primary key already indexes.
+CREATE TABLE post_reactions (
+    post_id UUID NOT NULL REFERENCES posts_by_id (post_id) ON DELETE CASCADE,
+    user_id UUID NOT NULL,
+    kind VARCHAR NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (post_id, user_id, kind)
+);
\ No newline at end of file
@@ -0,0 +1,12 @@
+-- Users are recorded when they first log in. Authors are not (yet) required to be Users, because
+-- Posts may have been written before this table existed.
+CREATE TABLE users (
+    user_id UUID PRIMARY KEY,
+    username VARCHAR NOT NULL,
+    display_name VARCHAR NOT NULL,
+    bio TEXT,
+    avatar_url VARCHAR,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    last_login_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
\ No newline at end of file
@@ -0,0 +1,3 @@
+DROP INDEX posts_by_id_feed_idx;
+
+DROP TABLE follows;
\ No newline at end of file
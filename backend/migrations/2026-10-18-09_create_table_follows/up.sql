@@ -0,0 +1,11 @@
+-- Each row means that one User follows one author. Feeds are built by looking up the authors which
+-- a User follows (the primary key), then the latest published Posts by each of those authors (the
+-- partial index on posts_by_id).
+CREATE TABLE follows (
+    follower_id UUID NOT NULL,
+    author_id UUID NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (follower_id, author_id)
+);
+
+CREATE INDEX posts_by_id_feed_idx ON posts_by_id (author_id, published_at DESC, post_id DESC) WHERE status = 'published';
\ No newline at end of file
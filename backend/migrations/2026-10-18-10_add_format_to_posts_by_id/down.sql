@@ -0,0 +1 @@
+ALTER TABLE posts_by_id DROP COLUMN body_html, DROP COLUMN format;
\ No newline at end of file
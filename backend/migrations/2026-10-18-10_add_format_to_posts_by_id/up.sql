@@ -0,0 +1,11 @@
+-- Posts which already exist were written as plain text. Their HTML is rendered here exactly as
+-- Format::Plain renders it (see model/format.rs): escaped, with blank lines separating paragraphs.
+ALTER TABLE posts_by_id
+    ADD COLUMN format VARCHAR NOT NULL DEFAULT 'plain' CHECK (format IN ('plain', 'markdown')),
+    ADD COLUMN body_html TEXT NOT NULL DEFAULT '';
+
+UPDATE posts_by_id SET body_html = '<p>' || replace(replace(
+    replace(replace(replace(replace(replace(replace(body, E'\r\n', E'\n'), '&', '&amp;'), '<', '&lt;'), '>', '&gt;'), '"', '&quot;'), '''', '&#39;'),
+    E'\n\n', '</p><p>'), E'\n', '<br>') || '</p>';
+
+ALTER TABLE posts_by_id ALTER COLUMN body_html DROP DEFAULT;
\ No newline at end of file
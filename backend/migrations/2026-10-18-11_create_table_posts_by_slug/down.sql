@@ -0,0 +1,3 @@
+DROP TABLE posts_by_slug;
+
+ALTER TABLE posts_by_id DROP COLUMN slug;
\ No newline at end of file
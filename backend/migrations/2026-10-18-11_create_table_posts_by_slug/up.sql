@@ -0,0 +1,41 @@
+-- Old slugs are kept when titles change, so that old links still lead to the same Post. Each slug
+-- belongs to at most one Post, forever.
+CREATE TABLE posts_by_slug (
+    slug VARCHAR PRIMARY KEY,
+    post_id UUID NOT NULL REFERENCES posts_by_id (post_id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX posts_by_slug_post_id_idx ON posts_by_slug (post_id);
+
+-- Every Post has a slug, made from its title exactly as Slug::from_title() makes it (see
+-- model/post.rs). Older Posts choose first, and each Post gets the first of its candidates which
+-- is not already taken, exactly as Slug::candidates() orders them: "hello-world", then
+-- "hello-world-2", and so on. A suffixed slug can also be another Post's plain slug (e.g. a Post
+-- titled "Hello World 2"), so every candidate is checked, not just counted.
+ALTER TABLE posts_by_id ADD COLUMN slug VARCHAR NOT NULL DEFAULT '';
+
+DO $$
+DECLARE
+    post RECORD;
+    base VARCHAR;
+    candidate VARCHAR;
+    n INTEGER;
+BEGIN
+    FOR post IN SELECT post_id, title, created_at FROM posts_by_id ORDER BY created_at, post_id LOOP
+        base := coalesce(nullif(rtrim(left(trim(both '-' FROM regexp_replace(lower(post.title), '[^a-z0-9]+', '-', 'g')), 80), '-'), ''), 'post');
+        candidate := base;
+        n := 1;
+
+        WHILE EXISTS (SELECT 1 FROM posts_by_slug WHERE slug = candidate) LOOP
+            n := n + 1;
+            candidate := base || '-' || n;
+        END LOOP;
+
+        INSERT INTO posts_by_slug (slug, post_id, created_at) VALUES (candidate, post.post_id, post.created_at);
+        UPDATE posts_by_id SET slug = candidate WHERE post_id = post.post_id;
+    END LOOP;
+END
+$$;
+
+ALTER TABLE posts_by_id ALTER COLUMN slug DROP DEFAULT;
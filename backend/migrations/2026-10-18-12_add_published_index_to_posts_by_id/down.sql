@@ -0,0 +1 @@
+DROP INDEX posts_by_id_published_idx;
\ No newline at end of file
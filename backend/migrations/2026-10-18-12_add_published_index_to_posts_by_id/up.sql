@@ -0,0 +1,3 @@
+-- Site-wide feeds and the sitemap list every published Post, newest first. Feeds of one author's
+-- Posts use posts_by_id_feed_idx instead.
+CREATE INDEX posts_by_id_published_idx ON posts_by_id (published_at DESC, post_id DESC) WHERE status = 'published';
\ No newline at end of file
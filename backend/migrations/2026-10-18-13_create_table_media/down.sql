@@ -0,0 +1,3 @@
+DROP INDEX media_uploader_id_idx;
+
+DROP TABLE media;
\ No newline at end of file
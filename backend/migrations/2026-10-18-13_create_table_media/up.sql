@@ -0,0 +1,11 @@
+-- What is known about each file uploaded by a User. The contents of the files are not kept in the
+-- database, but in a media store (see MediaStoreLike), under the same id.
+CREATE TABLE media (
+    media_id UUID PRIMARY KEY,
+    uploader_id UUID NOT NULL,
+    content_type VARCHAR NOT NULL,
+    size_bytes BIGINT NOT NULL CHECK (size_bytes >= 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX media_uploader_id_idx ON media (uploader_id);
\ No newline at end of file
@@ -0,0 +1,3 @@
+DROP INDEX sessions_expires_at_idx;
+
+DROP TABLE sessions;
\ No newline at end of file
@@ -0,0 +1,14 @@
+-- Each row is one logged-in session. Only a hash of each session's token is stored, never the token
+-- itself. Expired sessions are deleted periodically (see auth/sweeper.rs), using the index.
+CREATE TABLE sessions (
+    token_hash VARCHAR PRIMARY KEY,
+    user_id UUID NOT NULL,
+    username VARCHAR NOT NULL,
+    roles TEXT[] NOT NULL,
+    display_name VARCHAR,
+    refresh_token TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX sessions_expires_at_idx ON sessions (expires_at);
\ No newline at end of file
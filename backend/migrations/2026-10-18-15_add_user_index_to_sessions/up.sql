@@ -0,0 +1,3 @@
+-- Each User can have a limited number of sessions at once. When they log in again, their oldest
+-- sessions are found (and deleted) with this index.
+CREATE INDEX sessions_user_id_created_at_idx ON sessions (user_id, created_at DESC);
\ No newline at end of file
@@ -0,0 +1 @@
+ALTER TABLE post_revisions DROP COLUMN format;
\ No newline at end of file
@@ -0,0 +1,9 @@
+-- Revisions written before this were not recorded with their format. They take the format their
+-- Post has now, which is right for every Post whose format has never changed.
+ALTER TABLE post_revisions
+    ADD COLUMN format VARCHAR NOT NULL DEFAULT 'plain' CHECK (format IN ('plain', 'markdown'));
+
+UPDATE post_revisions SET format = posts_by_id.format
+FROM posts_by_id WHERE posts_by_id.post_id = post_revisions.post_id;
+
+ALTER TABLE post_revisions ALTER COLUMN format DROP DEFAULT;
@@ -0,0 +1 @@
+ALTER TABLE sessions RENAME COLUMN encrypted_refresh_token TO refresh_token;
\ No newline at end of file
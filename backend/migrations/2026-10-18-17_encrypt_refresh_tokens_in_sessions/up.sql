@@ -0,0 +1,7 @@
+-- Refresh tokens are now encrypted with a key derived from each session's token, which is never
+-- stored (see auth/sessions/postgres.rs). Refresh tokens which were stored before then cannot be
+-- encrypted, because their sessions' tokens are unknown, so they are dropped. Those sessions still
+-- work until they expire, but can no longer be renewed with the identity provider.
+ALTER TABLE sessions RENAME COLUMN refresh_token TO encrypted_refresh_token;
+
+UPDATE sessions SET encrypted_refresh_token = NULL;
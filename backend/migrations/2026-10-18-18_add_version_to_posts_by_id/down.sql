@@ -0,0 +1 @@
+ALTER TABLE posts_by_id DROP COLUMN version;
@@ -0,0 +1,4 @@
+-- Every write to a Post increments its version, even one which changes only its status or schedule
+-- (which keep the same revision). Writers only overwrite a Post if its version is still the one they
+-- read, so concurrent writes cannot silently undo each other.
+ALTER TABLE posts_by_id ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,129 @@
+use crate::db::error::DbError;
+use crate::media::MediaError;
+use salvo::http::header::CONTENT_TYPE;
+use salvo::http::{HeaderValue, StatusCode};
+use salvo::oapi::ToSchema;
+use salvo::prelude::Json;
+use salvo::{Response, Scribe};
+use serde::Serialize;
+
+/// The name of the header which holds the unique id of each request (see main.rs).
+pub(crate) const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// One invalid field in a request body.
+#[derive(Debug, Serialize, ToSchema)]
+#[salvo(schema(name = FieldError))]
+pub(crate) struct FieldError {
+    /// A JSON pointer to the invalid field, e.g. "/0/title" for the title of the first item in a
+    /// list. See https://www.rfc-editor.org/rfc/rfc6901
+    pointer: String,
+
+    /// Why the field is invalid.
+    detail: String,
+}
+
+impl FieldError {
+    pub(crate) fn new(pointer: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self { pointer: pointer.into(), detail: detail.into() }
+    }
+}
+
+/// An error response, in the "problem details" format described by RFC 7807.
+///
+/// Every handler should report errors by rendering one of these, so that clients only need to
+/// understand a single error format. See https://www.rfc-editor.org/rfc/rfc7807
+#[derive(Debug, Serialize, ToSchema)]
+#[salvo(schema(name = ApiError))]
+pub(crate) struct ApiError {
+    /// A URI identifying the kind of problem. Always "about:blank", which means that the problem
+    /// is fully described by its HTTP status code.
+    #[serde(rename = "type")]
+    problem_type: String,
+
+    /// A short summary of the problem, which is the same for every problem with this status.
+    title: String,
+
+    /// The HTTP status code of the response.
+    status: u16,
+
+    /// A description of this particular occurrence of the problem.
+    detail: String,
+
+    /// The unique id of the request which caused the problem, to help find it in the logs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
+
+    /// Every invalid field in the request body, if the request body was invalid.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    errors: Vec<FieldError>,
+}
+
+impl ApiError {
+    pub(crate) fn new(status: StatusCode, detail: impl Into<String>) -> Self {
+        Self {
+            problem_type: String::from("about:blank"),
+            title: status.canonical_reason().unwrap_or("Unknown Error").to_string(),
+            status: status.as_u16(),
+            detail: detail.into(),
+            request_id: None,
+            errors: vec![],
+        }
+    }
+
+    pub(crate) fn bad_request(detail: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, detail)
+    }
+
+    pub(crate) fn unauthorized(detail: impl Into<String>) -> Self {
+        Self::new(StatusCode::UNAUTHORIZED, detail)
+    }
+
+    pub(crate) fn forbidden(detail: impl Into<String>) -> Self {
+        Self::new(StatusCode::FORBIDDEN, detail)
+    }
+
+    pub(crate) fn not_found(detail: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND, detail)
+    }
+
+    /// The request body was well-formed, but some of its fields are invalid.
+    pub(crate) fn invalid(detail: impl Into<String>, errors: Vec<FieldError>) -> Self {
+        Self { errors, ..Self::new(StatusCode::UNPROCESSABLE_ENTITY, detail) }
+    }
+
+    /// Describes an error from the database layer, using the status code which best fits it.
+    pub(crate) fn from_db(context: &str, e: DbError) -> Self {
+        let status = match e {
+            DbError::NotFound(_) => StatusCode::NOT_FOUND,
+            DbError::Conflict(_) => StatusCode::CONFLICT,
+            DbError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            DbError::Constraint(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            DbError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        Self::new(status, format!("{}: {}", context, e))
+    }
+
+    /// Describes an error from a media store, using the status code which best fits it.
+    pub(crate) fn from_media(context: &str, e: MediaError) -> Self {
+        let status = match e {
+            MediaError::NotFound(_) => StatusCode::NOT_FOUND,
+            MediaError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+        };
+
+        Self::new(status, format!("{}: {}", context, e))
+    }
+}
+
+impl Scribe for ApiError {
+    fn render(mut self, res: &mut Response) {
+        // the RequestId middleware adds the request id to every response, before any handler runs
+        self.request_id = res.headers().get(REQUEST_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from);
+
+        res.status_code(StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR));
+        res.render(Json(self));
+        res.headers_mut().insert(CONTENT_TYPE, HeaderValue::from_static("application/problem+json"));
+    }
+}
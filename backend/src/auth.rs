@@ -1,13 +1,23 @@
 pub(crate) mod in_memory;
 pub(crate) mod keycloak;
+pub(crate) mod sessions;
+pub(crate) mod sweeper;
 
+use crate::db::tables::users::UsersTableRow;
+use crate::auth::sessions::{ActiveSessions, SessionStore, SessionStoreLike};
+use crate::db::Database;
+use crate::model::user;
 use base64::engine::general_purpose::STANDARD;
 use base64::Engine as _;
+use chrono::{DateTime, Utc};
 use rand::prelude::*;
+use salvo::prelude::Json;
 use salvo::{Response, Scribe};
-use std::collections::HashMap;
+use serde::Serialize;
 use std::hash::Hash;
+use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
 use uuid::Uuid;
 
 /// User information is held in memory until it expires.
@@ -17,15 +27,36 @@ pub(crate) struct User {
     pub(crate) id: Uuid,
     pub(crate) roles: Vec<String>,
     pub(crate) expires_at: u64, // UNIX timestamp
+    pub(crate) display_name: Option<String>, // e.g. "Bob User", if the identity provider knows it
+    pub(crate) refresh_token: Option<String>, // to renew (or end) the session with the identity provider, if it has one
+}
+
+/// Records a User in the users table every time they log in, so that every logged-in User has a
+/// profile. Logging in does not change the profile of a User who has logged in before.
+async fn record_login(db: &Mutex<Database>, user: &User) -> Result<(), String> {
+    let row = UsersTableRow::from(user::User::new(user::UserId(user.id), user.name.clone(), user.display_name.clone()));
+
+    match db.lock().await.users().record_login(row) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(format!("error recording login: {}", e)),
+    }
 }
 
 /// A token is associated with every unique, authenticated user session.
-#[derive(PartialEq, Eq, Hash, Clone, Debug)]
-pub(crate) struct Token(String);
+#[derive(PartialEq, Eq, Hash, Clone, Debug, Serialize)]
+pub(crate) struct Token(pub(in crate::auth) String);
+
+/// What a user is given when they log in (or refresh their session): the token to send with every
+/// request, and when it stops working.
+#[derive(Debug, Serialize)]
+pub(crate) struct Session {
+    pub(crate) token: Token,
+    pub(crate) expires_at: DateTime<Utc>,
+}
 
-impl Scribe for Token {
+impl Scribe for Session {
     fn render(self, res: &mut Response) {
-        res.render(self.0);
+        res.render(Json(self));
     }
 }
 
@@ -33,23 +64,42 @@ impl Token {
     pub(crate) fn new(raw: String) -> Self {
         Self(raw)
     }
+
+    pub(crate) fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
 }
 
-/// The AuthenticatorState holds a list of currently-logged-in users in memory.
+/// How many sessions there are, for monitoring. See handlers/metrics.rs.
+pub(crate) struct SessionMetrics {
+    pub(crate) n_active: ActiveSessions, // sessions which have not expired, in every replica sharing the session store
+    pub(crate) n_evicted: u64, // sessions removed because their User logged in too many times, by this replica
+}
+
+/// The AuthenticatorState holds the currently-logged-in users, in a SessionStore.
 pub(in crate::auth) struct AuthenticatorState {
     rand: StdRng,
-    map: HashMap<Token, User>, // TODO replace with a cache? Redis? ValKey?
+    sessions: SessionStore,
+    max_sessions_per_user: usize,
+    n_evicted: u64,
 }
 
-/// AuthenticatorState is basically just a fancy HashMap which
+/// AuthenticatorState is basically just a fancy map which
 /// - auto-generates keys (Tokens) on insert
 /// - auto-removes expired values (Users) on access
+/// - auto-removes the oldest values (Users) with the same id, when there are too many of them
 impl AuthenticatorState {
 
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(sessions: SessionStore, max_sessions_per_user: usize) -> Self {
         Self {
             rand: StdRng::from_os_rng(), // https://rust-random.github.io/book/guide-rngs.html
-            map: HashMap::new(),
+            sessions,
+            max_sessions_per_user: max_sessions_per_user.max(1), // or no one could stay logged in
+            n_evicted: 0,
         }
     }
 
@@ -59,23 +109,43 @@ impl AuthenticatorState {
         Token(STANDARD.encode(&random_bytes))
     }
 
-    fn add_user(&mut self, user: User) -> Token {
+    async fn add_user(&mut self, user: User) -> Result<Session, String> {
         let token = self.generate_token(32);
-        self.map.insert(token.clone(), user);
-        token
-    }
-
-    fn get_user(&mut self, token: Token) -> Option<User> {
-        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
-        match self.map.get(&token) {
-            Some(user) if user.expires_at > now => {
-                Some(user.clone())
-            }
-            _ => {
-                self.map.remove(&token);
-                None
-            },
+        let expires_at = DateTime::from_timestamp(user.expires_at as i64, 0).unwrap_or(DateTime::<Utc>::MAX_UTC);
+        let user_id = user.id;
+        self.sessions.insert(&token, user).await?;
+
+        // the new session is the most recent, so it is never evicted
+        let n_evicted = self.sessions.remove_oldest(&user_id, self.max_sessions_per_user).await?;
+
+        if n_evicted > 0 {
+            log::debug!("evicted {} of the oldest sessions of user {}", n_evicted, user_id);
+            self.n_evicted += n_evicted as u64;
         }
+
+        Ok(Session { token, expires_at })
+    }
+
+    /// Forgets a session, whether or not it has expired, returning its User if it existed.
+    async fn remove_user(&mut self, token: &Token) -> Result<Option<User>, String> {
+        self.sessions.remove(token).await
+    }
+
+    async fn get_user(&mut self, token: Token) -> Result<Option<User>, String> {
+        match self.sessions.get(&token).await? {
+            Some(user) if user.expires_at > now() => Ok(Some(user)),
+            Some(_) => self.sessions.remove(&token).await.map(|_| None),
+            None => Ok(None),
+        }
+    }
+
+    /// Forgets every session which has expired, returning how many there were.
+    async fn remove_expired(&mut self) -> Result<usize, String> {
+        self.sessions.remove_expired(now()).await
+    }
+
+    async fn metrics(&mut self) -> Result<SessionMetrics, String> {
+        Ok(SessionMetrics { n_active: self.sessions.active(now()).await?, n_evicted: self.n_evicted })
     }
 }
 
@@ -86,35 +156,91 @@ pub(crate) enum Authenticator {
 }
 
 impl Authenticator {
-    pub(crate) fn new(mode: &str) -> Self {
+    /// Fails if the Authenticator cannot be configured, e.g. if its users file is missing.
+    pub(crate) fn new(mode: &str, users_path: &str, db: Arc<Mutex<Database>>, sessions: SessionStore, max_sessions_per_user: usize) -> Result<Self, String> {
+        let state = AuthenticatorState::new(sessions, max_sessions_per_user);
+
         match mode {
-            "keycloak" => Authenticator::Keycloak(keycloak::Authenticator::new()),
-            "in-memory" => Authenticator::InMemory(in_memory::Authenticator::new()),
-            _ => panic!("Unsupported auth mode: {}", mode),
+            "keycloak" => Ok(Authenticator::Keycloak(keycloak::Authenticator::new(db, state))),
+            "in-memory" => in_memory::Authenticator::new(db, state, users_path).map(Authenticator::InMemory),
+            _ => Err(format!("Unsupported auth mode: {}", mode)),
+        }
+    }
+
+    /// Forgets every session which has expired, returning how many there were. See sweeper.rs.
+    pub(crate) async fn remove_expired_sessions(&mut self) -> Result<usize, String> {
+        match self {
+            Authenticator::Keycloak(x) => x.state.remove_expired().await,
+            Authenticator::InMemory(x) => x.state.remove_expired().await,
+        }
+    }
+
+    pub(crate) async fn session_metrics(&mut self) -> Result<SessionMetrics, String> {
+        match self {
+            Authenticator::Keycloak(x) => x.state.metrics().await,
+            Authenticator::InMemory(x) => x.state.metrics().await,
         }
     }
 }
 
+/// Logs in with a username and password. Checking a password is slow on purpose, so the in-memory
+/// Authenticator is not kept locked while it checks one, and other requests do not wait for it.
+pub(crate) async fn login_with_password(authenticator: &Mutex<Authenticator>, username: String, password: String) -> Result<Session, String> {
+    let users = match &*authenticator.lock().await {
+        Authenticator::InMemory(x) => Some(x.users()),
+        Authenticator::Keycloak(_) => None,
+    };
+
+    let Some(users) = users else {
+        return authenticator.lock().await.login(username, password).await;
+    };
+
+    let user = in_memory::check_password(users, username, password).await?;
+
+    match &mut *authenticator.lock().await {
+        Authenticator::InMemory(x) => x.start_session(user).await,
+        Authenticator::Keycloak(_) => Err("the Authenticator changed while logging in".to_owned()),
+    }
+}
+
 /// Every Authenticator should provide
-/// - the ability to login, and
-/// - the ability to get information about a logged-in user
+/// - the ability to login,
+/// - the ability to get information about a logged-in user,
+/// - the ability to swap an unexpired token for a new one, which expires later, and
+/// - the ability to logout, which makes a token unusable before it expires
 pub(crate) trait AuthenticatorLike {
-    async fn login(&mut self, username: String, password: String) -> Result<Token, String>;
-    fn get_user(&mut self, token: Token) -> Option<User>;
+    async fn login(&mut self, username: String, password: String) -> Result<Session, String>;
+    async fn get_user(&mut self, token: Token) -> Result<Option<User>, String>;
+    async fn refresh(&mut self, token: Token) -> Result<Session, String>;
+    async fn logout(&mut self, token: Token) -> Result<(), String>;
 }
 
 impl AuthenticatorLike for Authenticator {
-    async fn login(&mut self, username: String, password: String) -> Result<Token, String> {
+    async fn login(&mut self, username: String, password: String) -> Result<Session, String> {
         match self {
             Authenticator::Keycloak(x) => x.login(username, password).await,
             Authenticator::InMemory(x) => x.login(username, password).await,
         }
     }
 
-    fn get_user(&mut self, token: Token) -> Option<User> {
+    async fn refresh(&mut self, token: Token) -> Result<Session, String> {
+        match self {
+            Authenticator::Keycloak(x) => x.refresh(token).await,
+            Authenticator::InMemory(x) => x.refresh(token).await,
+        }
+    }
+
+    async fn logout(&mut self, token: Token) -> Result<(), String> {
+        match self {
+            Authenticator::Keycloak(x) => x.logout(token).await,
+            Authenticator::InMemory(x) => x.logout(token).await,
+        }
+    }
+
+    async fn get_user(&mut self, token: Token) -> Result<Option<User>, String> {
         match self {
-            Authenticator::Keycloak(x) => x.get_user(token),
-            Authenticator::InMemory(x) => x.get_user(token),
+            Authenticator::Keycloak(x) => x.get_user(token).await,
+            Authenticator::InMemory(x) => x.get_user(token).await,
         }
     }
 }
@@ -1,67 +1,242 @@
-use crate::auth::{AuthenticatorLike, AuthenticatorState, Token, User};
-use std::fs::File;
+use crate::auth::{record_login, AuthenticatorLike, AuthenticatorState, Session, Token, User};
+use crate::db::Database;
+use argon2::password_hash::SaltString;
+use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
+use std::fs;
 use std::hash::{DefaultHasher, Hash, Hasher};
-use std::io::BufReader;
+use std::path::Path;
+use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
 use uuid::Uuid;
 
+// TODO parameterize token lifetime, currently hard-coded to 30 seconds
+const TOKEN_LIFETIME_SECONDS: u64 = 30;
+
+fn expires_at() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + TOKEN_LIFETIME_SECONDS
+}
+
+/// An Argon2 hash of a random password, with the same parameters as the hashes in users files.
+/// Logging in as a user who does not exist checks the password against this, so that it takes as
+/// long as logging in with the wrong password, and does not reveal who exists.
+const DUMMY_PASSWORD_HASH: &str = "$argon2id$v=19$m=19456,t=2,p=1$Omobe6eiBs4QK/YN3zFHqQ$6IgjQY4UXjxCQjoT/FdHV4cQN3KbHtduz+msD5kzJGA";
+
+/// A known user's password, as an Argon2 hash in the PHC string format, e.g.
+/// "$argon2id$v=19$m=19456,t=2,p=1$...". Plaintext passwords (as found in Keycloak realm exports)
+/// are hashed when they are loaded, so that every password takes as long to check as every other.
+struct Password(String);
+
+impl Password {
+    /// Hashes a plaintext password, with a random salt and the same parameters as DUMMY_PASSWORD_HASH.
+    fn hash(plain: &str) -> Result<Self, String> {
+        let salt = SaltString::encode_b64(&rand::random::<[u8; 16]>()).map_err(|e| e.to_string())?;
+
+        Argon2::default().hash_password(plain.as_bytes(), &salt)
+            .map(|hash| Self(hash.to_string()))
+            .map_err(|e| e.to_string())
+    }
+
+    /// Checks a password. Verification compares hashes in constant time, so a password cannot be
+    /// guessed one character at a time.
+    fn matches(&self, password: &str) -> bool {
+        PasswordHash::new(&self.0)
+            .is_ok_and(|hash| Argon2::default().verify_password(password.as_bytes(), &hash).is_ok())
+    }
+}
+
+/// A user who can log in, loaded from the users file.
+pub(in crate::auth) struct KnownUser {
+    username: String,
+    id: Uuid,
+    roles: Vec<String>,
+    display_name: Option<String>,
+    password: Option<Password>, // users without a password cannot log in
+}
+
+/// Loads every user who can log in from a Keycloak realm export (a .json file, like
+/// keycloak/realm-export.json) or from a users file (a .toml file, like users.example.toml).
+fn load_users(path: &str) -> Result<Vec<KnownUser>, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("unable to read users file {}: {}", path, e))?;
+
+    match Path::new(path).extension().and_then(|extension| extension.to_str()) {
+        Some("json") => {
+            let realm_export: realm_export::RealmExport = serde_json::from_str(&content)
+                .map_err(|e| format!("unable to parse realm export {}: {}", path, e))?;
+
+            realm_export.users.into_iter().map(KnownUser::try_from).collect()
+        }
+        Some("toml") => {
+            let users_file: users_file::UsersFile = toml::from_str(&content)
+                .map_err(|e| format!("unable to parse users file {}: {}", path, e))?;
+
+            users_file.users.into_iter().map(KnownUser::try_from).collect()
+        }
+        _ => Err(format!("users file {} must be a realm export (.json) or a users file (.toml)", path)),
+    }
+}
+
+impl TryFrom<realm_export::User> for KnownUser {
+    type Error = String;
+
+    fn try_from(user: realm_export::User) -> Result<Self, Self::Error> {
+
+        // fake deterministic UUIDs for sample data
+        let mut hasher = DefaultHasher::new();
+        user.hash(&mut hasher);
+
+        let display_name = match (&user.first_name, &user.last_name) {
+            (Some(first), Some(last)) => Some(format!("{} {}", first, last)),
+            (first, last) => first.clone().or(last.clone()),
+        };
+
+        let password = user.credentials.iter()
+            .find(|&cred| cred.cred_type == "password")
+            .map(|cred| Password::hash(&cred.value))
+            .transpose()
+            .map_err(|e| format!("unable to hash password for user {}: {}", user.username, e))?;
+
+        Ok(Self {
+            id: Uuid::new_v3(&Uuid::NAMESPACE_DNS, &hasher.finish().to_be_bytes()),
+            username: user.username,
+            roles: user.realm_roles,
+            display_name,
+            password,
+        })
+    }
+}
+
+impl TryFrom<users_file::User> for KnownUser {
+    type Error = String;
+
+    fn try_from(user: users_file::User) -> Result<Self, Self::Error> {
+        // reject malformed hashes now, rather than failing every login later
+        if let Err(e) = PasswordHash::new(&user.password_hash) {
+            return Err(format!("invalid password_hash for user {}: {}", user.username, e));
+        }
+
+        Ok(Self {
+            id: user.id.unwrap_or_else(|| Uuid::new_v3(&Uuid::NAMESPACE_DNS, user.username.as_bytes())),
+            username: user.username,
+            roles: user.roles,
+            display_name: user.display_name,
+            password: Some(Password(user.password_hash)),
+        })
+    }
+}
+
+/// Finds the known user with this username and password. Argon2 is slow on purpose, so this runs on
+/// a blocking thread, rather than holding up the async runtime.
+pub(in crate::auth) async fn check_password(users: Arc<Vec<KnownUser>>, username: String, password: String) -> Result<User, String> {
+    let known = tokio::task::spawn_blocking(move || {
+        let known = users.iter().find(|&user| user.username == username);
+
+        // check the password even if there is no such user (or they cannot log in), so that every
+        // failed login takes about as long as every other
+        let matches = match known.and_then(|known| known.password.as_ref()) {
+            Some(expected) => expected.matches(&password),
+            None => {
+                let _ = Password(String::from(DUMMY_PASSWORD_HASH)).matches(&password);
+                false
+            }
+        };
+
+        known.filter(|_| matches).map(|known| User {
+            name: known.username.clone(),
+            id: known.id,
+            roles: known.roles.clone(),
+            expires_at: expires_at(),
+            display_name: known.display_name.clone(),
+            refresh_token: None,
+        })
+    }).await.map_err(|e| format!("error checking password: {}", e))?;
+
+    known.ok_or_else(|| "username or password incorrect".to_owned())
+}
+
 pub(crate) struct Authenticator {
-    state: AuthenticatorState,
+    pub(in crate::auth) state: AuthenticatorState,
+    db: Arc<Mutex<Database>>, // to record Users when they log in
+    users: Arc<Vec<KnownUser>>, // shared, so passwords can be checked without this Authenticator
 }
 
 impl Authenticator {
-    pub(in crate::auth) fn new() -> Self {
-        Self { state: AuthenticatorState::new() }
+    /// Fails if the users file cannot be read or parsed.
+    pub(in crate::auth) fn new(db: Arc<Mutex<Database>>, state: AuthenticatorState, users_path: &str) -> Result<Self, String> {
+        let users = load_users(users_path)?;
+        log::info!("loaded {} users from {}", users.len(), users_path);
+        Ok(Self { state, db, users: Arc::new(users) })
     }
-}
 
-impl AuthenticatorLike for Authenticator {
-    async fn login(&mut self, username: String, password: String) -> Result<Token, String> {
+    pub(in crate::auth) fn users(&self) -> Arc<Vec<KnownUser>> {
+        Arc::clone(&self.users)
+    }
 
-        // here, we need to read realm-export.json and pull user info from there
+    /// Starts a session for a User whose password has been checked (see check_password()).
+    pub(in crate::auth) async fn start_session(&mut self, user: User) -> Result<Session, String> {
+        record_login(&self.db, &user).await?;
+        self.state.add_user(user).await
+    }
+}
 
-        // TODO inject RealmExport to make this method unit-testable?
-        let file_path = "../keycloak/realm-export.json";
-        let file = File::open(file_path).unwrap();
-        let reader = BufReader::new(file);
+impl AuthenticatorLike for Authenticator {
+    async fn login(&mut self, username: String, password: String) -> Result<Session, String> {
+        let user = check_password(self.users(), username, password).await?;
+        self.start_session(user).await
+    }
 
-        let realm_export: realm_export::RealmExport = serde_json::from_reader(reader).unwrap();
+    async fn get_user(&mut self, token: Token) -> Result<Option<User>, String> {
+        self.state.get_user(token).await
+    }
 
-        match realm_export.users.iter().find(|&user| user.username == username) {
-            None => Err("username or password incorrect".to_owned()),
+    async fn refresh(&mut self, token: Token) -> Result<Session, String> {
+        match self.state.get_user(token.clone()).await? {
+            None => Err("unrecognized or expired token".to_owned()),
             Some(user) => {
-                match user.credentials.iter().find(|&cred|
-                    cred.cred_type == "password" && cred.value == password
-                ) {
-                    None => Err("username or password incorrect".to_owned()),
-                    Some(_) => {
-
-                        // fake deterministic UUIDs for sample data
-                        let mut hasher = DefaultHasher::new();
-                        user.hash(&mut hasher);
-
-                        let user = User {
-                            name: user.username.clone(),
-                            id: Uuid::new_v3(&Uuid::NAMESPACE_DNS, &hasher.finish().to_be_bytes()),
-                            roles: user.realm_roles.clone(),
-                            // TODO parameterize token lifetime, currently hard-coded to 30 seconds
-                            expires_at: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + 30,
-                        };
-
-                        Ok(self.state.add_user(user))
-                    }
-                }
+                self.state.remove_user(&token).await?;
+                self.state.add_user(User { expires_at: expires_at(), ..user }).await
             }
         }
     }
 
-    fn get_user(&mut self, token: Token) -> Option<User> {
-        self.state.get_user(token)
+    async fn logout(&mut self, token: Token) -> Result<(), String> {
+        match self.state.remove_user(&token).await? {
+            None => Err("unrecognized token".to_owned()),
+            Some(_) => Ok(()),
+        }
+    }
+}
+
+mod users_file {
+    use serde::Deserialize;
+    use uuid::Uuid;
+
+    // example users file
+    //
+    // [[users]]
+    // username = "bob"
+    // id = "b336c148-8d9e-31d8-ba1a-7448436e1bf0" # optional, derived from the username if missing
+    // display_name = "Bob User" # optional
+    // roles = ["user"]
+    // password_hash = "$argon2id$v=19$m=19456,t=2,p=1$..."
+    #[derive(Deserialize)]
+    pub(in crate::auth) struct UsersFile {
+        pub(in crate::auth) users: Vec<User>,
+    }
+
+    #[derive(Deserialize)]
+    pub(in crate::auth) struct User {
+        pub(in crate::auth) username: String,
+        pub(in crate::auth) id: Option<Uuid>,
+        pub(in crate::auth) display_name: Option<String>,
+        pub(in crate::auth) roles: Vec<String>,
+        pub(in crate::auth) password_hash: String,
     }
 }
 
 mod realm_export {
     use serde::Deserialize;
+    use std::hash::{Hash, Hasher};
 
     #[derive(Deserialize)]
     pub(in crate::auth) struct RealmExport {
@@ -135,7 +310,7 @@ mod realm_export {
     //     description: String,
     // }
 
-    #[derive(Deserialize, Hash)]
+    #[derive(Deserialize)]
     pub(in crate::auth) struct User {
         pub(in crate::auth) username: String,
         // enabled: bool,
@@ -143,11 +318,11 @@ mod realm_export {
         // #[serde(rename = "emailVerified")]
         // email_verified: bool,
 
-        // #[serde(rename = "firstName")]
-        // first_name: String,
+        #[serde(rename = "firstName")]
+        pub(in crate::auth) first_name: Option<String>,
 
-        // #[serde(rename = "lastName")]
-        // last_name: String,
+        #[serde(rename = "lastName")]
+        pub(in crate::auth) last_name: Option<String>,
 
         // email: String,
 
@@ -157,6 +332,16 @@ mod realm_export {
         pub(in crate::auth) credentials: Vec<Credential>,
     }
 
+    // User ids are derived from this hash, so it only includes the fields which were hashed before
+    // names were added, in the same order, to keep every User's id the same
+    impl Hash for User {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.username.hash(state);
+            self.realm_roles.hash(state);
+            self.credentials.hash(state);
+        }
+    }
+
     #[derive(Deserialize, Hash)]
     pub(in crate::auth) struct Credential {
         #[serde(rename = "type")]
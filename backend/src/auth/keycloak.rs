@@ -1,20 +1,39 @@
-use crate::auth::{AuthenticatorLike, AuthenticatorState, Token, User};
+use crate::auth::{record_login, AuthenticatorLike, AuthenticatorState, Session, Token, User};
+use crate::db::Database;
 use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, TokenData, Validation};
 use reqwest::{Client, ClientBuilder};
 use serde::de::DeserializeOwned;
 use serde::Deserialize;
 use std::sync::Arc;
+use tokio::sync::Mutex;
 
+// TODO host name and port and realm here should be env vars
+const TOKEN_URL: &str = "https://subway-keycloak:8443/realms/myrealm/protocol/openid-connect/token";
+const LOGOUT_URL: &str = "https://subway-keycloak:8443/realms/myrealm/protocol/openid-connect/logout";
+
+// TODO move client credentials to config
+const CLIENT_ID: &str = "my-confidential-client";
+const CLIENT_SECRET: &str = "my-client-secret";
+
+/// The tokens Keycloak returns from its token endpoint, when logging in or refreshing a session.
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    id_token: String,
+    refresh_token: Option<String>,
+}
 
 pub(crate) struct Authenticator {
-    state: AuthenticatorState,
+    pub(in crate::auth) state: AuthenticatorState,
     client: Client,
+    db: Arc<Mutex<Database>>, // to record Users when they log in
 }
 
 impl Authenticator {
-    pub(in crate::auth) fn new() -> Self {
+    pub(in crate::auth) fn new(db: Arc<Mutex<Database>>, state: AuthenticatorState) -> Self {
         Self {
-            state: AuthenticatorState::new(),
+            state,
+            db,
             client: ClientBuilder::new()
                 .danger_accept_invalid_certs(true) // TODO FIXME do not use in production
                 .build()
@@ -58,8 +77,21 @@ impl Authenticator {
         &mut self,
         access_token: &str,
         id_token: &str,
+        refresh_token: Option<String>,
         realm: &str
-    ) -> Result<Token, String> {
+    ) -> Result<Session, String> {
+        let user = self.validate_tokens(access_token, id_token, refresh_token, realm).await?;
+        record_login(&self.db, &user).await?;
+        self.state.add_user(user).await
+    }
+
+    async fn validate_tokens(
+        &self,
+        access_token: &str,
+        id_token: &str,
+        refresh_token: Option<String>,
+        realm: &str
+    ) -> Result<User, String> {
 
         // validate token, signature, and claims (exp, aud, iss)
 
@@ -79,8 +111,7 @@ impl Authenticator {
 
         match (maybe_access_data, maybe_id_data) {
             (Ok(access_token_data), Ok(id_token_data)) => {
-                let user = keycloak::user_from(access_token_data.claims, id_token_data.claims);
-                Ok(self.state.add_user(user))
+                Ok(keycloak::user_from(access_token_data.claims, id_token_data.claims, refresh_token))
             }
             _ => {
                 Err(String::from("invalid tokens"))
@@ -90,11 +121,7 @@ impl Authenticator {
 }
 
 impl AuthenticatorLike for Authenticator {
-    async fn login(&mut self, username: String, password: String) -> Result<Token, String> {
-
-        // TODO host name and port and realm here should be env vars
-        let url = "https://subway-keycloak:8443/realms/myrealm/protocol/openid-connect/token";
-
+    async fn login(&mut self, username: String, password: String) -> Result<Session, String> {
         let client = ClientBuilder::new()
             .danger_accept_invalid_certs(true) // TODO FIXME do not use in production
             .build()
@@ -102,8 +129,8 @@ impl AuthenticatorLike for Authenticator {
 
         // TODO do away with this "direct access grant" pattern and use "Authorization Code Flow" instead
         let params = [
-            ("client_id", String::from("my-confidential-client")),
-            ("client_secret", String::from("my-client-secret")),
+            ("client_id", String::from(CLIENT_ID)),
+            ("client_secret", String::from(CLIENT_SECRET)),
             ("grant_type", String::from("password")),
             ("username", username),
             ("password", password),
@@ -111,24 +138,78 @@ impl AuthenticatorLike for Authenticator {
             ("scope", String::from("openid")),
         ];
 
-        #[derive(Deserialize)]
-        struct Response {
-            access_token: String,
-            id_token: String,
-        }
-
-        match client.post(url).form(&params).send().await.unwrap().json::<Response>().await {
+        match client.post(TOKEN_URL).form(&params).send().await.unwrap().json::<TokenResponse>().await {
             // TODO fix this hard-coded realm, below
-            Ok(r) => self.login_with_tokens(r.access_token.as_str(), r.id_token.as_str(), "myrealm").await,
+            Ok(r) => self.login_with_tokens(r.access_token.as_str(), r.id_token.as_str(), r.refresh_token, "myrealm").await,
             Err(e) => Err(format!("error parsing Keycloak response: {}", e)),
         }
     }
 
-    fn get_user(&mut self, token: Token) -> Option<User> {
-        self.state.get_user(token)
+    async fn get_user(&mut self, token: Token) -> Result<Option<User>, String> {
+        self.state.get_user(token).await
+    }
+
+    async fn refresh(&mut self, token: Token) -> Result<Session, String> {
+        let Some(user) = self.state.get_user(token.clone()).await? else {
+            return Err(String::from("unrecognized or expired token"));
+        };
+
+        let Some(refresh_token) = user.refresh_token else {
+            return Err(String::from("this session cannot be refreshed, log in again instead"));
+        };
+
+        // Keycloak rotates refresh tokens, so the old one cannot be used again after this
+        let params = [
+            ("client_id", String::from(CLIENT_ID)),
+            ("client_secret", String::from(CLIENT_SECRET)),
+            ("grant_type", String::from("refresh_token")),
+            ("refresh_token", refresh_token),
+        ];
+
+        let response = match self.client.post(TOKEN_URL).form(&params).send().await {
+            Ok(response) if response.status().is_success() => response,
+            Ok(response) => return Err(format!("Keycloak refused to refresh the session: {}", response.status())),
+            Err(e) => return Err(format!("error contacting Keycloak: {}", e)),
+        };
+
+        let tokens = match response.json::<TokenResponse>().await {
+            Ok(tokens) => tokens,
+            Err(e) => return Err(format!("error parsing Keycloak response: {}", e)),
+        };
+
+        // TODO fix this hard-coded realm, below
+        let user = self.validate_tokens(tokens.access_token.as_str(), tokens.id_token.as_str(), tokens.refresh_token, "myrealm").await?;
+
+        self.state.remove_user(&token).await?;
+        self.state.add_user(user).await
+    }
+
+    async fn logout(&mut self, token: Token) -> Result<(), String> {
+        let Some(user) = self.state.remove_user(&token).await? else {
+            return Err(String::from("unrecognized token"));
+        };
+
+        // ending the Keycloak session revokes its refresh token, so it cannot be used to log back
+        // in. The user is logged out of Subway either way, so a failure here is only logged
+        if let Some(refresh_token) = user.refresh_token {
+            let params = [
+                ("client_id", String::from(CLIENT_ID)),
+                ("client_secret", String::from(CLIENT_SECRET)),
+                ("refresh_token", refresh_token),
+            ];
+
+            match self.client.post(LOGOUT_URL).form(&params).send().await {
+                Ok(response) if response.status().is_success() => {}
+                Ok(response) => log::warn!("Keycloak refused to end the session of {}: {}", user.name, response.status()),
+                Err(e) => log::warn!("error ending the Keycloak session of {}: {}", user.name, e),
+            }
+        }
+
+        Ok(())
     }
 }
 
+#[allow(clippy::module_inception)]
 mod keycloak {
     use crate::auth::User;
     use serde::Deserialize;
@@ -167,6 +248,7 @@ mod keycloak {
         // azp: String, // authorized party (the client / app acting on behalf of the user), should be: my-confidential-client
         realm_access: Roles, // list of roles in the realm
         preferred_username: String, // the user's (mutable) username
+        name: Option<String>, // the user's full name
     }
 
     // example decoded id_token
@@ -197,7 +279,7 @@ mod keycloak {
         // preferred_username: String, // the user's (mutable) username
     }
 
-    pub(in crate::auth::keycloak) fn user_from(access_token: AccessToken, id_token: IdToken) -> User {
+    pub(in crate::auth::keycloak) fn user_from(access_token: AccessToken, id_token: IdToken, refresh_token: Option<String>) -> User {
         let expires_at = min(access_token.exp, id_token.exp);
 
         User {
@@ -205,6 +287,8 @@ mod keycloak {
             id: id_token.sub.parse().unwrap(),
             roles: access_token.realm_access.roles.clone(),
             expires_at,
+            display_name: access_token.name,
+            refresh_token,
         }
     }
 }
\ No newline at end of file
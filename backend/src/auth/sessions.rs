@@ -0,0 +1,104 @@
+pub(crate) mod in_memory;
+pub(crate) mod postgres;
+
+use crate::auth::{Token, User};
+use uuid::Uuid;
+
+/// All implemented session stores are listed here.
+pub(crate) enum SessionStore {
+    InMemory(in_memory::Impl),
+    Postgres(postgres::Impl),
+}
+
+/// The number of sessions which have not expired, or a way to count them without the session store.
+/// Sessions in a database can be counted without keeping the Authenticator locked, which would
+/// stop anyone logging in or out until the count is done.
+pub(crate) enum ActiveSessions {
+    Counted(usize),
+    InDatabase { store: postgres::Impl, now: u64 },
+}
+
+impl ActiveSessions {
+    pub(crate) async fn count(self) -> Result<usize, String> {
+        match self {
+            ActiveSessions::Counted(n) => Ok(n),
+            ActiveSessions::InDatabase { mut store, now } => store.count_active(now).await,
+        }
+    }
+}
+
+impl SessionStore {
+    /// Counts the sessions which have not expired by `now` (a UNIX timestamp), unless they are in a
+    /// database, in which case they are counted by ActiveSessions::count().
+    pub(crate) async fn active(&mut self, now: u64) -> Result<ActiveSessions, String> {
+        match self {
+            SessionStore::InMemory(x) => x.count_active(now).await.map(ActiveSessions::Counted),
+            SessionStore::Postgres(x) => Ok(ActiveSessions::InDatabase { store: x.clone(), now }),
+        }
+    }
+}
+
+/// Every session store should provide the ability to add, get, and remove the User logged in with
+/// a Token, and to remove every session which has expired, whether or not its Token is used again.
+///
+/// Expiry is checked by the AuthenticatorState, not by the store.
+pub(crate) trait SessionStoreLike {
+    async fn insert(&mut self, token: &Token, user: User) -> Result<(), String>;
+    async fn get(&mut self, token: &Token) -> Result<Option<User>, String>;
+    async fn remove(&mut self, token: &Token) -> Result<Option<User>, String>;
+
+    /// Removes every session which expired at or before `now` (a UNIX timestamp), returning how
+    /// many were removed.
+    async fn remove_expired(&mut self, now: u64) -> Result<usize, String>;
+
+    /// Removes all but the `keep` most recently added sessions of one User, returning how many
+    /// were removed.
+    async fn remove_oldest(&mut self, user_id: &Uuid, keep: usize) -> Result<usize, String>;
+
+    /// Counts the sessions which have not expired by `now` (a UNIX timestamp).
+    async fn count_active(&mut self, now: u64) -> Result<usize, String>;
+}
+
+impl SessionStoreLike for SessionStore {
+    async fn insert(&mut self, token: &Token, user: User) -> Result<(), String> {
+        match self {
+            SessionStore::InMemory(x) => x.insert(token, user).await,
+            SessionStore::Postgres(x) => x.insert(token, user).await,
+        }
+    }
+
+    async fn get(&mut self, token: &Token) -> Result<Option<User>, String> {
+        match self {
+            SessionStore::InMemory(x) => x.get(token).await,
+            SessionStore::Postgres(x) => x.get(token).await,
+        }
+    }
+
+    async fn remove(&mut self, token: &Token) -> Result<Option<User>, String> {
+        match self {
+            SessionStore::InMemory(x) => x.remove(token).await,
+            SessionStore::Postgres(x) => x.remove(token).await,
+        }
+    }
+
+    async fn remove_expired(&mut self, now: u64) -> Result<usize, String> {
+        match self {
+            SessionStore::InMemory(x) => x.remove_expired(now).await,
+            SessionStore::Postgres(x) => x.remove_expired(now).await,
+        }
+    }
+
+    async fn remove_oldest(&mut self, user_id: &Uuid, keep: usize) -> Result<usize, String> {
+        match self {
+            SessionStore::InMemory(x) => x.remove_oldest(user_id, keep).await,
+            SessionStore::Postgres(x) => x.remove_oldest(user_id, keep).await,
+        }
+    }
+
+    async fn count_active(&mut self, now: u64) -> Result<usize, String> {
+        match self {
+            SessionStore::InMemory(x) => x.count_active(now).await,
+            SessionStore::Postgres(x) => x.count_active(now).await,
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use crate::auth::sessions::SessionStoreLike;
+use crate::auth::{Token, User};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Keeps sessions in process memory, so every restart logs everyone out, and replicas cannot share
+/// sessions.
+pub(crate) struct Impl {
+    map: HashMap<Token, (u64, User)>, // each User is numbered in the order it was added
+    n_added: u64,
+}
+
+impl Impl {
+    pub(crate) fn new() -> Self {
+        Self { map: HashMap::new(), n_added: 0 }
+    }
+}
+
+impl SessionStoreLike for Impl {
+    async fn insert(&mut self, token: &Token, user: User) -> Result<(), String> {
+        self.n_added += 1;
+        self.map.insert(token.clone(), (self.n_added, user));
+        Ok(())
+    }
+
+    async fn get(&mut self, token: &Token) -> Result<Option<User>, String> {
+        Ok(self.map.get(token).map(|(_, user)| user.clone()))
+    }
+
+    async fn remove(&mut self, token: &Token) -> Result<Option<User>, String> {
+        Ok(self.map.remove(token).map(|(_, user)| user))
+    }
+
+    async fn remove_expired(&mut self, now: u64) -> Result<usize, String> {
+        let before = self.map.len();
+        self.map.retain(|_, (_, user)| user.expires_at > now);
+        Ok(before - self.map.len())
+    }
+
+    async fn remove_oldest(&mut self, user_id: &Uuid, keep: usize) -> Result<usize, String> {
+        let mut sessions: Vec<(u64, Token)> = self.map.iter()
+            .filter(|(_, (_, user))| user.id == *user_id)
+            .map(|(token, (n, _))| (*n, token.clone()))
+            .collect();
+
+        sessions.sort_by_key(|(n, _)| Reverse(*n));
+
+        let oldest: Vec<Token> = sessions.into_iter().skip(keep).map(|(_, token)| token).collect();
+        oldest.iter().for_each(|token| { self.map.remove(token); });
+        Ok(oldest.len())
+    }
+
+    async fn count_active(&mut self, now: u64) -> Result<usize, String> {
+        Ok(self.map.values().filter(|(_, user)| user.expires_at > now).count())
+    }
+}
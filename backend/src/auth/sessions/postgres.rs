@@ -0,0 +1,132 @@
+use crate::auth::sessions::SessionStoreLike;
+use crate::auth::{Token, User};
+use crate::db::error::DbError;
+use crate::db::tables::sessions::SessionsTableRow;
+use crate::db::Database;
+use aes_gcm::aead::Aead;
+use aes_gcm::{Aes256Gcm, KeyInit, Nonce};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine as _;
+use chrono::DateTime;
+use sha2::{Digest, Sha256};
+use std::fmt::Write;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// Keeps sessions in the sessions table, so they survive restarts, and are shared by every replica
+/// which uses the same database.
+#[derive(Clone)]
+pub(crate) struct Impl {
+    db: Arc<Mutex<Database>>,
+}
+
+impl Impl {
+    pub(crate) fn new(db: Arc<Mutex<Database>>) -> Self {
+        Self { db }
+    }
+}
+
+/// Tokens are random, so a fast, unsalted hash is enough to make the stored hashes useless to
+/// anyone who reads them.
+fn hash(token: &Token) -> String {
+    Sha256::digest(token.0.as_bytes()).iter().fold(String::new(), |mut hex, byte| {
+        let _ = write!(hex, "{:02x}", byte);
+        hex
+    })
+}
+
+/// Refresh tokens are encrypted with a key derived from the token of the session they belong to.
+/// Only a hash of the token is stored, so they cannot be decrypted by anyone who does not already
+/// have the token. The key is hashed differently from the stored hash, so the hash is not the key.
+fn cipher(token: &Token) -> Aes256Gcm {
+    let key = Sha256::new().chain_update(b"subway refresh token key:").chain_update(token.0.as_bytes()).finalize();
+    Aes256Gcm::new(&key)
+}
+
+/// Encrypts a refresh token, returning the (random) nonce and the ciphertext, base64-encoded.
+fn encrypt(token: &Token, refresh_token: &str) -> Result<String, String> {
+    let nonce: [u8; 12] = rand::random();
+
+    let ciphertext = cipher(token).encrypt(&Nonce::from(nonce), refresh_token.as_bytes())
+        .map_err(|_| String::from("error encrypting refresh token"))?;
+
+    Ok(STANDARD.encode([nonce.as_slice(), &ciphertext].concat()))
+}
+
+/// Decrypts a refresh token encrypted by encrypt() with the same token.
+fn decrypt(token: &Token, encrypted: &str) -> Result<String, String> {
+    let error = || String::from("error decrypting refresh token");
+    let bytes = STANDARD.decode(encrypted).map_err(|_| error())?;
+    let (nonce, ciphertext) = bytes.split_first_chunk::<12>().ok_or_else(error)?;
+    let plaintext = cipher(token).decrypt(&Nonce::from(*nonce), ciphertext).map_err(|_| error())?;
+    String::from_utf8(plaintext).map_err(|_| error())
+}
+
+/// Gets the User logged in with a session, with their decrypted refresh token.
+fn user_from(token: &Token, row: SessionsTableRow) -> Result<User, String> {
+    let (mut user, encrypted_refresh_token) = row.into_user();
+    user.refresh_token = encrypted_refresh_token.map(|encrypted| decrypt(token, &encrypted)).transpose()?;
+    Ok(user)
+}
+
+impl SessionStoreLike for Impl {
+    async fn insert(&mut self, token: &Token, user: User) -> Result<(), String> {
+        let encrypted_refresh_token = user.refresh_token.as_deref().map(|refresh_token| encrypt(token, refresh_token)).transpose()?;
+
+        self.db.lock().await.sessions().insert(SessionsTableRow::new(hash(token), user, encrypted_refresh_token))
+            .map_err(|e| format!("error storing session: {}", e))
+    }
+
+    async fn get(&mut self, token: &Token) -> Result<Option<User>, String> {
+        match self.db.lock().await.sessions().get(&hash(token)) {
+            Ok(row) => user_from(token, row).map(Some),
+            Err(DbError::NotFound(_)) => Ok(None),
+            Err(e) => Err(format!("error getting session: {}", e)),
+        }
+    }
+
+    async fn remove(&mut self, token: &Token) -> Result<Option<User>, String> {
+        match self.db.lock().await.sessions().delete(&hash(token)) {
+            Ok(row) => user_from(token, row).map(Some),
+            Err(DbError::NotFound(_)) => Ok(None),
+            Err(e) => Err(format!("error removing session: {}", e)),
+        }
+    }
+
+    async fn remove_expired(&mut self, now: u64) -> Result<usize, String> {
+        let now = DateTime::from_timestamp(now as i64, 0).unwrap_or_default();
+
+        self.db.lock().await.sessions().delete_expired(now)
+            .map_err(|e| format!("error removing expired sessions: {}", e))
+    }
+
+    async fn remove_oldest(&mut self, user_id: &Uuid, keep: usize) -> Result<usize, String> {
+        self.db.lock().await.sessions().delete_oldest(user_id, keep)
+            .map_err(|e| format!("error removing oldest sessions: {}", e))
+    }
+
+    async fn count_active(&mut self, now: u64) -> Result<usize, String> {
+        let now = DateTime::from_timestamp(now as i64, 0).unwrap_or_default();
+
+        self.db.lock().await.sessions().count_active(now)
+            .map_err(|e| format!("error counting sessions: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decrypt, encrypt, hash};
+    use crate::auth::Token;
+
+    #[test]
+    fn refresh_tokens_can_only_be_decrypted_with_their_session_token() {
+        let token = Token(String::from("session token"));
+        let encrypted = encrypt(&token, "refresh token").unwrap();
+
+        assert!(!encrypted.contains("refresh token"));
+        assert_eq!(decrypt(&token, &encrypted).unwrap(), "refresh token");
+        assert!(decrypt(&Token(String::from("another session token")), &encrypted).is_err());
+        assert!(decrypt(&Token(hash(&token)), &encrypted).is_err());
+    }
+}
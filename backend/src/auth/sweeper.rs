@@ -0,0 +1,33 @@
+use crate::auth::Authenticator;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{watch, Mutex};
+use tokio::time::MissedTickBehavior;
+
+/// Forgets expired sessions every `interval`, until `shutdown` changes. Otherwise, a session is
+/// only forgotten when its token is used after it expires, which may never happen.
+///
+/// Several replicas can run this at once, since removing an expired session twice is harmless.
+pub(crate) async fn run(authenticator: Arc<Mutex<Authenticator>>, interval: Duration, mut shutdown: watch::Receiver<bool>) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    log::info!("started session sweeper, removing expired sessions every {:?}", interval);
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => remove_expired(&authenticator).await,
+            _ = shutdown.changed() => break,
+        }
+    }
+
+    log::info!("stopped session sweeper");
+}
+
+async fn remove_expired(authenticator: &Arc<Mutex<Authenticator>>) {
+    match authenticator.lock().await.remove_expired_sessions().await {
+        Ok(0) => {}
+        Ok(n) => log::debug!("removed {} expired sessions", n),
+        Err(e) => log::error!("error removing expired sessions: {}", e),
+    }
+}
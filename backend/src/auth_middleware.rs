@@ -1,16 +1,89 @@
+use crate::api_error::ApiError;
 use crate::auth::{Authenticator, AuthenticatorLike, Token};
+use salvo::http::header::AUTHORIZATION;
+use salvo::http::Method;
 use salvo::prelude::*;
 use std::sync::Arc;
+use subtle::ConstantTimeEq;
 use tokio::sync::Mutex;
 
+/// The cookie which holds a browser's authentication token. See handlers/login.rs.
+pub(crate) const SESSION_COOKIE: &str = "subway_session";
+
+/// The cookie which holds a browser's CSRF token, which pages must copy into the CSRF_HEADER.
+pub(crate) const CSRF_COOKIE: &str = "subway_csrf";
+pub(crate) const CSRF_HEADER: &str = "x-csrf-token";
+
+/// Where a request's authentication token was found.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TokenSource {
+    Header,
+    Cookie,
+}
+
+/// Why a request's authentication token could not be read.
+pub(crate) enum TokenError {
+    MalformedAuthorization,
+    IncorrectCsrf,
+}
+
+impl Scribe for TokenError {
+    fn render(self, res: &mut Response) {
+        match self {
+            TokenError::MalformedAuthorization => res.render(ApiError::unauthorized("Malformed Authorization header, expected \"Bearer <token>\"")),
+            TokenError::IncorrectCsrf => res.render(ApiError::forbidden(format!("Missing or incorrect {} header", CSRF_HEADER))),
+        }
+    }
+}
+
+/// Reads the authentication token sent with a request, if there is one, from (in order) an
+/// "Authorization: Bearer" header, an x-token header, or the session cookie.
+///
+/// Browsers send cookies with every request, even requests made by other sites, so a request which
+/// uses the session cookie to change anything must also copy the CSRF cookie into the CSRF header.
+/// Other sites cannot read the cookie, so they cannot do that ("double-submit" CSRF protection).
+pub(crate) fn token(req: &Request) -> Result<Option<(Token, TokenSource)>, TokenError> {
+    if let Some(header) = req.header::<&str>(AUTHORIZATION) {
+        // the scheme is case-insensitive (RFC 9110)
+        return match header.split_once(' ') {
+            Some((scheme, token)) if scheme.eq_ignore_ascii_case("bearer") && !token.trim().is_empty() => Ok(Some((Token::new(String::from(token.trim())), TokenSource::Header))),
+            _ => Err(TokenError::MalformedAuthorization),
+        };
+    }
+
+    if let Some(header) = req.header::<&str>("x-token") {
+        return Ok(Some((Token::new(String::from(header)), TokenSource::Header)));
+    }
+
+    let Some(cookie) = req.cookie(SESSION_COOKIE) else { return Ok(None) };
+
+    if ![Method::GET, Method::HEAD, Method::OPTIONS].contains(req.method()) {
+        let expected = req.cookie(CSRF_COOKIE).map(|cookie| cookie.value()).unwrap_or_default();
+        let actual = req.header::<&str>(CSRF_HEADER).unwrap_or_default();
+
+        if expected.is_empty() || !bool::from(expected.as_bytes().ct_eq(actual.as_bytes())) {
+            return Err(TokenError::IncorrectCsrf);
+        }
+    }
+
+    Ok(Some((Token::new(String::from(cookie.value())), TokenSource::Cookie)))
+}
+
 #[derive(Clone)]
 pub(crate) struct Auth {
     roles: Vec<String>, // roles allowed to access this route
+    optional: bool, // if true, anonymous users can also access this route
 }
 
 impl Auth {
     pub(crate) fn new(roles: &[&str]) -> Self {
-        Self { roles: roles.iter().map(|s| s.to_string()).collect() }
+        Self { roles: roles.iter().map(|s| s.to_string()).collect(), optional: false }
+    }
+
+    /// Identifies the user (by adding them to the Depot) if they are logged in, but lets anonymous
+    /// users through, too. Use this for public routes which show more to logged-in users.
+    pub(crate) fn optional() -> Self {
+        Self { roles: vec![], optional: true }
     }
 }
 
@@ -18,36 +91,49 @@ impl Auth {
 impl Handler for Auth {
     async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
 
-        let user = match req.header::<&str>("x-token") {
-            None => {
-                res.status_code(StatusCode::UNAUTHORIZED);
-                res.render("Missing or malformed x-token header");
+        let user = match token(req) {
+            Err(e) => {
+                res.render(e);
                 return;
             }
 
-            Some(token_header) => {
-                let token = Token::new(String::from(token_header));
+            Ok(None) if self.optional => return,
+            Ok(None) => {
+                res.render(ApiError::unauthorized("Missing authentication token"));
+                return;
+            }
+
+            Ok(Some((token, _))) => {
                 let state = depot.obtain::<Arc<Mutex<Authenticator>>>().unwrap();
                 let mut auth = state.lock().await;
-                auth.get_user(token)
+
+                match auth.get_user(token).await {
+                    Ok(user) => user,
+                    Err(e) => {
+                        res.render(ApiError::new(StatusCode::SERVICE_UNAVAILABLE, format!("error checking authentication token: {}", e)));
+                        return;
+                    }
+                }
             }
         };
 
         match user {
-            Some(user) if user.roles.iter().any(|role| self.roles.contains(role)) => {
-                // TODO here we should add the roles, "sub", "preferred_username", etc. to the Depot
+            Some(user) if self.optional || user.roles.iter().any(|role| self.roles.contains(role)) => {
+                // TODO here we should add the "sub", "preferred_username", etc. to the Depot
                 depot.insert("token_user_name", user.name);
                 depot.insert("token_user_id", user.id);
+                depot.insert("token_user_roles", user.roles);
             }
 
+            // expired tokens are common on public routes, so treat them as anonymous there
+            None if self.optional => {}
+
             None => {
-                res.status_code(StatusCode::UNAUTHORIZED);
-                res.render("Unrecognized authentication token");
+                res.render(ApiError::unauthorized("Unrecognized authentication token"));
             }
 
             _ => {
-                res.status_code(StatusCode::UNAUTHORIZED);
-                res.render("User is missing required role");
+                res.render(ApiError::forbidden("User is missing required role"));
             }
         }
     }
@@ -10,6 +10,35 @@ pub(crate) struct DBConfig {
 #[derive(Debug, Deserialize)]
 pub(crate) struct AuthConfig {
     pub(crate) mode: String,
+    pub(crate) users_path: String,
+    pub(crate) session_store: String,
+    pub(crate) sweep_interval_seconds: u64,
+    pub(crate) max_sessions_per_user: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct SiteConfig {
+    pub(crate) title: String,
+    pub(crate) url: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct PostsConfig {
+    pub(crate) title_max_length: usize,
+    pub(crate) body_max_length: usize,
+    pub(crate) reaction_kinds: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct SchedulerConfig {
+    pub(crate) interval_seconds: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct MediaConfig {
+    pub(crate) store: String,
+    pub(crate) path: String,
+    pub(crate) max_size_bytes: usize,
 }
 
 #[derive(Debug, Deserialize)]
@@ -21,8 +50,12 @@ pub(crate) struct Config {
     pub(crate) tls_certificate_path: String,
     pub(crate) log_level: String,
     pub(crate) tls_key_path: String,
+    pub(crate) site: SiteConfig,
     pub(crate) db: DBConfig,
     pub(crate) auth: AuthConfig,
+    pub(crate) scheduler: SchedulerConfig,
+    pub(crate) posts: PostsConfig,
+    pub(crate) media: MediaConfig,
 }
 
 impl Config {
@@ -30,13 +63,13 @@ impl Config {
 
         // panic if we cannot read the app config file
         let toml_content = fs::read_to_string(toml_file)
-            .expect(format!("Failed to read {}", toml_file).as_str());
+            .unwrap_or_else(|_| panic!("Failed to read {}", toml_file));
 
         let config: Config = toml::from_str(&toml_content)
             .expect("Failed to parse TOML");
 
         // override with env vars
-        Config {
+        let config = Config {
             host: env::var("SUBWAY_HOST").unwrap_or(config.host),
             port: match env::var("SUBWAY_PORT").map(|s| s.parse::<u16>()) {
                 Ok(Ok(port)) => port,
@@ -46,14 +79,65 @@ impl Config {
             tls_certificate_path: env::var("SUBWAY_TLS_CERTIFICATE_PATH").unwrap_or(config.tls_certificate_path),
             tls_key_path: env::var("SUBWAY_TLS_KEY_PATH").unwrap_or(config.tls_key_path),
             log_level: env::var("RUST_LOG").unwrap_or(config.log_level),
+            site: SiteConfig {
+                title: env::var("SUBWAY_SITE_TITLE").unwrap_or(config.site.title),
+                url: env::var("SUBWAY_SITE_URL").unwrap_or(config.site.url),
+            },
             db: DBConfig {
                 mode: env::var("SUBWAY_DB_MODE").unwrap_or(config.db.mode),
                 url: env::var("SUBWAY_DB_URL").unwrap_or(config.db.url),
             },
             auth: AuthConfig {
                 mode: env::var("SUBWAY_AUTH_MODE").unwrap_or(config.auth.mode),
-            }
+                users_path: env::var("SUBWAY_AUTH_USERS_PATH").unwrap_or(config.auth.users_path),
+                session_store: env::var("SUBWAY_AUTH_SESSION_STORE").unwrap_or(config.auth.session_store),
+                sweep_interval_seconds: match env::var("SUBWAY_AUTH_SWEEP_INTERVAL_SECONDS").map(|s| s.parse::<u64>()) {
+                    Ok(Ok(seconds)) => seconds,
+                    _ => config.auth.sweep_interval_seconds
+                },
+                max_sessions_per_user: match env::var("SUBWAY_AUTH_MAX_SESSIONS_PER_USER").map(|s| s.parse::<usize>()) {
+                    Ok(Ok(n)) => n,
+                    _ => config.auth.max_sessions_per_user
+                },
+            },
+            scheduler: SchedulerConfig {
+                interval_seconds: match env::var("SUBWAY_SCHEDULER_INTERVAL_SECONDS").map(|s| s.parse::<u64>()) {
+                    Ok(Ok(seconds)) => seconds,
+                    _ => config.scheduler.interval_seconds
+                },
+            },
+            posts: PostsConfig {
+                title_max_length: match env::var("SUBWAY_POSTS_TITLE_MAX_LENGTH").map(|s| s.parse::<usize>()) {
+                    Ok(Ok(length)) => length,
+                    _ => config.posts.title_max_length
+                },
+                body_max_length: match env::var("SUBWAY_POSTS_BODY_MAX_LENGTH").map(|s| s.parse::<usize>()) {
+                    Ok(Ok(length)) => length,
+                    _ => config.posts.body_max_length
+                },
+                reaction_kinds: env::var("SUBWAY_POSTS_REACTION_KINDS").map(|s| s.split(',').map(|e| e.to_owned()).collect())
+                    .unwrap_or(config.posts.reaction_kinds),
+            },
+            media: MediaConfig {
+                store: env::var("SUBWAY_MEDIA_STORE").unwrap_or(config.media.store),
+                path: env::var("SUBWAY_MEDIA_PATH").unwrap_or(config.media.path),
+                max_size_bytes: match env::var("SUBWAY_MEDIA_MAX_SIZE_BYTES").map(|s| s.parse::<usize>()) {
+                    Ok(Ok(size)) => size,
+                    _ => config.media.max_size_bytes
+                },
+            },
+        };
+
+        // a zero interval would panic later, when the background task which uses it is started
+        if config.auth.sweep_interval_seconds == 0 {
+            panic!("auth.sweep_interval_seconds must be at least 1");
         }
+
+        if config.scheduler.interval_seconds == 0 {
+            panic!("scheduler.interval_seconds must be at least 1");
+        }
+
+        config
     }
 
     fn parse_cors_allowlist(cors_allowlist: Vec<String>) -> Vec<String> {
@@ -1,7 +1,18 @@
+use crate::db::tables::comments_by_post_id::CommentsByPostIdTableLike;
+use crate::db::tables::follows::FollowsTableLike;
+use crate::db::tables::media::MediaTableLike;
+use crate::db::tables::post_reactions::PostReactionsTableLike;
+use crate::db::tables::post_revisions::PostRevisionsTableLike;
+use crate::db::tables::post_tags::PostTagsTableLike;
 use crate::db::tables::posts_by_id::PostsByIdTableLike;
+use crate::db::tables::posts_by_slug::PostsBySlugTableLike;
+use crate::db::tables::sessions::SessionsTableLike;
+use crate::db::tables::users::UsersTableLike;
 use std::ops::DerefMut;
 
+pub(crate) mod error;
 pub(crate) mod in_memory;
+pub(crate) mod page;
 pub(crate) mod postgres;
 pub(crate) mod tables;
 mod table;
@@ -19,4 +30,242 @@ impl Database {
             Database::InMemory(inner) => inner.posts_by_id.deref_mut(),
         }
     }
-}
\ No newline at end of file
+
+    pub(crate) fn posts_by_slug(&mut self) -> &mut dyn PostsBySlugTableLike {
+        match self {
+            Database::Postgres(inner) => inner.posts_by_slug.deref_mut(),
+            Database::InMemory(inner) => inner.posts_by_slug.deref_mut(),
+        }
+    }
+
+    pub(crate) fn post_revisions(&mut self) -> &mut dyn PostRevisionsTableLike {
+        match self {
+            Database::Postgres(inner) => inner.post_revisions.deref_mut(),
+            Database::InMemory(inner) => inner.post_revisions.deref_mut(),
+        }
+    }
+
+    pub(crate) fn post_tags(&mut self) -> &mut dyn PostTagsTableLike {
+        match self {
+            Database::Postgres(inner) => inner.post_tags.deref_mut(),
+            Database::InMemory(inner) => inner.post_tags.deref_mut(),
+        }
+    }
+
+    pub(crate) fn post_reactions(&mut self) -> &mut dyn PostReactionsTableLike {
+        match self {
+            Database::Postgres(inner) => inner.post_reactions.deref_mut(),
+            Database::InMemory(inner) => inner.post_reactions.deref_mut(),
+        }
+    }
+
+    pub(crate) fn users(&mut self) -> &mut dyn UsersTableLike {
+        match self {
+            Database::Postgres(inner) => inner.users.deref_mut(),
+            Database::InMemory(inner) => inner.users.deref_mut(),
+        }
+    }
+
+    pub(crate) fn follows(&mut self) -> &mut dyn FollowsTableLike {
+        match self {
+            Database::Postgres(inner) => inner.follows.deref_mut(),
+            Database::InMemory(inner) => inner.follows.deref_mut(),
+        }
+    }
+
+    pub(crate) fn comments_by_post_id(&mut self) -> &mut dyn CommentsByPostIdTableLike {
+        match self {
+            Database::Postgres(inner) => inner.comments_by_post_id.deref_mut(),
+            Database::InMemory(inner) => inner.comments_by_post_id.deref_mut(),
+        }
+    }
+
+    pub(crate) fn media(&mut self) -> &mut dyn MediaTableLike {
+        match self {
+            Database::Postgres(inner) => inner.media.deref_mut(),
+            Database::InMemory(inner) => inner.media.deref_mut(),
+        }
+    }
+
+    pub(crate) fn sessions(&mut self) -> &mut dyn SessionsTableLike {
+        match self {
+            Database::Postgres(inner) => inner.sessions.deref_mut(),
+            Database::InMemory(inner) => inner.sessions.deref_mut(),
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use crate::db::error::DbError;
+    use crate::db::page::PageRequest;
+    use crate::db::tables::comments_by_post_id::CommentsByPostIdTableRow;
+    use crate::db::tables::posts_by_id::{PostsByIdFilter, PostsByIdTableRow, PostsByIdUpdate};
+    use crate::db::{in_memory, postgres, Database};
+    use crate::model::comment::{Comment, CommentId};
+    use crate::model::format::Format;
+    use crate::model::post::{now, AuthorId, Body, Post, PostId, Status, Title};
+    use chrono::Duration;
+    use std::sync::Mutex;
+    use uuid::Uuid;
+
+    // Databases run their migrations when they are created, so Postgres Databases are created one
+    // at a time
+    static CREATING: Mutex<()> = Mutex::new(());
+
+    // Every test runs against the in-memory Database, and also against Postgres when
+    // SUBWAY_TEST_DB_URL is set (see db/postgres.rs). Each test writes Posts by an author of its
+    // own, so tests can share a Postgres database, and deletes them when it is done.
+    fn databases() -> Vec<Database> {
+        let mut databases = vec![Database::InMemory(in_memory::Database::new())];
+
+        if let Ok(url) = std::env::var("SUBWAY_TEST_DB_URL") {
+            let _creating = CREATING.lock().unwrap();
+            databases.push(Database::Postgres(postgres::Database::new(&url)));
+        }
+
+        databases
+    }
+
+    fn insert_posts(db: &mut Database, author_id: Uuid, n: usize) -> Vec<Uuid> {
+        let rows = (0..n).map(|i| {
+            let post = Post::new(AuthorId(author_id), Title(format!("Post {}", i)), Body(String::from("body")), Format::Plain);
+            PostsByIdTableRow::from(post)
+        }).collect();
+
+        db.posts_by_id().insert(rows, vec![]).unwrap()
+    }
+
+    fn ids(rows: &[PostsByIdTableRow]) -> Vec<Uuid> {
+        rows.iter().map(PostsByIdTableRow::post_id).collect()
+    }
+
+    #[test]
+    fn both_databases_page_through_posts_oldest_first_in_both_directions() {
+        for mut db in databases() {
+            let author_id = Uuid::now_v7();
+            let created = insert_posts(&mut db, author_id, 5);
+            let filter = PostsByIdFilter { author_id: Some(author_id), ..PostsByIdFilter::default() };
+
+            let mut forwards = vec![];
+            let mut page = PageRequest::First;
+
+            loop {
+                let found = db.posts_by_id().list_page(&page, 2, &filter).unwrap();
+                forwards.extend(ids(&found.rows));
+
+                match found.next {
+                    Some(next) => page = next,
+                    None => break,
+                }
+            }
+
+            assert_eq!(forwards, created);
+
+            let mut backwards = vec![];
+            let mut page = PageRequest::Before(Uuid::max());
+
+            loop {
+                let found = db.posts_by_id().list_page(&page, 2, &filter).unwrap();
+                backwards.splice(0..0, ids(&found.rows));
+
+                match found.prev {
+                    Some(prev) => page = prev,
+                    None => break,
+                }
+            }
+
+            assert_eq!(backwards, created);
+            db.posts_by_id().delete(created).unwrap();
+        }
+    }
+
+    #[test]
+    fn both_databases_reject_stale_updates() {
+        for mut db in databases() {
+            let created = insert_posts(&mut db, Uuid::now_v7(), 1);
+            let id = created[0];
+
+            let mut first = Post::from(db.posts_by_id().get(&id).unwrap());
+            let mut second = Post::from(db.posts_by_id().get(&id).unwrap());
+
+            // publishing does not change the revision, but it still makes the second copy stale
+            first.publish();
+            db.posts_by_id().update(vec![PostsByIdUpdate::from(first)]).unwrap();
+
+            second.edit(Some(Title(String::from("Edited"))), None, None);
+            let result = db.posts_by_id().update(vec![PostsByIdUpdate::from(second)]);
+            assert!(matches!(result, Err(DbError::Conflict(_))), "{:?}", result);
+
+            let stored = Post::from(db.posts_by_id().get(&id).unwrap());
+            assert_eq!(stored.title.0, "Post 0");
+            assert_eq!(stored.revision, 1);
+            db.posts_by_id().delete(created).unwrap();
+        }
+    }
+
+    #[test]
+    fn both_databases_publish_scheduled_posts_once_they_are_due() {
+        for mut db in databases() {
+            let created = insert_posts(&mut db, Uuid::now_v7(), 1);
+            let id = created[0];
+
+            let mut post = Post::from(db.posts_by_id().get(&id).unwrap());
+            let publish_at = now() - Duration::seconds(1);
+            post.schedule(Some(publish_at)).unwrap();
+            db.posts_by_id().update(vec![PostsByIdUpdate::from(post)]).unwrap();
+
+            assert!(db.posts_by_id().publish_due(now(), 100).unwrap().contains(&id));
+
+            let stored = Post::from(db.posts_by_id().get(&id).unwrap());
+            assert_eq!(stored.status, Status::Published);
+            assert_eq!(stored.published_at, Some(publish_at));
+            assert_eq!(stored.version, 3);
+            db.posts_by_id().delete(created).unwrap();
+        }
+    }
+
+    #[test]
+    fn both_databases_reject_updates_which_repeat_a_post_and_change_nothing() {
+        for mut db in databases() {
+            let created = insert_posts(&mut db, Uuid::now_v7(), 2);
+
+            let updates = [created[0], created[1], created[0]].iter().map(|id| {
+                let mut post = Post::from(db.posts_by_id().get(id).unwrap());
+                post.edit(Some(Title(String::from("Edited"))), None, None);
+                PostsByIdUpdate::from(post)
+            }).collect();
+
+            let result = db.posts_by_id().update(updates);
+            assert!(matches!(result, Err(DbError::Conflict(_))), "{:?}", result);
+
+            for (i, id) in created.iter().enumerate() {
+                assert_eq!(Post::from(db.posts_by_id().get(id).unwrap()).title.0, format!("Post {}", i));
+                let revisions = db.post_revisions().list_page(id, &PageRequest::First, 10).unwrap();
+                assert_eq!(revisions.rows.len(), 1);
+            }
+
+            db.posts_by_id().delete(created).unwrap();
+        }
+    }
+
+    #[test]
+    fn both_databases_delete_comments_along_with_their_posts() {
+        for mut db in databases() {
+            let author_id = Uuid::now_v7();
+            let created = insert_posts(&mut db, author_id, 1);
+            let post_id = created[0];
+
+            let comment = Comment::new(PostId(post_id), AuthorId(author_id), None, Body(String::from("first")));
+            let comment_id = comment.comment_id.0;
+            let reply = Comment::new(PostId(post_id), AuthorId(author_id), Some(CommentId(comment_id)), Body(String::from("reply")));
+            let comments = vec![CommentsByPostIdTableRow::from(comment), CommentsByPostIdTableRow::from(reply)];
+            db.comments_by_post_id().insert(comments).unwrap();
+
+            db.posts_by_id().delete(created).unwrap();
+
+            assert!(db.posts_by_id().get(&post_id).is_err());
+            assert!(db.comments_by_post_id().get(&post_id, &comment_id).is_err());
+            assert!(db.comments_by_post_id().list_page(&post_id, &PageRequest::First, 10).unwrap().rows.is_empty());
+        }
+    }
+}
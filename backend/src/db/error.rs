@@ -0,0 +1,36 @@
+use std::fmt::{Display, Formatter};
+
+/// Everything that can go wrong when reading from or writing to a table, in any Database.
+///
+/// Each variant carries a human-readable description of what went wrong.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum DbError {
+    /// There is no row with the requested key.
+    NotFound(String),
+
+    /// A row with the same key already exists, or the row was changed concurrently.
+    Conflict(String),
+
+    /// The database could not be reached, or did not respond.
+    Unavailable(String),
+
+    /// The write would break some other rule of the table (a foreign key, a NOT NULL, a CHECK...)
+    Constraint(String),
+
+    /// Anything else, which is probably a bug.
+    Internal(String),
+}
+
+impl Display for DbError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DbError::NotFound(message) => write!(f, "not found: {}", message),
+            DbError::Conflict(message) => write!(f, "conflict: {}", message),
+            DbError::Unavailable(message) => write!(f, "database unavailable: {}", message),
+            DbError::Constraint(message) => write!(f, "constraint violated: {}", message),
+            DbError::Internal(message) => write!(f, "internal database error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for DbError {}
@@ -1,20 +1,63 @@
+use crate::db::in_memory::table::InMemoryTable;
+use crate::db::tables::comments_by_post_id::CommentsByPostIdTableLike;
+use crate::db::tables::follows::FollowsTableLike;
+use crate::db::tables::media::MediaTableLike;
+use crate::db::tables::post_reactions::PostReactionsTableLike;
+use crate::db::tables::post_revisions::PostRevisionsTableLike;
+use crate::db::tables::post_tags::PostTagsTableLike;
 use crate::db::tables::posts_by_id::PostsByIdTableLike;
+use crate::db::tables::posts_by_slug::PostsBySlugTableLike;
+use crate::db::tables::sessions::SessionsTableLike;
+use crate::db::tables::users::UsersTableLike;
+use std::sync::{Arc, RwLock};
 
 // defines what a 'Table' is
 pub(in crate::db) mod table;
 
+// indexes Posts for full-text search
+pub(in crate::db) mod search;
+
 // gives a list of Tables
 pub(in crate::db) mod tables;
 
 // list the Tables we want to use here
 pub(crate) struct Database {
     pub(in crate::db) posts_by_id: Box<dyn PostsByIdTableLike>,
+    pub(in crate::db) posts_by_slug: Box<dyn PostsBySlugTableLike>,
+    pub(in crate::db) post_revisions: Box<dyn PostRevisionsTableLike>,
+    pub(in crate::db) post_tags: Box<dyn PostTagsTableLike>,
+    pub(in crate::db) post_reactions: Box<dyn PostReactionsTableLike>,
+    pub(in crate::db) comments_by_post_id: Box<dyn CommentsByPostIdTableLike>,
+    pub(in crate::db) users: Box<dyn UsersTableLike>,
+    pub(in crate::db) follows: Box<dyn FollowsTableLike>,
+    pub(in crate::db) media: Box<dyn MediaTableLike>,
+    pub(in crate::db) sessions: Box<dyn SessionsTableLike>,
 }
 
 impl Database {
     pub(crate) fn new() -> Self {
+        // Revisions are written by posts_by_id (see PostRevisionsTableLike), Tags are read by
+        // posts_by_id (see PostTagsTableLike), and both are deleted along with their Posts, as are
+        // Reactions and Comments, so those tables share their rows with posts_by_id. So do Follows,
+        // which posts_by_id reads to build feeds, and slugs, which posts_by_id claims for its Posts
+        let slugs = Arc::new(RwLock::new(InMemoryTable::new()));
+        let revisions = Arc::new(RwLock::new(InMemoryTable::new()));
+        let tags = Arc::new(RwLock::new(InMemoryTable::new()));
+        let reactions = Arc::new(RwLock::new(InMemoryTable::new()));
+        let comments = Arc::new(RwLock::new(InMemoryTable::new()));
+        let follows = Arc::new(RwLock::new(InMemoryTable::new()));
+
         Database {
-            posts_by_id: Box::new(tables::posts_by_id::Impl::new()),
+            posts_by_id: Box::new(tables::posts_by_id::Impl::new(Arc::clone(&revisions), Arc::clone(&tags), Arc::clone(&reactions), Arc::clone(&comments), Arc::clone(&follows), Arc::clone(&slugs))),
+            posts_by_slug: Box::new(tables::posts_by_slug::Impl::new(slugs)),
+            post_revisions: Box::new(tables::post_revisions::Impl::new(revisions)),
+            post_tags: Box::new(tables::post_tags::Impl::new(tags)),
+            post_reactions: Box::new(tables::post_reactions::Impl::new(reactions)),
+            comments_by_post_id: Box::new(tables::comments_by_post_id::Impl::new(comments)),
+            users: Box::new(tables::users::Impl::new()),
+            follows: Box::new(tables::follows::Impl::new(follows)),
+            media: Box::new(tables::media::Impl::new()),
+            sessions: Box::new(tables::sessions::Impl::new()),
         }
     }
 }
\ No newline at end of file
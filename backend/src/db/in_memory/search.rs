@@ -0,0 +1,119 @@
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+// Postgres ranks matches in titles (weight 'A') above matches in bodies (weight 'B'), using these
+// default weights. See the migration which adds the search_vector column.
+const TITLE_WEIGHT: f32 = 1.0;
+const BODY_WEIGHT: f32 = 0.4;
+
+// Words which are too common to be worth searching for. Postgres's 'english' configuration ignores
+// these (and more), so that searching for "the rust book" finds Posts which mention "rust book".
+const STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "but", "by", "for", "if", "in", "into", "is", "it",
+    "no", "not", "of", "on", "or", "such", "that", "the", "their", "then", "there", "these", "they",
+    "this", "to", "was", "will", "with",
+];
+
+// how many words of a body are included in a snippet
+const SNIPPET_WORDS: usize = 30;
+
+/// Splits text into lowercase words, ignoring punctuation and stop words.
+///
+/// Unlike Postgres, this does not stem words, so "running" does not match "run". That's good enough
+/// for local development.
+pub(in crate::db) fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .filter(|word| !STOP_WORDS.contains(&word.as_str()))
+        .collect()
+}
+
+/// Maps every word to the Posts which contain it, so Posts can be found without scanning them all.
+pub(in crate::db) struct InvertedIndex {
+    postings: HashMap<String, HashMap<Uuid, f32>>, // word => Post => weight of that word in that Post
+    words: HashMap<Uuid, HashSet<String>>, // Post => every word it contains, to remove it later
+}
+
+impl InvertedIndex {
+    pub(in crate::db) fn new() -> Self {
+        Self { postings: HashMap::new(), words: HashMap::new() }
+    }
+
+    /// Adds a Post to the index, replacing it if it has been indexed before.
+    pub(in crate::db) fn index(&mut self, post_id: Uuid, title: &str, body: &str) {
+        self.remove(&post_id);
+
+        let weighted = tokenize(title).into_iter().map(|word| (word, TITLE_WEIGHT))
+            .chain(tokenize(body).into_iter().map(|word| (word, BODY_WEIGHT)));
+
+        let mut words = HashSet::new();
+
+        for (word, weight) in weighted {
+            *self.postings.entry(word.clone()).or_default().entry(post_id).or_default() += weight;
+            words.insert(word);
+        }
+
+        self.words.insert(post_id, words);
+    }
+
+    pub(in crate::db) fn remove(&mut self, post_id: &Uuid) {
+        for word in self.words.remove(post_id).unwrap_or_default() {
+            if let Some(posts) = self.postings.get_mut(&word) {
+                posts.remove(post_id);
+
+                if posts.is_empty() {
+                    self.postings.remove(&word);
+                }
+            }
+        }
+    }
+
+    /// Finds every Post which contains all of the words in the query, best matches first.
+    pub(in crate::db) fn search(&self, query: &str) -> Vec<(Uuid, f32)> {
+        let words = tokenize(query);
+
+        let Some((first, rest)) = words.split_first() else { return vec![] };
+
+        let mut matches: Vec<(Uuid, f32)> = self.postings.get(first).into_iter().flatten()
+            .filter_map(|(post_id, weight)| {
+                rest.iter().try_fold(*weight, |total, word| {
+                    self.postings.get(word).and_then(|posts| posts.get(post_id)).map(|weight| total + weight)
+                }).map(|total| (*post_id, total))
+            })
+            .collect();
+
+        matches.sort_by(|(a_id, a_rank), (b_id, b_rank)| b_rank.total_cmp(a_rank).then(a_id.cmp(b_id)));
+        matches
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Builds a short, HTML-escaped excerpt of a body, around the first word which matches the query,
+/// with every matching word wrapped in <mark> tags. This mimics Postgres's ts_headline().
+pub(in crate::db) fn snippet(body: &str, query: &str) -> String {
+    let query: HashSet<String> = tokenize(query).into_iter().collect();
+    let words: Vec<&str> = body.split_whitespace().collect();
+
+    let is_match = |word: &str| tokenize(word).iter().any(|token| query.contains(token));
+
+    let first_match = words.iter().position(|word| is_match(word)).unwrap_or(0);
+    let start = first_match.saturating_sub(SNIPPET_WORDS / 3);
+    let end = (start + SNIPPET_WORDS).min(words.len());
+
+    let excerpt = words[start..end].iter().map(|word| {
+        if is_match(word) {
+            format!("<mark>{}</mark>", escape_html(word))
+        } else {
+            escape_html(word)
+        }
+    }).collect::<Vec<_>>().join(" ");
+
+    let prefix = if start > 0 { "… " } else { "" };
+    let suffix = if end < words.len() { " …" } else { "" };
+
+    format!("{}{}{}", prefix, excerpt, suffix)
+}
@@ -1,42 +1,183 @@
+use crate::db::error::DbError;
+use crate::db::page;
+use crate::db::page::{Page, PageRequest};
 use crate::db::table::{Table, TableRow};
-use std::collections::HashMap;
-use std::hash::Hash;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Bound::{Excluded, Unbounded};
 
 pub(in crate::db) struct InMemoryTable<PrimaryKey, Row> {
-    data: HashMap<PrimaryKey, Row>,
+    data: BTreeMap<PrimaryKey, Row>,
 }
 
 // We add a new() function to avoid making 'data' visible
 impl<PrimaryKey, Row> InMemoryTable<PrimaryKey, Row> {
     pub(in crate::db) fn new() -> Self {
-        Self { data: HashMap::new() }
+        Self { data: BTreeMap::new() }
     }
 }
 
+/// Tables which are shared between several table implementations are kept behind a lock. If one of
+/// those locks is poisoned (because a thread panicked while holding it), the table is unusable.
+pub(in crate::db) fn poisoned<T>(_: T) -> DbError {
+    DbError::Internal(String::from("in-memory table lock was poisoned"))
+}
+
 impl<PrimaryKey, Row> Table<PrimaryKey, Row> for InMemoryTable<PrimaryKey, Row>
 where
-    PrimaryKey: Eq + Hash, // required by HashMap
+    PrimaryKey: Ord, // required by BTreeMap, which keeps rows sorted by key for pagination
     PrimaryKey: Clone, // required for insert() to take ownership of K
     Row: TableRow<PrimaryKey>,
     Row: Clone, // required to turn &V into V after calling .get()
 {
-    fn insert(&mut self, rows: Vec<Row>) -> Result<Vec<PrimaryKey>, String> {
-        rows.into_iter().try_fold(vec![], |mut vec, row| {
-            let pk = row.primary_key().clone();
+    fn insert(&mut self, rows: Vec<Row>) -> Result<Vec<PrimaryKey>, DbError> {
+        // check every key up front (like a primary key constraint in a real database would), so
+        // that a failed insert leaves the table untouched
+        let mut keys = BTreeSet::new();
+
+        if rows.iter().any(|row| self.data.contains_key(&row.primary_key()) || !keys.insert(row.primary_key())) {
+            return Err(DbError::Conflict("Key already exists".to_string()));
+        }
+
+        Ok(rows.into_iter().map(|row| {
+            let pk = row.primary_key();
             self.data.insert(pk.clone(), row);
-            vec.push(pk);
-            Ok(vec)
-        })
+            pk
+        }).collect())
     }
 
-    fn get(&self, key: &PrimaryKey) -> Result<Row, String> {
+    fn get(&self, key: &PrimaryKey) -> Result<Row, DbError> {
         match self.data.get(key) {
-            None => Err("Key not found".to_string()),
+            None => Err(DbError::NotFound("Key not found".to_string())),
             Some(value) => Ok(value.clone()),
         }
     }
 
-    fn list(&self, limit: usize) -> Result<Vec<Row>, String> {
-        Ok(self.data.values().take(limit).cloned().collect())
+    fn list_page(&self, page: &PageRequest<PrimaryKey>, limit: usize, filter: &dyn Fn(&Row) -> bool) -> Result<Page<PrimaryKey, Row>, DbError> {
+        // scan one row past the limit, to find out if there is another page after this one
+        let n_rows = limit.saturating_add(1);
+
+        let rows = match page {
+            PageRequest::First => self.data.values()
+                .filter(|row| filter(row)).take(n_rows).cloned().collect(),
+            PageRequest::After(key) => self.data.range((Excluded(key), Unbounded))
+                .map(|(_, row)| row).filter(|row| filter(row)).take(n_rows).cloned().collect(),
+            PageRequest::Before(key) => self.data.range(..key).rev()
+                .map(|(_, row)| row).filter(|row| filter(row)).take(n_rows).cloned().collect(),
+        };
+
+        Ok(page::from_scan(page, rows, limit))
+    }
+
+    fn update(&mut self, rows: Vec<Row>) -> Result<Vec<PrimaryKey>, DbError> {
+        // check every key up front, so that a failed update leaves the table untouched
+        if rows.iter().any(|row| !self.data.contains_key(&row.primary_key())) {
+            return Err(DbError::NotFound("Key not found".to_string()));
+        }
+
+        Ok(rows.into_iter().map(|row| {
+            let pk = row.primary_key();
+            self.data.insert(pk.clone(), row);
+            pk
+        }).collect())
+    }
+
+    fn delete(&mut self, keys: Vec<PrimaryKey>) -> Result<Vec<PrimaryKey>, DbError> {
+        // check every key up front, so that a failed delete leaves the table untouched. A key which
+        // is repeated is not found the second time, as in a real database
+        let mut seen = BTreeSet::new();
+
+        if keys.iter().any(|key| !self.data.contains_key(key) || !seen.insert(key)) {
+            return Err(DbError::NotFound("Key not found".to_string()));
+        }
+
+        keys.iter().for_each(|key| { self.data.remove(key); });
+        Ok(keys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InMemoryTable;
+    use crate::db::page::PageRequest;
+    use crate::db::table::{Table, TableRow};
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Row(u32);
+
+    impl TableRow<u32> for Row {
+        fn primary_key(&self) -> u32 {
+            self.0
+        }
+    }
+
+    fn table(keys: impl Iterator<Item = u32>) -> InMemoryTable<u32, Row> {
+        let mut table = InMemoryTable::new();
+        table.insert(keys.map(Row).collect()).unwrap();
+        table
+    }
+
+    fn keys(rows: &[Row]) -> Vec<u32> {
+        rows.iter().map(|row| row.0).collect()
+    }
+
+    #[test]
+    fn pages_forwards_from_the_first_row() {
+        let table = table(1..=5);
+
+        let first = table.list_page(&PageRequest::First, 2, &|_| true).unwrap();
+        assert_eq!(keys(&first.rows), vec![1, 2]);
+        assert_eq!(first.prev, None);
+        assert_eq!(first.next, Some(PageRequest::After(2)));
+
+        let second = table.list_page(&first.next.unwrap(), 2, &|_| true).unwrap();
+        assert_eq!(keys(&second.rows), vec![3, 4]);
+        assert_eq!(second.prev, Some(PageRequest::Before(3)));
+
+        let last = table.list_page(&second.next.unwrap(), 2, &|_| true).unwrap();
+        assert_eq!(keys(&last.rows), vec![5]);
+        assert_eq!(last.next, None);
+    }
+
+    #[test]
+    fn pages_backwards_in_the_same_order() {
+        let table = table(1..=5);
+
+        let last = table.list_page(&PageRequest::Before(6), 2, &|_| true).unwrap();
+        assert_eq!(keys(&last.rows), vec![4, 5]);
+        assert_eq!(last.prev, Some(PageRequest::Before(4)));
+
+        let middle = table.list_page(&last.prev.unwrap(), 2, &|_| true).unwrap();
+        assert_eq!(keys(&middle.rows), vec![2, 3]);
+        assert_eq!(middle.next, Some(PageRequest::After(3)));
+
+        let first = table.list_page(&middle.prev.unwrap(), 2, &|_| true).unwrap();
+        assert_eq!(keys(&first.rows), vec![1]);
+        assert_eq!(first.prev, None);
+    }
+
+    #[test]
+    fn only_pages_through_rows_which_match_the_filter() {
+        let table = table(1..=10);
+        let even = |row: &Row| row.0.is_multiple_of(2);
+
+        let first = table.list_page(&PageRequest::After(3), 2, &even).unwrap();
+        assert_eq!(keys(&first.rows), vec![4, 6]);
+
+        let back = table.list_page(&PageRequest::Before(4), 2, &even).unwrap();
+        assert_eq!(keys(&back.rows), vec![2]);
+        assert_eq!(back.prev, None);
+    }
+
+    #[test]
+    fn failed_writes_leave_the_table_untouched() {
+        let mut table = table(1..=3);
+
+        assert!(table.insert(vec![Row(4), Row(4)]).is_err());
+        assert!(table.update(vec![Row(3), Row(4)]).is_err());
+        assert!(table.delete(vec![1, 1]).is_err());
+        assert!(table.delete(vec![2, 4]).is_err());
+
+        let all = table.list_page(&PageRequest::First, 10, &|_| true).unwrap();
+        assert_eq!(keys(&all.rows), vec![1, 2, 3]);
     }
 }
@@ -1 +1,10 @@
-pub(in crate::db) mod posts_by_id;
\ No newline at end of file
+pub(in crate::db) mod posts_by_id;
+pub(in crate::db) mod post_revisions;
+pub(in crate::db) mod post_tags;
+pub(in crate::db) mod comments_by_post_id;
+pub(in crate::db) mod post_reactions;
+pub(in crate::db) mod users;
+pub(in crate::db) mod follows;
+pub(in crate::db) mod posts_by_slug;
+pub(in crate::db) mod media;
+pub(in crate::db) mod sessions;
\ No newline at end of file
@@ -0,0 +1,75 @@
+use crate::db::error::DbError;
+use crate::db::in_memory::table::{poisoned, InMemoryTable};
+use crate::db::page::{Page, PageRequest};
+use crate::db::table::{Table, TableRow};
+use crate::db::tables::comments_by_post_id::{by_comment_id, with_post_id, CommentsByPostIdKey, CommentsByPostIdTableLike, CommentsByPostIdTableRow};
+use std::collections::HashSet;
+use std::sync::{Arc, RwLock};
+use uuid::Uuid;
+
+/// The rows of this table are shared with the posts_by_id table, which deletes the Comments on a
+/// Post when that Post is deleted.
+pub(in crate::db) type SharedComments = Arc<RwLock<InMemoryTable<CommentsByPostIdKey, CommentsByPostIdTableRow>>>;
+
+pub(in crate::db) struct Impl {
+    delegate: SharedComments,
+}
+
+// We add a new() function to avoid making 'delegate' public
+impl Impl {
+    pub(in crate::db) fn new(delegate: SharedComments) -> Self {
+        Self { delegate }
+    }
+}
+
+impl CommentsByPostIdTableLike for Impl {
+    fn insert(&mut self, rows: Vec<CommentsByPostIdTableRow>) -> Result<Vec<CommentsByPostIdKey>, DbError> {
+        let mut table = self.delegate.write().map_err(poisoned)?;
+
+        // like the foreign key on (post_id, parent_id) in Postgres -- a reply may also be to a
+        // Comment earlier in the same batch
+        let mut existing: HashSet<CommentsByPostIdKey> = HashSet::new();
+
+        for row in &rows {
+            if let Some(parent_id) = row.parent_id {
+                let parent = (row.post_id, parent_id);
+
+                if !existing.contains(&parent) && table.get(&parent).is_err() {
+                    return Err(DbError::Constraint(format!("no Comment with id {} on Post {}", parent_id, row.post_id)));
+                }
+            }
+
+            existing.insert(row.primary_key());
+        }
+
+        table.insert(rows)
+    }
+
+    fn get(&self, post_id: &Uuid, comment_id: &Uuid) -> Result<CommentsByPostIdTableRow, DbError> {
+        self.delegate.read().map_err(poisoned)?.get(&(*post_id, *comment_id))
+    }
+
+    fn list_page(&self, post_id: &Uuid, page: &PageRequest<Uuid>, limit: usize) -> Result<Page<Uuid, CommentsByPostIdTableRow>, DbError> {
+        let table = self.delegate.read().map_err(poisoned)?;
+        let page = table.list_page(&with_post_id(*post_id, page), limit, &|row| row.post_id == *post_id)?;
+        Ok(by_comment_id(page))
+    }
+
+    fn delete(&mut self, post_id: &Uuid, comment_id: &Uuid) -> Result<CommentsByPostIdKey, DbError> {
+        let mut table = self.delegate.write().map_err(poisoned)?;
+        let key = table.get(&(*post_id, *comment_id))?.primary_key();
+
+        // like ON DELETE CASCADE in Postgres, find the replies, then the replies to those, etc.
+        let on_post = table.list_page(&PageRequest::First, usize::MAX, &|row| row.post_id == *post_id)?.rows;
+        let mut doomed = vec![key];
+        let mut i = 0;
+
+        while let Some((_, parent_id)) = doomed.get(i).copied() {
+            doomed.extend(on_post.iter().filter(|row| row.parent_id == Some(parent_id)).map(TableRow::primary_key));
+            i += 1;
+        }
+
+        table.delete(doomed)?;
+        Ok(key)
+    }
+}
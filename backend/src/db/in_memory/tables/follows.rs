@@ -0,0 +1,36 @@
+use crate::db::error::DbError;
+use crate::db::in_memory::table::{poisoned, InMemoryTable};
+use crate::db::table::{Table, TableRow};
+use crate::db::tables::follows::{FollowsKey, FollowsTableLike, FollowsTableRow};
+use std::sync::{Arc, RwLock};
+use uuid::Uuid;
+
+/// The rows of this table are shared with the posts_by_id table, which builds feeds from them.
+pub(in crate::db) type SharedFollows = Arc<RwLock<InMemoryTable<FollowsKey, FollowsTableRow>>>;
+
+pub(in crate::db) struct Impl {
+    delegate: SharedFollows,
+}
+
+// We add a new() function to avoid making 'delegate' public
+impl Impl {
+    pub(in crate::db) fn new(delegate: SharedFollows) -> Self {
+        Self { delegate }
+    }
+}
+
+impl FollowsTableLike for Impl {
+    fn insert(&mut self, row: FollowsTableRow) -> Result<bool, DbError> {
+        let mut table = self.delegate.write().map_err(poisoned)?;
+
+        if table.get(&row.primary_key()).is_ok() {
+            return Ok(false);
+        }
+
+        table.insert(vec![row]).map(|_| true)
+    }
+
+    fn delete(&mut self, follower_id: &Uuid, author_id: &Uuid) -> Result<(), DbError> {
+        self.delegate.write().map_err(poisoned)?.delete(vec![(*follower_id, *author_id)]).map(|_| ())
+    }
+}
@@ -0,0 +1,26 @@
+use crate::db::error::DbError;
+use crate::db::in_memory::table::InMemoryTable;
+use crate::db::table::Table;
+use crate::db::tables::media::{MediaTableLike, MediaTableRow};
+use uuid::Uuid;
+
+pub(in crate::db) struct Impl {
+    delegate: InMemoryTable<Uuid, MediaTableRow>,
+}
+
+// We add a new() function to avoid making 'delegate' public
+impl Impl {
+    pub(in crate::db) fn new() -> Self {
+        Self { delegate: InMemoryTable::new() }
+    }
+}
+
+impl MediaTableLike for Impl {
+    fn insert(&mut self, row: MediaTableRow) -> Result<Uuid, DbError> {
+        self.delegate.insert(vec![row]).map(|mut keys| keys.remove(0))
+    }
+
+    fn get(&self, key: &Uuid) -> Result<MediaTableRow, DbError> {
+        self.delegate.get(key)
+    }
+}
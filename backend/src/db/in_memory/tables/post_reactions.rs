@@ -0,0 +1,53 @@
+use crate::db::error::DbError;
+use crate::db::in_memory::table::{poisoned, InMemoryTable};
+use crate::db::page::PageRequest;
+use crate::db::table::{Table, TableRow};
+use crate::db::tables::post_reactions::{PostReactionsKey, PostReactionsTableLike, PostReactionsTableRow, ReactionCount};
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+use uuid::Uuid;
+
+/// The rows of this table are shared with the posts_by_id table, which deletes the Reactions to a
+/// Post when that Post is deleted.
+pub(in crate::db) type SharedReactions = Arc<RwLock<InMemoryTable<PostReactionsKey, PostReactionsTableRow>>>;
+
+pub(in crate::db) struct Impl {
+    delegate: SharedReactions,
+}
+
+// We add a new() function to avoid making 'delegate' public
+impl Impl {
+    pub(in crate::db) fn new(delegate: SharedReactions) -> Self {
+        Self { delegate }
+    }
+}
+
+impl PostReactionsTableLike for Impl {
+    fn insert(&mut self, row: PostReactionsTableRow) -> Result<bool, DbError> {
+        let mut table = self.delegate.write().map_err(poisoned)?;
+
+        if table.get(&row.primary_key()).is_ok() {
+            return Ok(false);
+        }
+
+        table.insert(vec![row]).map(|_| true)
+    }
+
+    fn delete(&mut self, post_id: &Uuid, user_id: &Uuid, kind: &str) -> Result<(), DbError> {
+        let mut table = self.delegate.write().map_err(poisoned)?;
+        table.delete(vec![(*post_id, *user_id, kind.to_string())]).map(|_| ())
+    }
+
+    fn count_for_posts(&self, post_ids: &[Uuid]) -> Result<Vec<ReactionCount>, DbError> {
+        let table = self.delegate.read().map_err(poisoned)?;
+        let rows = table.list_page(&PageRequest::First, usize::MAX, &|row| post_ids.contains(&row.post_id))?.rows;
+
+        let mut counts = BTreeMap::new();
+
+        for row in rows {
+            *counts.entry((row.post_id, row.kind)).or_insert(0) += 1;
+        }
+
+        Ok(counts.into_iter().map(|((post_id, kind), count)| ReactionCount { post_id, kind, count }).collect())
+    }
+}
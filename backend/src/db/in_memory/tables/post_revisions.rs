@@ -0,0 +1,33 @@
+use crate::db::error::DbError;
+use crate::db::in_memory::table::{poisoned, InMemoryTable};
+use crate::db::page::{Page, PageRequest};
+use crate::db::table::Table;
+use crate::db::tables::post_revisions::{by_revision_number, with_post_id, PostRevisionsKey, PostRevisionsTableLike, PostRevisionsTableRow};
+use std::sync::{Arc, RwLock};
+use uuid::Uuid;
+
+/// The rows of this table are shared with the posts_by_id table, which writes them.
+pub(in crate::db) type SharedRevisions = Arc<RwLock<InMemoryTable<PostRevisionsKey, PostRevisionsTableRow>>>;
+
+pub(in crate::db) struct Impl {
+    delegate: SharedRevisions,
+}
+
+// We add a new() function to avoid making 'delegate' public
+impl Impl {
+    pub(in crate::db) fn new(delegate: SharedRevisions) -> Self {
+        Self { delegate }
+    }
+}
+
+impl PostRevisionsTableLike for Impl {
+    fn get(&self, post_id: &Uuid, revision: i32) -> Result<PostRevisionsTableRow, DbError> {
+        self.delegate.read().map_err(poisoned)?.get(&(*post_id, revision))
+    }
+
+    fn list_page(&self, post_id: &Uuid, page: &PageRequest<i32>, limit: usize) -> Result<Page<i32, PostRevisionsTableRow>, DbError> {
+        let table = self.delegate.read().map_err(poisoned)?;
+        let page = table.list_page(&with_post_id(*post_id, page), limit, &|row| row.post_id == *post_id)?;
+        Ok(by_revision_number(page))
+    }
+}
@@ -0,0 +1,30 @@
+use crate::db::error::DbError;
+use crate::db::in_memory::table::{poisoned, InMemoryTable};
+use crate::db::page::PageRequest;
+use crate::db::table::Table;
+use crate::db::tables::post_tags::{PostTagsKey, PostTagsTableLike, PostTagsTableRow};
+use std::sync::{Arc, RwLock};
+use uuid::Uuid;
+
+/// The rows of this table are shared with the posts_by_id table, which filters and counts Posts by
+/// their Tags.
+pub(in crate::db) type SharedTags = Arc<RwLock<InMemoryTable<PostTagsKey, PostTagsTableRow>>>;
+
+pub(in crate::db) struct Impl {
+    delegate: SharedTags,
+}
+
+// We add a new() function to avoid making 'delegate' public
+impl Impl {
+    pub(in crate::db) fn new(delegate: SharedTags) -> Self {
+        Self { delegate }
+    }
+}
+
+impl PostTagsTableLike for Impl {
+    fn list_for_posts(&self, post_ids: &[Uuid]) -> Result<Vec<PostTagsTableRow>, DbError> {
+        let table = self.delegate.read().map_err(poisoned)?;
+        let page = table.list_page(&PageRequest::First, usize::MAX, &|row| post_ids.contains(&row.post_id))?;
+        Ok(page.rows)
+    }
+}
@@ -1,29 +1,303 @@
-use crate::db::in_memory::table::InMemoryTable;
-use crate::db::table::Table;
-use crate::db::tables::posts_by_id::{PostsByIdTableLike, PostsByIdTableRow};
+use crate::db::error::DbError;
+use crate::db::in_memory::search;
+use crate::db::in_memory::search::InvertedIndex;
+use crate::db::in_memory::table::{poisoned, InMemoryTable};
+use crate::db::in_memory::tables::comments_by_post_id::SharedComments;
+use crate::db::in_memory::tables::follows::SharedFollows;
+use crate::db::in_memory::tables::post_reactions::SharedReactions;
+use crate::db::in_memory::tables::post_revisions::SharedRevisions;
+use crate::db::in_memory::tables::post_tags::SharedTags;
+use crate::db::in_memory::tables::posts_by_slug::SharedSlugs;
+use crate::db::page;
+use crate::db::page::{Page, PageRequest};
+use crate::db::table::{Table, TableRow};
+use crate::db::tables::post_revisions::PostRevisionsTableRow;
+use crate::db::tables::post_tags::{PostTagsTableRow, TagCount};
+use crate::db::tables::posts_by_id::{PostsByIdFilter, PostsByIdTableLike, PostsByIdTableRow, PostsByIdUpdate, SearchResult, Visibility};
+use crate::db::tables::posts_by_id::{feed_page, FeedEntry, FeedPosition};
+use crate::db::tables::posts_by_slug::{first_free, PostsBySlugTableRow};
+use crate::model::post::{Post, Status};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use uuid::Uuid;
 
 pub(in crate::db) struct Impl {
     delegate: InMemoryTable<Uuid, PostsByIdTableRow>,
+    revisions: SharedRevisions,
+    tags: SharedTags,
+    reactions: SharedReactions,
+    comments: SharedComments,
+    follows: SharedFollows,
+    slugs: SharedSlugs,
+    index: InvertedIndex,
+}
+
+fn is_visible(visibility: &Visibility, row: &PostsByIdTableRow) -> bool {
+    match visibility {
+        Visibility::All => true,
+        Visibility::Published => row.status == Status::Published.to_string(),
+        Visibility::PublishedOrBy(author_id) => row.status == Status::Published.to_string() || row.author_id == *author_id,
+    }
+}
+
+// This must agree with the WHERE clause built by the Postgres implementation of list_page().
+// `tagged` holds the ids of the Posts with the Tag in the filter, if there is one.
+fn matches(filter: &PostsByIdFilter, tagged: Option<&HashSet<Uuid>>, row: &PostsByIdTableRow) -> bool {
+    filter.author_id.is_none_or(|author_id| row.author_id == author_id)
+        && filter.created_after.is_none_or(|after| row.created_at > after)
+        && filter.created_before.is_none_or(|before| row.created_at < before)
+        && filter.title_contains.as_ref().is_none_or(|substring| {
+            row.title.to_lowercase().contains(&substring.to_lowercase())
+        })
+        && tagged.is_none_or(|tagged| tagged.contains(&row.post_id))
+        && is_visible(&filter.visibility, row)
+}
+
+/// Sorts published Posts newest first, then picks out the requested page of them. This must agree
+/// with the ORDER BY in the Postgres implementations of feed() and published().
+fn newest_first(mut entries: Vec<FeedEntry>, page: &PageRequest<FeedPosition>, limit: usize) -> Page<FeedPosition, PostsByIdTableRow> {
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.primary_key()));
+
+    // scan one row past the limit, in the direction of the request -- see page::from_scan()
+    let n_rows = limit.saturating_add(1);
+
+    let rows = match page {
+        PageRequest::First => entries.into_iter().take(n_rows).collect(),
+        PageRequest::After(position) => entries.into_iter()
+            .filter(|entry| entry.primary_key() < *position).take(n_rows).collect(),
+        PageRequest::Before(position) => entries.into_iter().rev()
+            .filter(|entry| entry.primary_key() > *position).take(n_rows).collect(),
+    };
+
+    feed_page(page::from_scan(page, rows, limit))
 }
 
 // We add a new() function to avoid making 'delegate' public
 impl Impl {
-    pub(in crate::db) fn new() -> Self {
-        Self { delegate: InMemoryTable::new() }
+    pub(in crate::db) fn new(revisions: SharedRevisions, tags: SharedTags, reactions: SharedReactions, comments: SharedComments, follows: SharedFollows, slugs: SharedSlugs) -> Self {
+        Self { delegate: InMemoryTable::new(), revisions, tags, reactions, comments, follows, slugs, index: InvertedIndex::new() }
+    }
+
+    /// Lists every row of the post_tags table which matches the filter.
+    fn tags_where(&self, filter: &dyn Fn(&PostTagsTableRow) -> bool) -> Result<Vec<PostTagsTableRow>, DbError> {
+        let tags = self.tags.read().map_err(poisoned)?;
+        Ok(tags.list_page(&PageRequest::First, usize::MAX, filter)?.rows)
+    }
+
+    /// Records the latest Revision of each Post.
+    fn append_revisions<'a>(&self, rows: impl Iterator<Item = &'a PostsByIdTableRow>) -> Result<(), DbError> {
+        let new_revisions = rows.map(PostRevisionsTableRow::from).collect();
+        self.revisions.write().map_err(poisoned)?.insert(new_revisions).map(|_| ())
+    }
+
+    /// Gives each Post the first free slug, starting from the one it asks for, and returns the
+    /// slugs which no Post has had before. Those are only recorded once the Posts themselves are.
+    fn claim_slugs(&self, rows: &mut [PostsByIdTableRow]) -> Result<Vec<PostsBySlugTableRow>, DbError> {
+        let slugs = self.slugs.read().map_err(poisoned)?;
+        let mut claimed: HashMap<String, Uuid> = HashMap::new();
+        let mut new_slugs = vec![];
+
+        for row in rows.iter_mut() {
+            // Posts earlier in the same batch get first pick
+            let owner = |slug: &str| claimed.get(slug).copied()
+                .or_else(|| slugs.get(&slug.to_string()).ok().map(|row| row.post_id));
+
+            row.slug = first_free(&row.slug, &row.post_id, owner);
+
+            if owner(&row.slug).is_none() {
+                claimed.insert(row.slug.clone(), row.post_id);
+                new_slugs.push(PostsBySlugTableRow::new(row.slug.clone(), row.post_id));
+            }
+        }
+
+        Ok(new_slugs)
+    }
+
+    /// Replaces every Tag on the given Posts.
+    fn replace_tags(&self, post_ids: &[Uuid], tags: Vec<PostTagsTableRow>) -> Result<(), DbError> {
+        let orphans = self.tags_where(&|tag| post_ids.contains(&tag.post_id))?;
+        let mut table = self.tags.write().map_err(poisoned)?;
+        table.delete(orphans.iter().map(TableRow::primary_key).collect())?;
+        table.insert(tags).map(|_| ())
+    }
+
+    /// Keeps the search index up to date. Postgres does this itself (see the search_vector column).
+    fn reindex(&mut self, rows: &[PostsByIdTableRow]) {
+        for row in rows {
+            self.index.index(row.post_id, &row.title, &row.body);
+        }
     }
 }
 
 impl PostsByIdTableLike for Impl {
-    fn insert(&mut self, rows: Vec<PostsByIdTableRow>) -> Result<Vec<Uuid>, String> {
-        self.delegate.insert(rows)
+    fn insert(&mut self, mut rows: Vec<PostsByIdTableRow>, tags: Vec<PostTagsTableRow>) -> Result<Vec<Uuid>, DbError> {
+        let new_slugs = self.claim_slugs(&mut rows)?;
+        let keys = self.delegate.insert(rows.clone())?;
+        self.slugs.write().map_err(poisoned)?.insert(new_slugs)?;
+        self.append_revisions(rows.iter())?;
+        self.replace_tags(&keys, tags)?;
+        self.reindex(&rows);
+        Ok(keys)
     }
 
-    fn get(&self, key: &Uuid) -> Result<PostsByIdTableRow, String> {
+    fn get(&self, key: &Uuid) -> Result<PostsByIdTableRow, DbError> {
         self.delegate.get(key)
     }
 
-    fn list(&self, limit: usize) -> Result<Vec<PostsByIdTableRow>, String> {
-        self.delegate.list(limit)
+    fn list_page(&self, page: &PageRequest<Uuid>, limit: usize, filter: &PostsByIdFilter) -> Result<Page<Uuid, PostsByIdTableRow>, DbError> {
+        let tagged = match &filter.tag {
+            None => None,
+            Some(tag) => Some(self.tags_where(&|row| row.tag == *tag)?.into_iter().map(|row| row.post_id).collect()),
+        };
+
+        self.delegate.list_page(page, limit, &|row| matches(filter, tagged.as_ref(), row))
+    }
+
+    fn update(&mut self, updates: Vec<PostsByIdUpdate>) -> Result<Vec<Uuid>, DbError> {
+        // Check everything up front, so that a failed update leaves every table untouched, like the
+        // rolled back transaction of the Postgres implementation of update(). The version check
+        // must agree with its WHERE clause.
+        let mut seen = HashSet::new();
+
+        for update in &updates {
+            let stored = self.delegate.get(&update.row.post_id)?;
+
+            // in Postgres, the second update of the same Post would find it at a newer version
+            if !seen.insert(stored.post_id) {
+                return Err(DbError::Conflict(format!("Post {} cannot be updated more than once at a time", stored.post_id)));
+            }
+
+            if stored.version != update.read_version {
+                return Err(DbError::Conflict(format!("Post {} was changed by someone else, and is now at version {}", stored.post_id, stored.version)));
+            }
+        }
+
+        let edited: HashSet<Uuid> = updates.iter().filter(|update| update.is_edit()).map(|update| update.row.post_id).collect();
+        let (mut rows, tags): (Vec<_>, Vec<_>) = updates.into_iter().map(|update| (update.row, update.tags)).unzip();
+
+        let new_revisions: Vec<PostRevisionsTableRow> = rows.iter().filter(|row| edited.contains(&row.post_id)).map(PostRevisionsTableRow::from).collect();
+        let revisions = self.revisions.read().map_err(poisoned)?;
+        let recorded = new_revisions.iter().find(|revision| revisions.get(&revision.primary_key()).is_ok());
+
+        if let Some(revision) = recorded {
+            return Err(DbError::Conflict(format!("Revision {} of Post {} has already been recorded", revision.revision, revision.post_id)));
+        }
+
+        drop(revisions); // it is written below
+
+        let new_slugs = self.claim_slugs(&mut rows)?;
+        let retagged: Vec<Uuid> = rows.iter().zip(&tags).filter(|(_, tags)| tags.is_some()).map(|(row, _)| row.post_id).collect();
+
+        // nothing below can fail, except on a poisoned lock
+        let keys = self.delegate.update(rows.clone())?;
+        self.slugs.write().map_err(poisoned)?.insert(new_slugs)?;
+        self.revisions.write().map_err(poisoned)?.insert(new_revisions)?;
+        self.replace_tags(&retagged, tags.into_iter().flatten().flatten().collect())?;
+        self.reindex(&rows);
+        Ok(keys)
+    }
+
+    fn delete(&mut self, keys: Vec<Uuid>) -> Result<Vec<Uuid>, DbError> {
+        let keys = self.delegate.delete(keys)?;
+
+        for key in &keys {
+            self.index.remove(key);
+        }
+
+        // like ON DELETE CASCADE in Postgres
+        let mut revisions = self.revisions.write().map_err(poisoned)?;
+        let orphans = revisions.list_page(&PageRequest::First, usize::MAX, &|revision| keys.contains(&revision.post_id))?;
+        revisions.delete(orphans.rows.iter().map(TableRow::primary_key).collect())?;
+
+        let orphans = self.tags_where(&|tag| keys.contains(&tag.post_id))?;
+        self.tags.write().map_err(poisoned)?.delete(orphans.iter().map(TableRow::primary_key).collect())?;
+
+        let mut reactions = self.reactions.write().map_err(poisoned)?;
+        let orphans = reactions.list_page(&PageRequest::First, usize::MAX, &|reaction| keys.contains(&reaction.post_id))?;
+        reactions.delete(orphans.rows.iter().map(TableRow::primary_key).collect())?;
+
+        let mut comments = self.comments.write().map_err(poisoned)?;
+        let orphans = comments.list_page(&PageRequest::First, usize::MAX, &|comment| keys.contains(&comment.post_id))?;
+        comments.delete(orphans.rows.iter().map(TableRow::primary_key).collect())?;
+
+        let mut slugs = self.slugs.write().map_err(poisoned)?;
+        let orphans = slugs.list_page(&PageRequest::First, usize::MAX, &|slug| keys.contains(&slug.post_id))?;
+        slugs.delete(orphans.rows.iter().map(TableRow::primary_key).collect())?;
+
+        Ok(keys)
+    }
+
+    fn publish_due(&mut self, now: DateTime<Utc>, limit: usize) -> Result<Vec<Uuid>, DbError> {
+        // there is only one replica of an in-memory database, so no locking is needed here
+        let page = self.delegate.list_page(&PageRequest::First, limit, &|row| {
+            row.status != Status::Published.to_string() && row.publish_at.is_some_and(|at| at <= now)
+        })?;
+
+        let rows = page.rows.into_iter().filter_map(|row| {
+            let mut post = Post::from(row);
+            post.publish_if_due(now).then(|| PostsByIdUpdate::from(post))
+        }).collect();
+
+        self.update(rows)
+    }
+
+    fn tag_counts(&self, visibility: &Visibility) -> Result<Vec<TagCount>, DbError> {
+        let mut counts = BTreeMap::new();
+
+        for row in self.tags_where(&|_| true)? {
+            if self.delegate.get(&row.post_id).is_ok_and(|post| is_visible(visibility, &post)) {
+                *counts.entry(row.tag).or_insert(0) += 1;
+            }
+        }
+
+        Ok(counts.into_iter().map(|(tag, post_count)| TagCount { tag, post_count }).collect())
+    }
+
+    fn search(&self, query: &str, visibility: &Visibility, limit: usize) -> Result<Vec<SearchResult>, DbError> {
+        let mut results = vec![];
+
+        for (post_id, rank) in self.index.search(query) {
+            if results.len() >= limit {
+                break;
+            }
+
+            let post = self.delegate.get(&post_id)?;
+
+            if is_visible(visibility, &post) {
+                let snippet = search::snippet(&post.body, query);
+                results.push(SearchResult { post, rank, snippet });
+            }
+        }
+
+        Ok(results)
+    }
+
+    fn feed(&self, follower_id: &Uuid, page: &PageRequest<FeedPosition>, limit: usize) -> Result<Page<FeedPosition, PostsByIdTableRow>, DbError> {
+        let followed: Vec<Uuid> = {
+            let follows = self.follows.read().map_err(poisoned)?;
+            follows.list_page(&PageRequest::First, usize::MAX, &|row| row.follower_id == *follower_id)?
+                .rows.into_iter().map(|row| row.author_id).collect()
+        };
+
+        // fan out on read: gather the published Posts of each followed author, then merge them
+        let mut entries = vec![];
+
+        for author_id in followed {
+            let posts = self.delegate.list_page(&PageRequest::First, usize::MAX, &|row| {
+                row.author_id == author_id && row.status == Status::Published.to_string()
+            })?;
+
+            entries.extend(posts.rows.into_iter().map(FeedEntry));
+        }
+
+        Ok(newest_first(entries, page, limit))
+    }
+
+    fn published(&self, author_id: Option<&Uuid>, page: &PageRequest<FeedPosition>, limit: usize) -> Result<Page<FeedPosition, PostsByIdTableRow>, DbError> {
+        let posts = self.delegate.list_page(&PageRequest::First, usize::MAX, &|row| {
+            author_id.is_none_or(|author_id| row.author_id == *author_id) && row.status == Status::Published.to_string()
+        })?;
+
+        Ok(newest_first(posts.rows.into_iter().map(FeedEntry).collect(), page, limit))
     }
 }
\ No newline at end of file
@@ -0,0 +1,26 @@
+use crate::db::error::DbError;
+use crate::db::in_memory::table::{poisoned, InMemoryTable};
+use crate::db::table::Table;
+use crate::db::tables::posts_by_slug::{PostsBySlugTableLike, PostsBySlugTableRow};
+use std::sync::{Arc, RwLock};
+
+/// The rows of this table are shared with the posts_by_id table, which claims slugs for Posts as
+/// they are written. The table is keyed by slug, so it doubles as the unique index on slugs.
+pub(in crate::db) type SharedSlugs = Arc<RwLock<InMemoryTable<String, PostsBySlugTableRow>>>;
+
+pub(in crate::db) struct Impl {
+    delegate: SharedSlugs,
+}
+
+// We add a new() function to avoid making 'delegate' public
+impl Impl {
+    pub(in crate::db) fn new(delegate: SharedSlugs) -> Self {
+        Self { delegate }
+    }
+}
+
+impl PostsBySlugTableLike for Impl {
+    fn get(&self, slug: &str) -> Result<PostsBySlugTableRow, DbError> {
+        self.delegate.read().map_err(poisoned)?.get(&slug.to_string())
+    }
+}
@@ -0,0 +1,53 @@
+use crate::db::error::DbError;
+use crate::db::in_memory::table::InMemoryTable;
+use crate::db::page::PageRequest;
+use crate::db::table::Table;
+use crate::db::tables::sessions::{SessionsTableLike, SessionsTableRow};
+use chrono::{DateTime, Utc};
+use std::cmp::Reverse;
+use uuid::Uuid;
+
+pub(in crate::db) struct Impl {
+    delegate: InMemoryTable<String, SessionsTableRow>,
+}
+
+// We add a new() function to avoid making 'delegate' public
+impl Impl {
+    pub(in crate::db) fn new() -> Self {
+        Self { delegate: InMemoryTable::new() }
+    }
+}
+
+impl SessionsTableLike for Impl {
+    fn insert(&mut self, row: SessionsTableRow) -> Result<(), DbError> {
+        self.delegate.insert(vec![row]).map(|_| ())
+    }
+
+    fn get(&self, token_hash: &str) -> Result<SessionsTableRow, DbError> {
+        self.delegate.get(&token_hash.to_owned())
+    }
+
+    fn delete(&mut self, token_hash: &str) -> Result<SessionsTableRow, DbError> {
+        let row = self.delegate.get(&token_hash.to_owned())?;
+        self.delegate.delete(vec![row.token_hash.clone()]).map(|_| row)
+    }
+
+    fn delete_expired(&mut self, now: DateTime<Utc>) -> Result<usize, DbError> {
+        let expired = self.delegate.list_page(&PageRequest::First, usize::MAX, &|row| row.expires_at <= now)?;
+        let keys = expired.rows.into_iter().map(|row| row.token_hash).collect();
+        self.delegate.delete(keys).map(|keys| keys.len())
+    }
+
+    fn delete_oldest(&mut self, user_id: &Uuid, keep: usize) -> Result<usize, DbError> {
+        let mut rows = self.delegate.list_page(&PageRequest::First, usize::MAX, &|row| row.user_id == *user_id)?.rows;
+        rows.sort_by_key(|row| Reverse((row.created_at, row.token_hash.clone())));
+
+        let keys: Vec<String> = rows.into_iter().skip(keep).map(|row| row.token_hash).collect();
+        self.delegate.delete(keys).map(|keys| keys.len())
+    }
+
+    fn count_active(&self, now: DateTime<Utc>) -> Result<usize, DbError> {
+        let active = self.delegate.list_page(&PageRequest::First, usize::MAX, &|row| row.expires_at > now)?;
+        Ok(active.rows.len())
+    }
+}
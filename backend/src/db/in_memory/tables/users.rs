@@ -0,0 +1,42 @@
+use crate::db::error::DbError;
+use crate::db::in_memory::table::InMemoryTable;
+use crate::db::page::PageRequest;
+use crate::db::table::{Table, TableRow};
+use crate::db::tables::users::{UsersTableLike, UsersTableRow};
+use uuid::Uuid;
+
+pub(in crate::db) struct Impl {
+    delegate: InMemoryTable<Uuid, UsersTableRow>,
+}
+
+// We add a new() function to avoid making 'delegate' public
+impl Impl {
+    pub(in crate::db) fn new() -> Self {
+        Self { delegate: InMemoryTable::new() }
+    }
+}
+
+impl UsersTableLike for Impl {
+    fn record_login(&mut self, row: UsersTableRow) -> Result<Uuid, DbError> {
+        match self.delegate.get(&row.primary_key()) {
+            Err(_) => self.delegate.insert(vec![row]).map(|mut keys| keys.remove(0)),
+            Ok(existing) => {
+                let updated = UsersTableRow { username: row.username, last_login_at: row.last_login_at, ..existing };
+                self.delegate.update(vec![updated]).map(|mut keys| keys.remove(0))
+            }
+        }
+    }
+
+    fn get(&self, key: &Uuid) -> Result<UsersTableRow, DbError> {
+        self.delegate.get(key)
+    }
+
+    fn list_for_ids(&self, keys: &[Uuid]) -> Result<Vec<UsersTableRow>, DbError> {
+        let page = self.delegate.list_page(&PageRequest::First, usize::MAX, &|row| keys.contains(&row.user_id))?;
+        Ok(page.rows)
+    }
+
+    fn update(&mut self, row: UsersTableRow) -> Result<Uuid, DbError> {
+        self.delegate.update(vec![row]).map(|mut keys| keys.remove(0))
+    }
+}
@@ -0,0 +1,59 @@
+use crate::db::table::TableRow;
+
+/// Identifies one page of rows in a table. Rows are always sorted by their primary keys, so a page
+/// can be located relative to the primary key of a row on a neighbouring page.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum PageRequest<PrimaryKey> {
+    /// The first page of rows in the table.
+    First,
+
+    /// The page of rows immediately after the row with this primary key.
+    After(PrimaryKey),
+
+    /// The page of rows immediately before the row with this primary key.
+    Before(PrimaryKey),
+}
+
+/// One page of rows from a table, plus the requests needed to fetch the neighbouring pages.
+#[derive(Debug)]
+pub(crate) struct Page<PrimaryKey, Row> {
+    pub(crate) rows: Vec<Row>,
+    pub(crate) next: Option<PageRequest<PrimaryKey>>,
+    pub(crate) prev: Option<PageRequest<PrimaryKey>>,
+}
+
+/// Builds a Page from the rows found by scanning a table in the direction of the request.
+///
+/// Tables should scan up to `limit + 1` rows: forwards (ascending) for First and After, backwards
+/// (descending) for Before. The extra row is dropped, and only tells us whether there is another
+/// page beyond this one.
+pub(in crate::db) fn from_scan<PrimaryKey, Row>(
+    request: &PageRequest<PrimaryKey>,
+    mut rows: Vec<Row>,
+    limit: usize,
+) -> Page<PrimaryKey, Row>
+where
+    Row: TableRow<PrimaryKey>,
+{
+    let has_more = rows.len() > limit;
+    rows.truncate(limit);
+
+    let (has_next, has_prev) = match request {
+        PageRequest::First => (has_more, false),
+        PageRequest::After(_) => (has_more, true),
+        PageRequest::Before(_) => {
+            rows.reverse();
+            (true, has_more)
+        }
+    };
+
+    let next = rows.last()
+        .filter(|_| has_next)
+        .map(|row| PageRequest::After(row.primary_key()));
+
+    let prev = rows.first()
+        .filter(|_| has_prev)
+        .map(|row| PageRequest::Before(row.primary_key()));
+
+    Page { rows, next, prev }
+}
@@ -1,5 +1,16 @@
+use crate::db::error::DbError;
+use crate::db::tables::comments_by_post_id::CommentsByPostIdTableLike;
+use crate::db::tables::follows::FollowsTableLike;
+use crate::db::tables::media::MediaTableLike;
+use crate::db::tables::post_reactions::PostReactionsTableLike;
+use crate::db::tables::post_revisions::PostRevisionsTableLike;
+use crate::db::tables::post_tags::PostTagsTableLike;
 use crate::db::tables::posts_by_id::PostsByIdTableLike;
-use diesel::r2d2::{ConnectionManager, Pool};
+use crate::db::tables::posts_by_slug::PostsBySlugTableLike;
+use crate::db::tables::sessions::SessionsTableLike;
+use crate::db::tables::users::UsersTableLike;
+use diesel::r2d2::{ConnectionManager, Pool, PoolError};
+use diesel::result::{DatabaseErrorKind, Error};
 use diesel::PgConnection;
 use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
 use std::sync::Arc;
@@ -7,9 +18,20 @@ use std::sync::Arc;
 // gives a list of Tables
 pub(in crate::db) mod tables;
 
+const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
+
 // list the Tables we want to use here
 pub(crate) struct Database {
     pub(in crate::db) posts_by_id: Box<dyn PostsByIdTableLike>,
+    pub(in crate::db) posts_by_slug: Box<dyn PostsBySlugTableLike>,
+    pub(in crate::db) post_revisions: Box<dyn PostRevisionsTableLike>,
+    pub(in crate::db) post_tags: Box<dyn PostTagsTableLike>,
+    pub(in crate::db) post_reactions: Box<dyn PostReactionsTableLike>,
+    pub(in crate::db) comments_by_post_id: Box<dyn CommentsByPostIdTableLike>,
+    pub(in crate::db) users: Box<dyn UsersTableLike>,
+    pub(in crate::db) follows: Box<dyn FollowsTableLike>,
+    pub(in crate::db) media: Box<dyn MediaTableLike>,
+    pub(in crate::db) sessions: Box<dyn SessionsTableLike>,
 }
 
 impl Database {
@@ -28,8 +50,6 @@ impl Database {
         match pool {
             Err(_) => panic!("Database Pool Creation failed"),
             Ok(pool) => {
-                const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
-
                 match pool.get() {
                     Ok(mut connection) => connection.run_pending_migrations(MIGRATIONS).unwrap(),
                     Err(_) => panic!("Unable to run database migrations!"),
@@ -39,8 +59,100 @@ impl Database {
 
                 Database {
                     posts_by_id: Box::new(tables::posts_by_id::Impl { connection_pool: Arc::clone(&arc_pool) }),
+                    posts_by_slug: Box::new(tables::posts_by_slug::Impl { connection_pool: Arc::clone(&arc_pool) }),
+                    post_revisions: Box::new(tables::post_revisions::Impl { connection_pool: Arc::clone(&arc_pool) }),
+                    post_tags: Box::new(tables::post_tags::Impl { connection_pool: Arc::clone(&arc_pool) }),
+                    post_reactions: Box::new(tables::post_reactions::Impl { connection_pool: Arc::clone(&arc_pool) }),
+                    comments_by_post_id: Box::new(tables::comments_by_post_id::Impl { connection_pool: Arc::clone(&arc_pool) }),
+                    users: Box::new(tables::users::Impl { connection_pool: Arc::clone(&arc_pool) }),
+                    follows: Box::new(tables::follows::Impl { connection_pool: Arc::clone(&arc_pool) }),
+                    media: Box::new(tables::media::Impl { connection_pool: Arc::clone(&arc_pool) }),
+                    sessions: Box::new(tables::sessions::Impl { connection_pool: Arc::clone(&arc_pool) }),
+                }
+            }
+        }
+    }
+}
+
+impl From<Error> for DbError {
+    fn from(value: Error) -> Self {
+        match value {
+            Error::NotFound => DbError::NotFound(value.to_string()),
+            Error::DatabaseError(kind, ref info) => {
+                let message = info.message().to_string();
+                match kind {
+                    DatabaseErrorKind::UniqueViolation => DbError::Conflict(message),
+                    DatabaseErrorKind::SerializationFailure => DbError::Conflict(message),
+                    DatabaseErrorKind::ForeignKeyViolation => DbError::Constraint(message),
+                    DatabaseErrorKind::NotNullViolation => DbError::Constraint(message),
+                    DatabaseErrorKind::CheckViolation => DbError::Constraint(message),
+                    DatabaseErrorKind::ClosedConnection => DbError::Unavailable(message),
+                    _ => DbError::Internal(message),
                 }
             }
+            _ => DbError::Internal(value.to_string()),
+        }
+    }
+}
+
+impl From<PoolError> for DbError {
+    fn from(value: PoolError) -> Self {
+        DbError::Unavailable(format!("Unable to connect to DB: {}", value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MIGRATIONS;
+    use diesel::connection::SimpleConnection;
+    use diesel::sql_types::Text;
+    use diesel::{sql_query, Connection, PgConnection, QueryableByName, RunQueryDsl};
+    use diesel_migrations::MigrationHarness;
+
+    #[derive(QueryableByName)]
+    struct SlugRow {
+        #[diesel(sql_type = Text)]
+        slug: String,
+    }
+
+    // Migrations can only be tested against a real Postgres database, so this test only runs when
+    // SUBWAY_TEST_DB_URL is set, e.g. to postgres://postgres_user@localhost:5432/postgres_db. It
+    // migrates a schema of its own, which it drops when it is done.
+    #[test]
+    fn slug_migration_skips_suffixes_which_are_already_slugs() {
+        let Ok(url) = std::env::var("SUBWAY_TEST_DB_URL") else {
+            eprintln!("SUBWAY_TEST_DB_URL is not set, so the slug migration is not tested");
+            return;
+        };
+
+        let mut conn = PgConnection::establish(&url).unwrap();
+        conn.batch_execute("DROP SCHEMA IF EXISTS slug_migration_test CASCADE; CREATE SCHEMA slug_migration_test; SET search_path TO slug_migration_test").unwrap();
+
+        // migrate up to (but not including) the migration which gives every Post a slug
+        for migration in conn.pending_migrations(MIGRATIONS).unwrap() {
+            if migration.name().version().to_string() == "2026101811" {
+                break;
+            }
+
+            conn.run_migration(&migration).unwrap();
         }
+
+        // the oldest Post's slug is the same as the second Post's slug with its first suffix
+        conn.batch_execute("
+            INSERT INTO posts_by_id (post_id, author_id, title, body, body_html, created_at) VALUES
+                (gen_random_uuid(), gen_random_uuid(), 'Hello World 2', '', '', now() - interval '3 minutes'),
+                (gen_random_uuid(), gen_random_uuid(), 'Hello World', '', '', now() - interval '2 minutes'),
+                (gen_random_uuid(), gen_random_uuid(), 'Hello World', '', '', now() - interval '1 minute');
+        ").unwrap();
+
+        conn.run_pending_migrations(MIGRATIONS).unwrap();
+
+        let slugs: Vec<String> = sql_query("SELECT slug FROM posts_by_id ORDER BY created_at")
+            .load::<SlugRow>(&mut conn).unwrap()
+            .into_iter().map(|row| row.slug).collect();
+
+        conn.batch_execute("DROP SCHEMA slug_migration_test CASCADE").unwrap();
+
+        assert_eq!(slugs, vec!["hello-world-2", "hello-world", "hello-world-3"]);
     }
-}
\ No newline at end of file
+}
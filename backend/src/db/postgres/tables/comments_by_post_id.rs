@@ -0,0 +1,95 @@
+use crate::db::error::DbError;
+use crate::db::page;
+use crate::db::page::{Page, PageRequest};
+use crate::db::table::TableRow;
+use crate::db::tables::comments_by_post_id::{by_comment_id, with_post_id, CommentsByPostIdKey, CommentsByPostIdTableLike, CommentsByPostIdTableRow};
+use diesel::dsl::{delete, insert_into};
+use diesel::r2d2::{ConnectionManager, Pool};
+use diesel::{table, Connection, ExpressionMethods, QueryDsl, SelectableHelper};
+use diesel::{PgConnection, RunQueryDsl};
+use std::fmt::Debug;
+use std::sync::Arc;
+use uuid::Uuid;
+
+table! {
+    comments_by_post_id(post_id, comment_id) {
+        post_id -> Uuid,
+        comment_id -> Uuid,
+        author_id -> Uuid,
+        parent_id -> Nullable<Uuid>,
+        body -> Text,
+        created_at -> Timestamptz,
+    }
+}
+
+#[derive(Debug)]
+pub(in crate::db) struct Impl {
+    pub(in crate::db) connection_pool: Arc<Pool<ConnectionManager<PgConnection>>>,
+}
+
+impl CommentsByPostIdTableLike for Impl {
+    fn insert(&mut self, rows: Vec<CommentsByPostIdTableRow>) -> Result<Vec<CommentsByPostIdKey>, DbError> {
+        match self.connection_pool.get() {
+            Ok(mut connection) => {
+                connection.transaction::<_, _, _>(|conn| {
+                    let keys = rows.iter().map(TableRow::primary_key).collect();
+                    insert_into(comments_by_post_id::table).values(rows).execute(conn).map(|_| keys)
+                }).map_err(DbError::from)
+            }
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+
+    fn get(&self, post_id: &Uuid, comment_id: &Uuid) -> Result<CommentsByPostIdTableRow, DbError> {
+        match self.connection_pool.get() {
+            Ok(mut connection) => {
+                match comments_by_post_id::table.find((post_id, comment_id)).first::<CommentsByPostIdTableRow>(&mut connection) {
+                    Ok(comment) => Ok(comment),
+                    Err(e) => Err(DbError::from(e)),
+                }
+            }
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+
+    fn list_page(&self, post_id: &Uuid, page: &PageRequest<Uuid>, limit: usize) -> Result<Page<Uuid, CommentsByPostIdTableRow>, DbError> {
+        match self.connection_pool.get() {
+            Ok(mut connection) => {
+                let query = comments_by_post_id::table
+                    .select(CommentsByPostIdTableRow::as_select())
+                    .filter(comments_by_post_id::post_id.eq(post_id));
+
+                // scan in the same direction as the in-memory table, so both return the same pages
+                let query = match page {
+                    PageRequest::First => query.order(comments_by_post_id::comment_id.asc()).into_boxed(),
+                    PageRequest::After(comment_id) => query.filter(comments_by_post_id::comment_id.gt(comment_id)).order(comments_by_post_id::comment_id.asc()).into_boxed(),
+                    PageRequest::Before(comment_id) => query.filter(comments_by_post_id::comment_id.lt(comment_id)).order(comments_by_post_id::comment_id.desc()).into_boxed(),
+                };
+
+                // scan one row past the limit, to find out if there is another page after this one
+                match query.limit(i64::try_from(limit.saturating_add(1)).unwrap_or(i64::MAX)).load(&mut connection) {
+                    Ok(comments) => {
+                        let page = page::from_scan(&with_post_id(*post_id, page), comments, limit);
+                        Ok(by_comment_id(page))
+                    }
+                    Err(e) => Err(DbError::from(e)),
+                }
+            },
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+
+    fn delete(&mut self, post_id: &Uuid, comment_id: &Uuid) -> Result<CommentsByPostIdKey, DbError> {
+        match self.connection_pool.get() {
+            Ok(mut connection) => {
+                // replies are deleted by the foreign key on (post_id, parent_id) -- see the migration
+                match delete(comments_by_post_id::table.find((post_id, comment_id))).execute(&mut connection) {
+                    Ok(0) => Err(DbError::from(diesel::result::Error::NotFound)),
+                    Ok(_) => Ok((*post_id, *comment_id)),
+                    Err(e) => Err(DbError::from(e)),
+                }
+            }
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+}
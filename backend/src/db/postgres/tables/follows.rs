@@ -0,0 +1,49 @@
+use crate::db::error::DbError;
+use crate::db::tables::follows::{FollowsTableLike, FollowsTableRow};
+use diesel::dsl::{delete, insert_into};
+use diesel::r2d2::{ConnectionManager, Pool};
+use diesel::{table, QueryDsl};
+use diesel::{PgConnection, RunQueryDsl};
+use std::fmt::Debug;
+use std::sync::Arc;
+use uuid::Uuid;
+
+table! {
+    follows(follower_id, author_id) {
+        follower_id -> Uuid,
+        author_id -> Uuid,
+        created_at -> Timestamptz,
+    }
+}
+
+#[derive(Debug)]
+pub(in crate::db) struct Impl {
+    pub(in crate::db) connection_pool: Arc<Pool<ConnectionManager<PgConnection>>>,
+}
+
+impl FollowsTableLike for Impl {
+    fn insert(&mut self, row: FollowsTableRow) -> Result<bool, DbError> {
+        match self.connection_pool.get() {
+            Ok(mut connection) => {
+                match insert_into(follows::table).values(&row).on_conflict_do_nothing().execute(&mut connection) {
+                    Ok(n) => Ok(n > 0),
+                    Err(e) => Err(DbError::from(e)),
+                }
+            }
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+
+    fn delete(&mut self, follower_id: &Uuid, author_id: &Uuid) -> Result<(), DbError> {
+        match self.connection_pool.get() {
+            Ok(mut connection) => {
+                match delete(follows::table.find((follower_id, author_id))).execute(&mut connection) {
+                    Ok(0) => Err(DbError::from(diesel::result::Error::NotFound)),
+                    Ok(_) => Ok(()),
+                    Err(e) => Err(DbError::from(e)),
+                }
+            }
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use crate::db::error::DbError;
+use crate::db::tables::media::{MediaTableLike, MediaTableRow};
+use diesel::dsl::insert_into;
+use diesel::r2d2::{ConnectionManager, Pool};
+use diesel::{table, QueryDsl, SelectableHelper};
+use diesel::{PgConnection, RunQueryDsl};
+use std::fmt::Debug;
+use std::sync::Arc;
+use uuid::Uuid;
+
+table! {
+    media(media_id) {
+        media_id -> Uuid,
+        uploader_id -> Uuid,
+        content_type -> Varchar,
+        size_bytes -> Int8,
+        created_at -> Timestamptz,
+    }
+}
+
+#[derive(Debug)]
+pub(in crate::db) struct Impl {
+    pub(in crate::db) connection_pool: Arc<Pool<ConnectionManager<PgConnection>>>,
+}
+
+impl MediaTableLike for Impl {
+    fn insert(&mut self, row: MediaTableRow) -> Result<Uuid, DbError> {
+        match self.connection_pool.get() {
+            Ok(mut connection) => {
+                match insert_into(media::table).values(&row).returning(media::media_id).get_result(&mut connection) {
+                    Ok(key) => Ok(key),
+                    Err(e) => Err(DbError::from(e)),
+                }
+            }
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+
+    fn get(&self, key: &Uuid) -> Result<MediaTableRow, DbError> {
+        match self.connection_pool.get() {
+            Ok(mut connection) => {
+                match media::table.find(key).select(MediaTableRow::as_select()).first(&mut connection) {
+                    Ok(row) => Ok(row),
+                    Err(e) => Err(DbError::from(e)),
+                }
+            }
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+}
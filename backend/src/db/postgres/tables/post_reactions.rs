@@ -0,0 +1,69 @@
+use crate::db::error::DbError;
+use crate::db::tables::post_reactions::{PostReactionsTableLike, PostReactionsTableRow, ReactionCount};
+use diesel::dsl::{count_star, delete, insert_into};
+use diesel::r2d2::{ConnectionManager, Pool};
+use diesel::{table, ExpressionMethods, QueryDsl};
+use diesel::{PgConnection, RunQueryDsl};
+use std::fmt::Debug;
+use std::sync::Arc;
+use uuid::Uuid;
+
+table! {
+    post_reactions(post_id, user_id, kind) {
+        post_id -> Uuid,
+        user_id -> Uuid,
+        kind -> Varchar,
+        created_at -> Timestamptz,
+    }
+}
+
+#[derive(Debug)]
+pub(in crate::db) struct Impl {
+    pub(in crate::db) connection_pool: Arc<Pool<ConnectionManager<PgConnection>>>,
+}
+
+impl PostReactionsTableLike for Impl {
+    fn insert(&mut self, row: PostReactionsTableRow) -> Result<bool, DbError> {
+        match self.connection_pool.get() {
+            Ok(mut connection) => {
+                match insert_into(post_reactions::table).values(&row).on_conflict_do_nothing().execute(&mut connection) {
+                    Ok(n) => Ok(n > 0),
+                    Err(e) => Err(DbError::from(e)),
+                }
+            }
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+
+    fn delete(&mut self, post_id: &Uuid, user_id: &Uuid, kind: &str) -> Result<(), DbError> {
+        match self.connection_pool.get() {
+            Ok(mut connection) => {
+                match delete(post_reactions::table.find((post_id, user_id, kind))).execute(&mut connection) {
+                    Ok(0) => Err(DbError::from(diesel::result::Error::NotFound)),
+                    Ok(_) => Ok(()),
+                    Err(e) => Err(DbError::from(e)),
+                }
+            }
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+
+    fn count_for_posts(&self, post_ids: &[Uuid]) -> Result<Vec<ReactionCount>, DbError> {
+        match self.connection_pool.get() {
+            Ok(mut connection) => {
+                let counts = post_reactions::table
+                    .filter(post_reactions::post_id.eq_any(post_ids))
+                    .group_by((post_reactions::post_id, post_reactions::kind))
+                    .select((post_reactions::post_id, post_reactions::kind, count_star()))
+                    .order((post_reactions::post_id.asc(), post_reactions::kind.asc()))
+                    .load::<(Uuid, String, i64)>(&mut connection);
+
+                match counts {
+                    Ok(counts) => Ok(counts.into_iter().map(|(post_id, kind, count)| ReactionCount { post_id, kind, count }).collect()),
+                    Err(e) => Err(DbError::from(e)),
+                }
+            }
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+}
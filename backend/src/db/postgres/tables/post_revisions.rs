@@ -0,0 +1,67 @@
+use crate::db::error::DbError;
+use crate::db::page;
+use crate::db::page::{Page, PageRequest};
+use crate::db::tables::post_revisions::{by_revision_number, with_post_id, PostRevisionsTableLike, PostRevisionsTableRow};
+use diesel::r2d2::{ConnectionManager, Pool};
+use diesel::{table, ExpressionMethods, QueryDsl, SelectableHelper};
+use diesel::{PgConnection, RunQueryDsl};
+use std::fmt::Debug;
+use std::sync::Arc;
+use uuid::Uuid;
+
+table! {
+    post_revisions(post_id, revision) {
+        post_id -> Uuid,
+        revision -> Int4,
+        title -> Text,
+        body -> Text,
+        created_at -> Timestamptz,
+        format -> Varchar,
+    }
+}
+
+#[derive(Debug)]
+pub(in crate::db) struct Impl {
+    pub(in crate::db) connection_pool: Arc<Pool<ConnectionManager<PgConnection>>>,
+}
+
+impl PostRevisionsTableLike for Impl {
+    fn get(&self, post_id: &Uuid, revision: i32) -> Result<PostRevisionsTableRow, DbError> {
+        match self.connection_pool.get() {
+            Ok(mut connection) => {
+                match post_revisions::table.find((post_id, revision)).first::<PostRevisionsTableRow>(&mut connection) {
+                    Ok(revision) => Ok(revision),
+                    Err(e) => Err(DbError::from(e)),
+                }
+            }
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+
+    fn list_page(&self, post_id: &Uuid, page: &PageRequest<i32>, limit: usize) -> Result<Page<i32, PostRevisionsTableRow>, DbError> {
+        match self.connection_pool.get() {
+            Ok(mut connection) => {
+                let query = post_revisions::table
+                    .select(PostRevisionsTableRow::as_select())
+                    .filter(post_revisions::post_id.eq(post_id));
+
+                // scan in the same direction as the in-memory table, so both return the same pages
+                let query = match page {
+                    PageRequest::First => query.order(post_revisions::revision.asc()).into_boxed(),
+                    PageRequest::After(revision) => query.filter(post_revisions::revision.gt(revision)).order(post_revisions::revision.asc()).into_boxed(),
+                    PageRequest::Before(revision) => query.filter(post_revisions::revision.lt(revision)).order(post_revisions::revision.desc()).into_boxed(),
+                };
+
+                // scan one row past the limit, to find out if there is another page after this one
+                match query.limit(i64::try_from(limit.saturating_add(1)).unwrap_or(i64::MAX)).load(&mut connection) {
+                    Ok(revisions) => {
+                        let page = page::from_scan(&with_post_id(*post_id, page), revisions, limit);
+                        Ok(by_revision_number(page))
+                    }
+                    Err(e) => Err(DbError::from(e)),
+                }
+            },
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+}
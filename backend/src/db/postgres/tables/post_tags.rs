@@ -0,0 +1,36 @@
+use crate::db::error::DbError;
+use crate::db::tables::post_tags::{PostTagsTableLike, PostTagsTableRow};
+use diesel::r2d2::{ConnectionManager, Pool};
+use diesel::{table, ExpressionMethods, QueryDsl, SelectableHelper};
+use diesel::{PgConnection, RunQueryDsl};
+use std::fmt::Debug;
+use std::sync::Arc;
+use uuid::Uuid;
+
+table! {
+    post_tags(post_id, tag) {
+        post_id -> Uuid,
+        tag -> Varchar,
+    }
+}
+
+#[derive(Debug)]
+pub(in crate::db) struct Impl {
+    pub(in crate::db) connection_pool: Arc<Pool<ConnectionManager<PgConnection>>>,
+}
+
+impl PostTagsTableLike for Impl {
+    fn list_for_posts(&self, post_ids: &[Uuid]) -> Result<Vec<PostTagsTableRow>, DbError> {
+        match self.connection_pool.get() {
+            Ok(mut connection) => {
+                post_tags::table
+                    .select(PostTagsTableRow::as_select())
+                    .filter(post_tags::post_id.eq_any(post_ids))
+                    .order((post_tags::post_id.asc(), post_tags::tag.asc()))
+                    .load(&mut connection)
+                    .map_err(DbError::from)
+            }
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+}
@@ -1,9 +1,25 @@
+use crate::db::error::DbError;
+use crate::db::page;
+use crate::db::page::{Page, PageRequest};
+use crate::db::postgres::tables::follows::follows;
+use crate::db::postgres::tables::post_revisions::post_revisions;
+use crate::db::postgres::tables::post_tags::post_tags;
+use crate::db::postgres::tables::posts_by_slug::posts_by_slug;
 use crate::db::table::TableRow;
-use crate::db::tables::posts_by_id::{PostsByIdTableLike, PostsByIdTableRow};
-use diesel::dsl::insert_into;
+use crate::db::tables::post_revisions::PostRevisionsTableRow;
+use crate::db::tables::post_tags::{PostTagsTableRow, TagCount};
+use crate::db::tables::posts_by_id::{PostsByIdFilter, PostsByIdTableLike, PostsByIdTableRow, PostsByIdUpdate, SearchResult, Visibility};
+use crate::db::tables::posts_by_id::{feed_page, FeedEntry, FeedPosition};
+use crate::db::tables::posts_by_slug::{first_free, PostsBySlugTableRow};
+use crate::model::post::{Post, Status};
+use chrono::{DateTime, Utc};
+use diesel::dsl::{delete, insert_into, update};
 use diesel::r2d2::{ConnectionManager, Pool};
-use diesel::{table, Connection, QueryDsl, SelectableHelper};
-use diesel::{PgConnection, RunQueryDsl};
+use diesel::dsl::count_star;
+use diesel::sql_types::{Bool, Float4, Int8, Nullable, Text};
+use diesel::{allow_tables_to_appear_in_same_query, joinable, sql_query, table, BoolExpressionMethods, Connection, ExpressionMethods, PgTextExpressionMethods, QueryDsl, SelectableHelper, TextExpressionMethods};
+use diesel::{PgConnection, QueryableByName, RunQueryDsl};
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::sync::Arc;
 use uuid::Uuid;
@@ -14,9 +30,118 @@ table! {
         author_id -> Uuid,
         title -> Text,
         body -> Text,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        status -> Varchar,
+        published_at -> Nullable<Timestamptz>,
+        publish_at -> Nullable<Timestamptz>,
+        revision -> Int4,
+        format -> Varchar,
+        body_html -> Text,
+        slug -> Varchar,
+        version -> Int4,
     }
 }
 
+joinable!(post_tags -> posts_by_id (post_id));
+allow_tables_to_appear_in_same_query!(posts_by_id, post_tags);
+allow_tables_to_appear_in_same_query!(posts_by_id, follows);
+
+/// Gives a Post the first free slug, starting from the one it asks for. Returns the slug to record,
+/// unless the Post has had it before. This must be called in the same transaction as the insert or
+/// update of the Post itself, and the slug must be recorded before the next Post claims one.
+fn claim_slug(conn: &mut PgConnection, row: &mut PostsByIdTableRow) -> Result<Option<PostsBySlugTableRow>, diesel::result::Error> {
+    let taken: HashMap<String, Uuid> = posts_by_slug::table
+        .filter(posts_by_slug::slug.eq(&row.slug).or(posts_by_slug::slug.like(format!("{}-%", escape_like(&row.slug)))))
+        .select((posts_by_slug::slug, posts_by_slug::post_id))
+        .load::<(String, Uuid)>(conn)?
+        .into_iter()
+        .collect();
+
+    row.slug = first_free(&row.slug, &row.post_id, |slug| taken.get(slug).copied());
+
+    match taken.contains_key(&row.slug) {
+        true => Ok(None),
+        false => Ok(Some(PostsBySlugTableRow::new(row.slug.clone(), row.post_id))),
+    }
+}
+
+/// Records a slug claimed by claim_slug(), once its Post has been inserted. If another replica has
+/// just claimed the same slug, this fails with a unique violation, and the whole transaction is
+/// rolled back.
+fn record_slug(conn: &mut PgConnection, slug: Option<PostsBySlugTableRow>) -> Result<usize, diesel::result::Error> {
+    match slug {
+        None => Ok(0),
+        Some(slug) => insert_into(posts_by_slug::table).values(slug).execute(conn),
+    }
+}
+
+/// Records the latest Revision of a Post. This must be called in the same transaction as the insert
+/// or update of the Post itself. If the Revision has already been recorded, this fails with a
+/// unique violation, and the whole transaction is rolled back.
+fn append_revision(conn: &mut PgConnection, row: &PostsByIdTableRow) -> Result<usize, diesel::result::Error> {
+    insert_into(post_revisions::table)
+        .values(PostRevisionsTableRow::from(row))
+        .execute(conn)
+}
+
+/// Explains why an update of a Post matched no rows: either the Post does not exist, or it has been
+/// changed since it was read.
+fn not_updated(conn: &mut PgConnection, pk: Uuid) -> DbError {
+    match posts_by_id::table.find(pk).select(posts_by_id::version).first::<i32>(conn) {
+        Ok(version) => DbError::Conflict(format!("Post {} was changed by someone else, and is now at version {}", pk, version)),
+        Err(e) => DbError::from(e),
+    }
+}
+
+/// Replaces every Tag on the given Posts. This must be called in the same transaction as the insert
+/// or update of the Posts themselves.
+fn replace_tags(conn: &mut PgConnection, post_ids: &[Uuid], tags: &[PostTagsTableRow]) -> Result<usize, diesel::result::Error> {
+    delete(post_tags::table.filter(post_tags::post_id.eq_any(post_ids))).execute(conn)?;
+
+    match tags.is_empty() {
+        true => Ok(0),
+        false => insert_into(post_tags::table).values(tags).execute(conn),
+    }
+}
+
+/// Escapes the characters which have special meanings in LIKE and ILIKE patterns.
+fn escape_like(raw: &str) -> String {
+    raw.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+// Diesel has no built-in support for full-text search, so search() uses raw SQL. The search_vector
+// column is generated by Postgres (see the migration which adds it), so it is not in the table above.
+//
+// websearch_to_tsquery() accepts anything a user might type into a search box without raising an
+// error. The body is HTML-escaped before ts_headline() marks the matches, so that only the <mark>
+// tags in the snippet are HTML.
+const SEARCH: &str = "
+    SELECT posts_by_id.*,
+           ts_rank(search_vector, query) AS rank,
+           ts_headline(
+               'english',
+               replace(replace(replace(replace(body, '&', '&amp;'), '<', '&lt;'), '>', '&gt;'), '\"', '&quot;'),
+               query,
+               'StartSel=<mark>, StopSel=</mark>, MaxWords=30, MinWords=10'
+           ) AS snippet
+    FROM posts_by_id, websearch_to_tsquery('english', $1) AS query
+    WHERE search_vector @@ query
+      AND ($2 OR status = $3 OR author_id = $4)
+    ORDER BY rank DESC, post_id ASC
+    LIMIT $5
+";
+
+#[derive(QueryableByName)]
+struct SearchHit {
+    #[diesel(embed)]
+    post: PostsByIdTableRow,
+    #[diesel(sql_type = Float4)]
+    rank: f32,
+    #[diesel(sql_type = Text)]
+    snippet: String,
+}
+
 #[derive(Debug)]
 pub(in crate::db) struct Impl {
     pub(in crate::db) connection_pool: Arc<Pool<ConnectionManager<PgConnection>>>,
@@ -25,48 +150,314 @@ pub(in crate::db) struct Impl {
 // TODO pull this implementation out into a default trait
 
 impl PostsByIdTableLike for Impl {
-    fn insert(&mut self, rows: Vec<PostsByIdTableRow>) -> Result<Vec<Uuid>, String> {
+    fn insert(&mut self, rows: Vec<PostsByIdTableRow>, tags: Vec<PostTagsTableRow>) -> Result<Vec<Uuid>, DbError> {
         match self.connection_pool.get() {
             Ok(mut connection) => {
-                connection.transaction::<_, _, _>(|conn| {
-                    rows.into_iter().try_fold(vec![], |mut vec, row| {
-                        let pk = row.primary_key().clone();
-                        match insert_into(posts_by_id::table).values(row).execute(conn) {
+                connection.transaction::<_, diesel::result::Error, _>(|conn| {
+                    let keys = rows.into_iter().try_fold(vec![], |mut vec, mut row| {
+                        let pk = row.primary_key();
+                        let slug = claim_slug(conn, &mut row)?;
+                        match insert_into(posts_by_id::table).values(&row).execute(conn)
+                            .and_then(|_| record_slug(conn, slug))
+                            .and_then(|_| append_revision(conn, &row)) {
                             Ok(_) => {
                                 vec.push(pk);
                                 Ok(vec)
                             },
                             Err(e) => Err(e),
                         }
-                    })
-                }).map_err(|e| e.to_string())
+                    })?;
+
+                    replace_tags(conn, &keys, &tags)?;
+                    Ok(keys)
+                }).map_err(DbError::from)
             }
-            Err(e) => Err(format!("Unable to connect to DB: {}", e)),
+            Err(e) => Err(DbError::from(e)),
         }
     }
 
-    fn get(&self, key: &Uuid) -> Result<PostsByIdTableRow, String> {
+    fn get(&self, key: &Uuid) -> Result<PostsByIdTableRow, DbError> {
         match self.connection_pool.get() {
             Ok(mut connection) => {
                 match posts_by_id::table.find(key).first::<PostsByIdTableRow>(&mut connection) {
                     Ok(post) => Ok(post),
-                    Err(e) => Err(format!("Unable to find Post: {}", e)),
+                    Err(e) => Err(DbError::from(e)),
                 }
             }
-            Err(e) => Err(format!("Unable to connect to DB: {}", e)),
+            Err(e) => Err(DbError::from(e)),
         }
     }
 
-    fn list(&self, limit: usize) -> Result<Vec<PostsByIdTableRow>, String> {
+    fn list_page(&self, page: &PageRequest<Uuid>, limit: usize, filter: &PostsByIdFilter) -> Result<Page<Uuid, PostsByIdTableRow>, DbError> {
         match self.connection_pool.get() {
             Ok(mut connection) => {
-                match posts_by_id::table.select(PostsByIdTableRow::as_select()).limit(limit as i64).load(&mut connection) {
-                    Ok(posts) => Ok(posts),
-                    Err(e) => Err(format!("Unable to get Posts: {}", e)),
+                let mut query = posts_by_id::table.select(PostsByIdTableRow::as_select()).into_boxed();
+
+                // This must agree with the in-memory implementation of list_page()
+                if let Some(author_id) = filter.author_id {
+                    query = query.filter(posts_by_id::author_id.eq(author_id));
+                }
+
+                if let Some(after) = filter.created_after {
+                    query = query.filter(posts_by_id::created_at.gt(after));
+                }
+
+                if let Some(before) = filter.created_before {
+                    query = query.filter(posts_by_id::created_at.lt(before));
+                }
+
+                if let Some(substring) = &filter.title_contains {
+                    query = query.filter(posts_by_id::title.ilike(format!("%{}%", escape_like(substring))));
+                }
+
+                if let Some(tag) = &filter.tag {
+                    let tagged = post_tags::table.filter(post_tags::tag.eq(tag)).select(post_tags::post_id);
+                    query = query.filter(posts_by_id::post_id.eq_any(tagged));
+                }
+
+                query = match filter.visibility {
+                    Visibility::All => query,
+                    Visibility::Published => query.filter(posts_by_id::status.eq(Status::Published.to_string())),
+                    Visibility::PublishedOrBy(author_id) => query.filter(
+                        posts_by_id::status.eq(Status::Published.to_string()).or(posts_by_id::author_id.eq(author_id))
+                    ),
+                };
+
+                // scan in the same direction as the in-memory table, so both return the same pages
+                let query = match page {
+                    PageRequest::First => query.order(posts_by_id::post_id.asc()),
+                    PageRequest::After(key) => query.filter(posts_by_id::post_id.gt(key)).order(posts_by_id::post_id.asc()),
+                    PageRequest::Before(key) => query.filter(posts_by_id::post_id.lt(key)).order(posts_by_id::post_id.desc()),
+                };
+
+                // scan one row past the limit, to find out if there is another page after this one
+                match query.limit(i64::try_from(limit.saturating_add(1)).unwrap_or(i64::MAX)).load(&mut connection) {
+                    Ok(posts) => Ok(page::from_scan(page, posts, limit)),
+                    Err(e) => Err(DbError::from(e)),
                 }
             },
-            Err(e) => Err(format!("Unable to connect to DB: {}", e)),
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+
+    fn update(&mut self, updates: Vec<PostsByIdUpdate>) -> Result<Vec<Uuid>, DbError> {
+        match self.connection_pool.get() {
+            Ok(mut connection) => {
+                connection.transaction::<_, DbError, _>(|conn| {
+                    updates.into_iter().try_fold(vec![], |mut vec, changed| {
+                        let is_edit = changed.is_edit();
+                        let PostsByIdUpdate { mut row, read_version, tags, .. } = changed;
+                        let pk = row.primary_key();
+                        let slug = claim_slug(conn, &mut row)?;
 
+                        // only overwrite the Post if nobody else has changed it since it was read
+                        let unchanged = posts_by_id::table.find(pk).filter(posts_by_id::version.eq(read_version));
+
+                        match update(unchanged).set(&row).execute(conn)? {
+                            // roll back the whole transaction if any Post does not exist, or has changed
+                            0 => Err(not_updated(conn, pk)),
+                            _ => {
+                                record_slug(conn, slug)?;
+
+                                if is_edit {
+                                    append_revision(conn, &row)?;
+                                }
+
+                                if let Some(tags) = tags {
+                                    replace_tags(conn, &[pk], &tags)?;
+                                }
+
+                                vec.push(pk);
+                                Ok(vec)
+                            },
+                        }
+                    })
+                })
+            }
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+
+    fn delete(&mut self, keys: Vec<Uuid>) -> Result<Vec<Uuid>, DbError> {
+        match self.connection_pool.get() {
+            Ok(mut connection) => {
+                connection.transaction::<_, _, _>(|conn| {
+                    keys.into_iter().try_fold(vec![], |mut vec, pk| {
+                        match delete(posts_by_id::table.find(pk)).execute(conn) {
+                            // roll back the whole transaction if any Post does not exist
+                            Ok(0) => Err(diesel::result::Error::NotFound),
+                            Ok(_) => {
+                                vec.push(pk);
+                                Ok(vec)
+                            },
+                            Err(e) => Err(e),
+                        }
+                    })
+                }).map_err(DbError::from)
+            }
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+
+    fn publish_due(&mut self, now: DateTime<Utc>, limit: usize) -> Result<Vec<Uuid>, DbError> {
+        match self.connection_pool.get() {
+            Ok(mut connection) => {
+                connection.transaction::<_, diesel::result::Error, _>(|conn| {
+                    // Lock the due rows until this transaction commits. Other replicas skip them,
+                    // rather than waiting for them, so no Post is published twice.
+                    let rows = posts_by_id::table
+                        .select(PostsByIdTableRow::as_select())
+                        .filter(posts_by_id::status.ne(Status::Published.to_string()))
+                        .filter(posts_by_id::publish_at.le(now))
+                        .order(posts_by_id::publish_at.asc())
+                        .limit(i64::try_from(limit).unwrap_or(i64::MAX))
+                        .for_update()
+                        .skip_locked()
+                        .load(conn)?;
+
+                    rows.into_iter().try_fold(vec![], |mut vec, row| {
+                        let mut post = Post::from(row);
+
+                        if post.publish_if_due(now) {
+                            let PostsByIdUpdate { row, read_version, .. } = PostsByIdUpdate::from(post);
+                            let pk = row.primary_key();
+                            // The row is locked, so its version cannot have changed since it was read,
+                            // but check it anyway, like update() does. Publishing does not change the
+                            // revision, so there is no Revision to record.
+                            let unchanged = posts_by_id::table.find(pk).filter(posts_by_id::version.eq(read_version));
+
+                            if update(unchanged).set(&row).execute(conn)? > 0 {
+                                vec.push(pk);
+                            }
+                        }
+
+                        Ok(vec)
+                    })
+                }).map_err(DbError::from)
+            }
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+
+    fn tag_counts(&self, visibility: &Visibility) -> Result<Vec<TagCount>, DbError> {
+        match self.connection_pool.get() {
+            Ok(mut connection) => {
+                let query = post_tags::table
+                    .inner_join(posts_by_id::table)
+                    .group_by(post_tags::tag)
+                    .select((post_tags::tag, count_star()))
+                    .order(post_tags::tag.asc())
+                    .into_boxed();
+
+                // This must agree with the visibility filter in list_page()
+                let query = match visibility {
+                    Visibility::All => query,
+                    Visibility::Published => query.filter(posts_by_id::status.eq(Status::Published.to_string())),
+                    Visibility::PublishedOrBy(author_id) => query.filter(
+                        posts_by_id::status.eq(Status::Published.to_string()).or(posts_by_id::author_id.eq(author_id))
+                    ),
+                };
+
+                match query.load::<(String, i64)>(&mut connection) {
+                    Ok(counts) => Ok(counts.into_iter().map(|(tag, post_count)| TagCount { tag, post_count }).collect()),
+                    Err(e) => Err(DbError::from(e)),
+                }
+            }
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+
+    fn search(&self, query: &str, visibility: &Visibility, limit: usize) -> Result<Vec<SearchResult>, DbError> {
+        match self.connection_pool.get() {
+            Ok(mut connection) => {
+                // This must agree with the visibility filter in list_page()
+                let (all, author_id) = match visibility {
+                    Visibility::All => (true, None),
+                    Visibility::Published => (false, None),
+                    Visibility::PublishedOrBy(author_id) => (false, Some(*author_id)),
+                };
+
+                let hits = sql_query(SEARCH)
+                    .bind::<Text, _>(query)
+                    .bind::<Bool, _>(all)
+                    .bind::<Text, _>(Status::Published.to_string())
+                    .bind::<Nullable<diesel::sql_types::Uuid>, _>(author_id)
+                    .bind::<Int8, _>(i64::try_from(limit).unwrap_or(i64::MAX))
+                    .load::<SearchHit>(&mut connection);
+
+                match hits {
+                    Ok(hits) => Ok(hits.into_iter().map(|hit| SearchResult { post: hit.post, rank: hit.rank, snippet: hit.snippet }).collect()),
+                    Err(e) => Err(DbError::from(e)),
+                }
+            }
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+
+    fn feed(&self, follower_id: &Uuid, page: &PageRequest<FeedPosition>, limit: usize) -> Result<Page<FeedPosition, PostsByIdTableRow>, DbError> {
+        // This is answered by posts_by_id_feed_idx -- see the migration which creates it
+        self.published_where(Authors::FollowedBy(follower_id), page, limit)
+    }
+
+    fn published(&self, author_id: Option<&Uuid>, page: &PageRequest<FeedPosition>, limit: usize) -> Result<Page<FeedPosition, PostsByIdTableRow>, DbError> {
+        // This is answered by posts_by_id_feed_idx for one author, or by posts_by_id_published_idx
+        // for every author -- see the migrations which create them
+        match author_id {
+            Some(author_id) => self.published_where(Authors::Only(author_id), page, limit),
+            None => self.published_where(Authors::All, page, limit),
+        }
+    }
+}
+
+/// Whose published Posts to list, in published_where().
+enum Authors<'a> {
+    All,
+    Only(&'a Uuid),
+    FollowedBy(&'a Uuid),
+}
+
+impl Impl {
+    /// Lists one page of published Posts, newest first. This must agree with the in-memory
+    /// implementation of newest_first().
+    fn published_where(&self, authors: Authors, page: &PageRequest<FeedPosition>, limit: usize) -> Result<Page<FeedPosition, PostsByIdTableRow>, DbError> {
+        match self.connection_pool.get() {
+            Ok(mut connection) => {
+                let query = posts_by_id::table
+                    .select(PostsByIdTableRow::as_select())
+                    .filter(posts_by_id::status.eq(Status::Published.to_string()))
+                    .into_boxed();
+
+                let query = match authors {
+                    Authors::All => query,
+                    Authors::Only(author_id) => query.filter(posts_by_id::author_id.eq(author_id)),
+                    Authors::FollowedBy(follower_id) => query.filter(posts_by_id::author_id.eq_any(
+                        follows::table.filter(follows::follower_id.eq(follower_id)).select(follows::author_id)
+                    )),
+                };
+
+                // scan in the same direction as the in-memory table, so both return the same pages
+                let query = match page {
+                    PageRequest::First => query
+                        .order((posts_by_id::published_at.desc(), posts_by_id::post_id.desc())),
+                    PageRequest::After(position) => query
+                        .filter(posts_by_id::published_at.lt(position.published_at).or(
+                            posts_by_id::published_at.eq(position.published_at).and(posts_by_id::post_id.lt(position.post_id))
+                        ))
+                        .order((posts_by_id::published_at.desc(), posts_by_id::post_id.desc())),
+                    PageRequest::Before(position) => query
+                        .filter(posts_by_id::published_at.gt(position.published_at).or(
+                            posts_by_id::published_at.eq(position.published_at).and(posts_by_id::post_id.gt(position.post_id))
+                        ))
+                        .order((posts_by_id::published_at.asc(), posts_by_id::post_id.asc())),
+                };
+
+                // scan one row past the limit, to find out if there is another page after this one
+                match query.limit(i64::try_from(limit.saturating_add(1)).unwrap_or(i64::MAX)).load(&mut connection) {
+                    Ok(posts) => Ok(feed_page(page::from_scan(page, posts.into_iter().map(FeedEntry).collect(), limit))),
+                    Err(e) => Err(DbError::from(e)),
+                }
+            }
+            Err(e) => Err(DbError::from(e)),
         }
     }
 }
\ No newline at end of file
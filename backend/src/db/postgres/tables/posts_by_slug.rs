@@ -0,0 +1,34 @@
+use crate::db::error::DbError;
+use crate::db::tables::posts_by_slug::{PostsBySlugTableLike, PostsBySlugTableRow};
+use diesel::r2d2::{ConnectionManager, Pool};
+use diesel::{table, QueryDsl};
+use diesel::{PgConnection, RunQueryDsl};
+use std::fmt::Debug;
+use std::sync::Arc;
+
+table! {
+    posts_by_slug(slug) {
+        slug -> Varchar,
+        post_id -> Uuid,
+        created_at -> Timestamptz,
+    }
+}
+
+#[derive(Debug)]
+pub(in crate::db) struct Impl {
+    pub(in crate::db) connection_pool: Arc<Pool<ConnectionManager<PgConnection>>>,
+}
+
+impl PostsBySlugTableLike for Impl {
+    fn get(&self, slug: &str) -> Result<PostsBySlugTableRow, DbError> {
+        match self.connection_pool.get() {
+            Ok(mut connection) => {
+                match posts_by_slug::table.find(slug).first::<PostsBySlugTableRow>(&mut connection) {
+                    Ok(row) => Ok(row),
+                    Err(e) => Err(DbError::from(e)),
+                }
+            }
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use crate::db::error::DbError;
+use crate::db::tables::sessions::{SessionsTableLike, SessionsTableRow};
+use chrono::{DateTime, Utc};
+use diesel::dsl::{delete, insert_into};
+use diesel::r2d2::{ConnectionManager, Pool};
+use diesel::{table, ExpressionMethods, QueryDsl, SelectableHelper};
+use diesel::{PgConnection, RunQueryDsl};
+use std::fmt::Debug;
+use std::sync::Arc;
+use uuid::Uuid;
+
+table! {
+    sessions(token_hash) {
+        token_hash -> Varchar,
+        user_id -> Uuid,
+        username -> Varchar,
+        roles -> Array<Text>,
+        display_name -> Nullable<Varchar>,
+        encrypted_refresh_token -> Nullable<Text>,
+        created_at -> Timestamptz,
+        expires_at -> Timestamptz,
+    }
+}
+
+#[derive(Debug)]
+pub(in crate::db) struct Impl {
+    pub(in crate::db) connection_pool: Arc<Pool<ConnectionManager<PgConnection>>>,
+}
+
+impl SessionsTableLike for Impl {
+    fn insert(&mut self, row: SessionsTableRow) -> Result<(), DbError> {
+        match self.connection_pool.get() {
+            Ok(mut connection) => {
+                match insert_into(sessions::table).values(&row).execute(&mut connection) {
+                    Ok(_) => Ok(()),
+                    Err(e) => Err(DbError::from(e)),
+                }
+            }
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+
+    fn get(&self, token_hash: &str) -> Result<SessionsTableRow, DbError> {
+        match self.connection_pool.get() {
+            Ok(mut connection) => {
+                match sessions::table.find(token_hash).select(SessionsTableRow::as_select()).first(&mut connection) {
+                    Ok(row) => Ok(row),
+                    Err(e) => Err(DbError::from(e)),
+                }
+            }
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+
+    fn delete(&mut self, token_hash: &str) -> Result<SessionsTableRow, DbError> {
+        match self.connection_pool.get() {
+            Ok(mut connection) => {
+                match delete(sessions::table.find(token_hash)).returning(SessionsTableRow::as_returning()).get_result(&mut connection) {
+                    Ok(row) => Ok(row),
+                    Err(e) => Err(DbError::from(e)),
+                }
+            }
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+
+    fn delete_expired(&mut self, now: DateTime<Utc>) -> Result<usize, DbError> {
+        match self.connection_pool.get() {
+            Ok(mut connection) => {
+                // uses the index on expires_at
+                match delete(sessions::table.filter(sessions::expires_at.le(now))).execute(&mut connection) {
+                    Ok(n) => Ok(n),
+                    Err(e) => Err(DbError::from(e)),
+                }
+            }
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+
+    fn delete_oldest(&mut self, user_id: &Uuid, keep: usize) -> Result<usize, DbError> {
+        match self.connection_pool.get() {
+            Ok(mut connection) => {
+                // uses the index on (user_id, created_at)
+                let newest = sessions::table
+                    .filter(sessions::user_id.eq(user_id))
+                    .order((sessions::created_at.desc(), sessions::token_hash.desc()))
+                    .limit(i64::try_from(keep).unwrap_or(i64::MAX))
+                    .select(sessions::token_hash)
+                    .load::<String>(&mut connection);
+
+                let newest = match newest {
+                    Ok(newest) => newest,
+                    Err(e) => return Err(DbError::from(e)),
+                };
+
+                let oldest = sessions::table
+                    .filter(sessions::user_id.eq(user_id))
+                    .filter(sessions::token_hash.ne_all(newest));
+
+                match delete(oldest).execute(&mut connection) {
+                    Ok(n) => Ok(n),
+                    Err(e) => Err(DbError::from(e)),
+                }
+            }
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+
+    fn count_active(&self, now: DateTime<Utc>) -> Result<usize, DbError> {
+        match self.connection_pool.get() {
+            Ok(mut connection) => {
+                match sessions::table.filter(sessions::expires_at.gt(now)).count().get_result::<i64>(&mut connection) {
+                    Ok(n) => Ok(n as usize),
+                    Err(e) => Err(DbError::from(e)),
+                }
+            }
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+}
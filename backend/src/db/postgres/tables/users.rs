@@ -0,0 +1,92 @@
+use crate::db::error::DbError;
+use crate::db::table::TableRow;
+use crate::db::tables::users::{UsersTableLike, UsersTableRow};
+use diesel::dsl::{insert_into, update};
+use diesel::r2d2::{ConnectionManager, Pool};
+use diesel::upsert::excluded;
+use diesel::{table, ExpressionMethods, QueryDsl, SelectableHelper};
+use diesel::{PgConnection, RunQueryDsl};
+use std::fmt::Debug;
+use std::sync::Arc;
+use uuid::Uuid;
+
+table! {
+    users(user_id) {
+        user_id -> Uuid,
+        username -> Varchar,
+        display_name -> Varchar,
+        bio -> Nullable<Text>,
+        avatar_url -> Nullable<Varchar>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        last_login_at -> Timestamptz,
+    }
+}
+
+#[derive(Debug)]
+pub(in crate::db) struct Impl {
+    pub(in crate::db) connection_pool: Arc<Pool<ConnectionManager<PgConnection>>>,
+}
+
+impl UsersTableLike for Impl {
+    fn record_login(&mut self, row: UsersTableRow) -> Result<Uuid, DbError> {
+        match self.connection_pool.get() {
+            Ok(mut connection) => {
+                let pk = row.primary_key();
+
+                let upsert = insert_into(users::table)
+                    .values(&row)
+                    .on_conflict(users::user_id)
+                    .do_update()
+                    .set((users::username.eq(excluded(users::username)), users::last_login_at.eq(excluded(users::last_login_at))))
+                    .execute(&mut connection);
+
+                match upsert {
+                    Ok(_) => Ok(pk),
+                    Err(e) => Err(DbError::from(e)),
+                }
+            }
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+
+    fn get(&self, key: &Uuid) -> Result<UsersTableRow, DbError> {
+        match self.connection_pool.get() {
+            Ok(mut connection) => {
+                match users::table.find(key).first::<UsersTableRow>(&mut connection) {
+                    Ok(user) => Ok(user),
+                    Err(e) => Err(DbError::from(e)),
+                }
+            }
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+
+    fn list_for_ids(&self, keys: &[Uuid]) -> Result<Vec<UsersTableRow>, DbError> {
+        match self.connection_pool.get() {
+            Ok(mut connection) => {
+                users::table
+                    .select(UsersTableRow::as_select())
+                    .filter(users::user_id.eq_any(keys))
+                    .load(&mut connection)
+                    .map_err(DbError::from)
+            }
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+
+    fn update(&mut self, row: UsersTableRow) -> Result<Uuid, DbError> {
+        match self.connection_pool.get() {
+            Ok(mut connection) => {
+                let pk = row.primary_key();
+
+                match update(users::table.find(pk)).set(&row).execute(&mut connection) {
+                    Ok(0) => Err(DbError::from(diesel::result::Error::NotFound)),
+                    Ok(_) => Ok(pk),
+                    Err(e) => Err(DbError::from(e)),
+                }
+            }
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+}
@@ -1,19 +1,37 @@
+use crate::db::error::DbError;
+use crate::db::page::{Page, PageRequest};
+
 // It's unlikely that we'll have two tables with the same columns (same row type) but different
 // primary keys. So this can be reimplemented using an associated type, rather than a generic type
 // parameter, if necessary.
+//
+// The primary key is returned by value, so that keys made of several columns (as tuples) can be
+// built from the row's fields.
 pub(in crate::db) trait TableRow<PrimaryKey> {
-    fn primary_key(&self) -> &PrimaryKey;
+    fn primary_key(&self) -> PrimaryKey;
 }
 
 // Table can be reimplemented with associated types for the same reason.
 pub(in crate::db) trait Table<PrimaryKey, Row> where Row: TableRow<PrimaryKey> {
 
     /// Insert one or more rows into the table.
-    fn insert(&mut self, row: Vec<Row>) -> Result<Vec<PrimaryKey>, String>;
+    fn insert(&mut self, row: Vec<Row>) -> Result<Vec<PrimaryKey>, DbError>;
 
     /// Get a row from the table by its primary key.
-    fn get(&self, key: &PrimaryKey) -> Result<Row, String>;
+    fn get(&self, key: &PrimaryKey) -> Result<Row, DbError>;
+
+    /// List one page of rows from the table, sorted by primary key, up to some limit.
+    ///
+    /// Only rows which match the filter are returned.
+    fn list_page(&self, page: &PageRequest<PrimaryKey>, limit: usize, filter: &dyn Fn(&Row) -> bool) -> Result<Page<PrimaryKey, Row>, DbError>;
 
-    /// List all rows from the table, up to some limit.
-    fn list(&self, limit: usize) -> Result<Vec<Row>, String>;
-}
\ No newline at end of file
+    /// Replace one or more existing rows in the table, matching them by primary key.
+    ///
+    /// If any row does not already exist in the table, no rows are updated.
+    fn update(&mut self, rows: Vec<Row>) -> Result<Vec<PrimaryKey>, DbError>;
+
+    /// Delete one or more rows from the table by their primary keys.
+    ///
+    /// If any key does not exist in the table, no rows are deleted.
+    fn delete(&mut self, keys: Vec<PrimaryKey>) -> Result<Vec<PrimaryKey>, DbError>;
+}
@@ -1 +1,10 @@
-pub(crate) mod posts_by_id;
\ No newline at end of file
+pub(crate) mod posts_by_id;
+pub(crate) mod post_revisions;
+pub(crate) mod post_tags;
+pub(crate) mod comments_by_post_id;
+pub(crate) mod post_reactions;
+pub(crate) mod users;
+pub(crate) mod follows;
+pub(crate) mod posts_by_slug;
+pub(crate) mod media;
+pub(crate) mod sessions;
\ No newline at end of file
@@ -0,0 +1,96 @@
+use crate::db::error::DbError;
+use crate::db::page::{Page, PageRequest};
+use crate::db::postgres::tables::comments_by_post_id::comments_by_post_id;
+use crate::db::table::TableRow;
+use crate::model::comment::{Comment, CommentId};
+use crate::model::post::{AuthorId, Body, PostId};
+use chrono::{DateTime, Utc};
+use diesel::{Insertable, Queryable, Selectable};
+use serde::Serialize;
+use std::fmt::Debug;
+use uuid::Uuid;
+
+/// Comments are identified by the Post they belong to, and their own id.
+pub(in crate::db) type CommentsByPostIdKey = (Uuid, Uuid);
+
+#[derive(Clone, Debug, Serialize, Insertable, Queryable, Selectable)]
+#[diesel(table_name = comments_by_post_id)] // FIXME model should not depend on Postgres impl (diesel)
+#[diesel(primary_key(post_id, comment_id))]
+pub(crate) struct CommentsByPostIdTableRow {
+    pub(in crate::db) post_id: Uuid,
+    pub(in crate::db) comment_id: Uuid,
+    pub(in crate::db) author_id: Uuid,
+    pub(in crate::db) parent_id: Option<Uuid>,
+    pub(in crate::db) body: String,
+    pub(in crate::db) created_at: DateTime<Utc>,
+}
+
+impl TableRow<CommentsByPostIdKey> for CommentsByPostIdTableRow {
+    fn primary_key(&self) -> CommentsByPostIdKey {
+        (self.post_id, self.comment_id)
+    }
+}
+
+impl From<Comment> for CommentsByPostIdTableRow {
+    fn from(value: Comment) -> Self {
+        Self {
+            post_id: value.post_id.0,
+            comment_id: value.comment_id.0,
+            author_id: value.author_id.0,
+            parent_id: value.parent_id.map(|parent_id| parent_id.0),
+            body: value.body.0,
+            created_at: value.created_at,
+        }
+    }
+}
+
+impl From<CommentsByPostIdTableRow> for Comment {
+    fn from(value: CommentsByPostIdTableRow) -> Self {
+        Self {
+            comment_id: CommentId(value.comment_id),
+            post_id: PostId(value.post_id),
+            author_id: AuthorId(value.author_id),
+            parent_id: value.parent_id.map(CommentId),
+            body: Body(value.body),
+            created_at: value.created_at,
+        }
+    }
+}
+
+/// Locates a page of one Post's Comments by (post id, comment id).
+pub(in crate::db) fn with_post_id(post_id: Uuid, request: &PageRequest<Uuid>) -> PageRequest<CommentsByPostIdKey> {
+    match request {
+        PageRequest::First => PageRequest::First,
+        PageRequest::After(comment_id) => PageRequest::After((post_id, *comment_id)),
+        PageRequest::Before(comment_id) => PageRequest::Before((post_id, *comment_id)),
+    }
+}
+
+/// Converts a page of Comments keyed by (post id, comment id) into a page keyed by comment id
+/// alone, since every Comment on the page belongs to the same Post.
+pub(in crate::db) fn by_comment_id(page: Page<CommentsByPostIdKey, CommentsByPostIdTableRow>) -> Page<Uuid, CommentsByPostIdTableRow> {
+    let comment_id = |request: PageRequest<CommentsByPostIdKey>| match request {
+        PageRequest::First => PageRequest::First,
+        PageRequest::After((_, comment_id)) => PageRequest::After(comment_id),
+        PageRequest::Before((_, comment_id)) => PageRequest::Before(comment_id),
+    };
+
+    Page { rows: page.rows, next: page.next.map(comment_id), prev: page.prev.map(comment_id) }
+}
+
+/// A reply must be on the same Post as the Comment it replies to, and deleting a Comment also
+/// deletes every reply to it (and every reply to those replies, and so on).
+pub(crate) trait CommentsByPostIdTableLike: Sync + Send {
+    /// Adds one or more Comments. If any Comment replies to a Comment which does not exist on the
+    /// same Post, no Comments are added.
+    fn insert(&mut self, rows: Vec<CommentsByPostIdTableRow>) -> Result<Vec<CommentsByPostIdKey>, DbError>;
+
+    fn get(&self, post_id: &Uuid, comment_id: &Uuid) -> Result<CommentsByPostIdTableRow, DbError>;
+
+    /// Lists one page of the Comments on one Post, oldest first. Replies are listed alongside the
+    /// Comments they reply to, so that clients can arrange them into threads using `parent_id`.
+    fn list_page(&self, post_id: &Uuid, page: &PageRequest<Uuid>, limit: usize) -> Result<Page<Uuid, CommentsByPostIdTableRow>, DbError>;
+
+    /// Deletes one Comment, along with all of its replies.
+    fn delete(&mut self, post_id: &Uuid, comment_id: &Uuid) -> Result<CommentsByPostIdKey, DbError>;
+}
@@ -0,0 +1,44 @@
+use crate::db::error::DbError;
+use crate::db::postgres::tables::follows::follows;
+use crate::db::table::TableRow;
+use crate::model::post::now;
+use chrono::{DateTime, Utc};
+use diesel::{Insertable, Queryable, Selectable};
+use serde::Serialize;
+use std::fmt::Debug;
+use uuid::Uuid;
+
+/// Each User can follow each author at most once.
+pub(in crate::db) type FollowsKey = (Uuid, Uuid);
+
+#[derive(Clone, Debug, Serialize, Insertable, Queryable, Selectable)]
+#[diesel(table_name = follows)] // FIXME model should not depend on Postgres impl (diesel)
+#[diesel(primary_key(follower_id, author_id))]
+pub(crate) struct FollowsTableRow {
+    pub(in crate::db) follower_id: Uuid,
+    pub(in crate::db) author_id: Uuid,
+    pub(in crate::db) created_at: DateTime<Utc>,
+}
+
+impl FollowsTableRow {
+    pub(crate) fn new(follower_id: Uuid, author_id: Uuid) -> Self {
+        Self { follower_id, author_id, created_at: now() }
+    }
+}
+
+impl TableRow<FollowsKey> for FollowsTableRow {
+    fn primary_key(&self) -> FollowsKey {
+        (self.follower_id, self.author_id)
+    }
+}
+
+// Feeds are built by the posts_by_id table, from the authors in this table -- see
+// PostsByIdTableLike::feed().
+pub(crate) trait FollowsTableLike: Sync + Send {
+    /// Follows an author, unless the User already follows them. Returns whether the User was not
+    /// already following the author.
+    fn insert(&mut self, row: FollowsTableRow) -> Result<bool, DbError>;
+
+    /// Stops following an author.
+    fn delete(&mut self, follower_id: &Uuid, author_id: &Uuid) -> Result<(), DbError>;
+}
@@ -0,0 +1,60 @@
+use crate::db::error::DbError;
+use crate::db::postgres::tables::media::media;
+use crate::db::table::TableRow;
+use crate::model::media::{Media, MediaId, UploaderId};
+use chrono::{DateTime, Utc};
+use diesel::{Insertable, Queryable, Selectable};
+use serde::Serialize;
+use std::fmt::Debug;
+use uuid::Uuid;
+
+#[derive(Clone, Debug, Serialize, Insertable, Queryable, Selectable)]
+#[diesel(table_name = media)] // FIXME model should not depend on Postgres impl (diesel)
+#[diesel(primary_key(media_id))]
+pub(crate) struct MediaTableRow {
+    pub(in crate::db) media_id: Uuid,
+    pub(in crate::db) uploader_id: Uuid,
+    pub(in crate::db) content_type: String,
+    pub(in crate::db) size_bytes: i64,
+    pub(in crate::db) created_at: DateTime<Utc>,
+}
+
+impl TableRow<Uuid> for MediaTableRow {
+    fn primary_key(&self) -> Uuid {
+        self.media_id
+    }
+}
+
+impl From<Media> for MediaTableRow {
+    fn from(value: Media) -> Self {
+        Self {
+            media_id: value.media_id.0,
+            uploader_id: value.uploader_id.0,
+            content_type: value.media_type.to_string(),
+            size_bytes: value.size_bytes,
+            created_at: value.created_at,
+        }
+    }
+}
+
+impl TryFrom<MediaTableRow> for Media {
+    type Error = String;
+
+    fn try_from(value: MediaTableRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            media_id: MediaId(value.media_id),
+            uploader_id: UploaderId(value.uploader_id),
+            media_type: value.content_type.parse()?,
+            size_bytes: value.size_bytes,
+            created_at: value.created_at,
+        })
+    }
+}
+
+// Only what is known about each file is kept in the database. Its contents are kept in a media
+// store -- see MediaStoreLike.
+pub(crate) trait MediaTableLike: Sync + Send {
+    fn insert(&mut self, row: MediaTableRow) -> Result<Uuid, DbError>;
+
+    fn get(&self, key: &Uuid) -> Result<MediaTableRow, DbError>;
+}
@@ -0,0 +1,60 @@
+use crate::db::error::DbError;
+use crate::db::postgres::tables::post_reactions::post_reactions;
+use crate::db::table::TableRow;
+use crate::model::reaction::Reaction;
+use chrono::{DateTime, Utc};
+use diesel::{Insertable, Queryable, Selectable};
+use serde::Serialize;
+use std::fmt::Debug;
+use uuid::Uuid;
+
+/// Each user can add at most one Reaction of each kind to each Post.
+pub(in crate::db) type PostReactionsKey = (Uuid, Uuid, String);
+
+#[derive(Clone, Debug, Serialize, Insertable, Queryable, Selectable)]
+#[diesel(table_name = post_reactions)] // FIXME model should not depend on Postgres impl (diesel)
+#[diesel(primary_key(post_id, user_id, kind))]
+pub(crate) struct PostReactionsTableRow {
+    pub(in crate::db) post_id: Uuid,
+    pub(in crate::db) user_id: Uuid,
+    pub(in crate::db) kind: String,
+    pub(in crate::db) created_at: DateTime<Utc>,
+}
+
+impl TableRow<PostReactionsKey> for PostReactionsTableRow {
+    fn primary_key(&self) -> PostReactionsKey {
+        (self.post_id, self.user_id, self.kind.clone())
+    }
+}
+
+impl From<Reaction> for PostReactionsTableRow {
+    fn from(value: Reaction) -> Self {
+        Self {
+            post_id: value.post_id.0,
+            user_id: value.user_id,
+            kind: value.kind.0,
+            created_at: value.created_at,
+        }
+    }
+}
+
+/// How many Reactions of one kind one Post has.
+#[derive(Clone, Debug)]
+pub(crate) struct ReactionCount {
+    pub(crate) post_id: Uuid,
+    pub(crate) kind: String,
+    pub(crate) count: i64,
+}
+
+pub(crate) trait PostReactionsTableLike: Sync + Send {
+    /// Adds a Reaction, unless the same user has already added the same kind of Reaction to the
+    /// same Post. Returns whether the Reaction was added.
+    fn insert(&mut self, row: PostReactionsTableRow) -> Result<bool, DbError>;
+
+    /// Removes one user's Reaction of one kind from one Post.
+    fn delete(&mut self, post_id: &Uuid, user_id: &Uuid, kind: &str) -> Result<(), DbError>;
+
+    /// Counts the Reactions of each kind on any of the given Posts, in a single query. Kinds
+    /// without any Reactions are not listed.
+    fn count_for_posts(&self, post_ids: &[Uuid]) -> Result<Vec<ReactionCount>, DbError>;
+}
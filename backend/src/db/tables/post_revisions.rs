@@ -0,0 +1,89 @@
+use crate::db::error::DbError;
+use crate::db::page::{Page, PageRequest};
+use crate::db::postgres::tables::post_revisions::post_revisions;
+use crate::db::table::TableRow;
+use crate::model::format::Format;
+use crate::model::post::{Body, PostId, Revision, Title};
+use chrono::{DateTime, Utc};
+use diesel::{Insertable, Queryable, Selectable};
+use serde::Serialize;
+use std::fmt::Debug;
+use uuid::Uuid;
+
+/// Revisions are identified by the Post they belong to, and their number.
+pub(in crate::db) type PostRevisionsKey = (Uuid, i32);
+
+#[derive(Clone, Debug, Serialize, Insertable, Queryable, Selectable)]
+#[diesel(table_name = post_revisions)] // FIXME model should not depend on Postgres impl (diesel)
+#[diesel(primary_key(post_id, revision))]
+pub(crate) struct PostRevisionsTableRow {
+    pub(in crate::db) post_id: Uuid,
+    pub(in crate::db) revision: i32,
+    pub(in crate::db) title: String,
+    pub(in crate::db) body: String,
+    pub(in crate::db) created_at: DateTime<Utc>,
+    pub(in crate::db) format: String,
+}
+
+impl TableRow<PostRevisionsKey> for PostRevisionsTableRow {
+    fn primary_key(&self) -> PostRevisionsKey {
+        (self.post_id, self.revision)
+    }
+}
+
+impl From<Revision> for PostRevisionsTableRow {
+    fn from(value: Revision) -> Self {
+        Self {
+            post_id: value.post_id.0,
+            revision: value.revision,
+            title: value.title.0,
+            body: value.body.0,
+            created_at: value.created_at,
+            format: value.format.to_string(),
+        }
+    }
+}
+
+impl From<PostRevisionsTableRow> for Revision {
+    fn from(value: PostRevisionsTableRow) -> Self {
+        Self {
+            post_id: PostId(value.post_id),
+            revision: value.revision,
+            title: Title(value.title),
+            body: Body(value.body),
+            created_at: value.created_at,
+            // like posts_by_id, format has a CHECK constraint
+            format: value.format.parse().unwrap_or(Format::Plain),
+        }
+    }
+}
+
+/// Locates a page of one Post's Revisions by (post id, revision number).
+pub(in crate::db) fn with_post_id(post_id: Uuid, request: &PageRequest<i32>) -> PageRequest<PostRevisionsKey> {
+    match request {
+        PageRequest::First => PageRequest::First,
+        PageRequest::After(revision) => PageRequest::After((post_id, *revision)),
+        PageRequest::Before(revision) => PageRequest::Before((post_id, *revision)),
+    }
+}
+
+/// Converts a page of Revisions keyed by (post id, revision number) into a page keyed by revision
+/// number alone, since every Revision on the page belongs to the same Post.
+pub(in crate::db) fn by_revision_number(page: Page<PostRevisionsKey, PostRevisionsTableRow>) -> Page<i32, PostRevisionsTableRow> {
+    let number = |request: PageRequest<PostRevisionsKey>| match request {
+        PageRequest::First => PageRequest::First,
+        PageRequest::After((_, revision)) => PageRequest::After(revision),
+        PageRequest::Before((_, revision)) => PageRequest::Before(revision),
+    };
+
+    Page { rows: page.rows, next: page.next.map(number), prev: page.prev.map(number) }
+}
+
+// Revisions are only ever written by the posts_by_id table, whenever a Post is inserted or
+// updated, so that a Post and its latest Revision are always written in the same transaction.
+pub(crate) trait PostRevisionsTableLike: Sync + Send {
+    fn get(&self, post_id: &Uuid, revision: i32) -> Result<PostRevisionsTableRow, DbError>;
+
+    /// Lists one page of the Revisions of one Post, oldest first.
+    fn list_page(&self, post_id: &Uuid, page: &PageRequest<i32>, limit: usize) -> Result<Page<i32, PostRevisionsTableRow>, DbError>;
+}
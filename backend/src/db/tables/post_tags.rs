@@ -0,0 +1,47 @@
+use crate::db::error::DbError;
+use crate::db::postgres::tables::post_tags::post_tags;
+use crate::db::table::TableRow;
+use crate::model::post::PostId;
+use crate::model::tag::Tag;
+use diesel::{Insertable, Queryable, Selectable};
+use serde::Serialize;
+use std::fmt::Debug;
+use uuid::Uuid;
+
+/// Each row links one Post to one Tag.
+pub(in crate::db) type PostTagsKey = (Uuid, String);
+
+#[derive(Clone, Debug, Serialize, Insertable, Queryable, Selectable)]
+#[diesel(table_name = post_tags)] // FIXME model should not depend on Postgres impl (diesel)
+#[diesel(primary_key(post_id, tag))]
+pub(crate) struct PostTagsTableRow {
+    pub(crate) post_id: Uuid,
+    pub(crate) tag: String,
+}
+
+impl TableRow<PostTagsKey> for PostTagsTableRow {
+    fn primary_key(&self) -> PostTagsKey {
+        (self.post_id, self.tag.clone())
+    }
+}
+
+impl From<(&PostId, Tag)> for PostTagsTableRow {
+    fn from((post_id, tag): (&PostId, Tag)) -> Self {
+        Self { post_id: post_id.0, tag: tag.0 }
+    }
+}
+
+/// How many Posts have a given Tag.
+#[derive(Clone, Debug, Serialize)]
+pub(crate) struct TagCount {
+    pub(in crate::db) tag: String,
+    pub(in crate::db) post_count: i64,
+}
+
+// Posts are tagged when they are inserted or updated, and Tags are counted, and Posts are filtered
+// by Tag, by the posts_by_id table, because only visible Posts should be counted or listed -- see
+// PostsByIdTableLike.
+pub(crate) trait PostTagsTableLike: Sync + Send {
+    /// Lists every Tag on any of the given Posts, in a single query.
+    fn list_for_posts(&self, post_ids: &[Uuid]) -> Result<Vec<PostTagsTableRow>, DbError>;
+}
@@ -1,39 +1,265 @@
-use crate::model::post::Post;
+use crate::db::error::DbError;
+use crate::model::format::Format;
+use crate::model::post::{AuthorId, Body, Post, PostId, Slug, Status, Title};
+use crate::model::tag::Tag;
 use crate::db::postgres::tables::posts_by_id::posts_by_id;
+use crate::db::page::{Page, PageRequest};
 use crate::db::table::TableRow;
-use diesel::{Insertable, Queryable, Selectable};
+use crate::db::tables::post_revisions::PostRevisionsTableRow;
+use crate::db::tables::post_tags::{PostTagsTableRow, TagCount};
+use chrono::{DateTime, SecondsFormat, Utc};
+use diesel::{AsChangeset, Insertable, Queryable, QueryableByName, Selectable};
 use serde::Serialize;
-use std::fmt::Debug;
+use std::collections::BTreeSet;
+use std::fmt::{Debug, Display, Formatter};
+use std::str::FromStr;
 use uuid::Uuid;
 
-#[derive(Clone, Debug, Serialize, Insertable, Queryable, Selectable)]
+#[derive(Clone, Debug, Serialize, Insertable, Queryable, QueryableByName, Selectable, AsChangeset)]
 #[diesel(table_name = posts_by_id)] // FIXME model should not depend on Postgres impl (diesel)
+#[diesel(primary_key(post_id))]
 pub(crate) struct PostsByIdTableRow {
-    post_id: Uuid,
-    author_id: Uuid,
-    title: String,
-    body: String,
+    pub(in crate::db) post_id: Uuid,
+    pub(in crate::db) author_id: Uuid,
+    pub(in crate::db) title: String,
+    pub(in crate::db) body: String,
+    pub(in crate::db) created_at: DateTime<Utc>,
+    pub(in crate::db) updated_at: DateTime<Utc>,
+    pub(in crate::db) status: String,
+    pub(in crate::db) published_at: Option<DateTime<Utc>>,
+    pub(in crate::db) publish_at: Option<DateTime<Utc>>,
+    pub(in crate::db) revision: i32,
+    pub(in crate::db) format: String,
+    pub(in crate::db) body_html: String,
+    pub(in crate::db) slug: String,
+    pub(in crate::db) version: i32,
+}
+
+impl PostsByIdTableRow {
+    pub(crate) fn post_id(&self) -> Uuid {
+        self.post_id
+    }
+
+    pub(crate) fn author_id(&self) -> Uuid {
+        self.author_id
+    }
 }
 
 impl TableRow<Uuid> for PostsByIdTableRow {
-    fn primary_key(&self) -> &Uuid {
-        &self.post_id
+    fn primary_key(&self) -> Uuid {
+        self.post_id
     }
 }
 
 impl From<Post> for PostsByIdTableRow {
     fn from(value: Post) -> Self {
         Self {
-            post_id: value.post_id.0.clone(),
-            author_id: value.author_id.0.clone(),
-            title: value.title.0.clone(),
-            body: value.body.0.clone(),
+            post_id: value.post_id.0,
+            author_id: value.author_id.0,
+            title: value.title.0,
+            body: value.body.0,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+            status: value.status.to_string(),
+            published_at: value.published_at,
+            publish_at: value.publish_at,
+            revision: value.revision,
+            format: value.format.to_string(),
+            body_html: value.body_html,
+            slug: value.slug.0,
+            version: value.version,
         }
     }
 }
 
+impl From<&PostsByIdTableRow> for PostRevisionsTableRow {
+    fn from(value: &PostsByIdTableRow) -> Self {
+        Self::from(Post::from(value.clone()).latest_revision())
+    }
+}
+
+impl From<PostsByIdTableRow> for Post {
+    fn from(value: PostsByIdTableRow) -> Self {
+        Self {
+            post_id: PostId(value.post_id),
+            author_id: AuthorId(value.author_id),
+            title: Title(value.title),
+            body: Body(value.body),
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+            // the status column has a CHECK constraint, so this should never fall back to Draft,
+            // but if it does, it's safer to hide the Post than to show it
+            status: value.status.parse().unwrap_or(Status::Draft),
+            published_at: value.published_at,
+            publish_at: value.publish_at,
+            revision: value.revision,
+            read_revision: value.revision,
+            // like status, format has a CHECK constraint. body_html was rendered when the row was
+            // written, so it is still safe to show, whatever the format
+            format: value.format.parse().unwrap_or(Format::Plain),
+            body_html: value.body_html,
+            slug: Slug(value.slug),
+            version: value.version,
+        }
+    }
+}
+
+/// A changed Post, to be written back to the table, and the version and revision it had when it was
+/// read. Writing it fails with a Conflict if another writer has changed the Post since then -- even
+/// if they only changed its status or schedule, which keep the same revision.
+pub(crate) struct PostsByIdUpdate {
+    pub(in crate::db) row: PostsByIdTableRow, // already at the next version
+    pub(in crate::db) read_version: i32,
+    pub(in crate::db) read_revision: i32,
+    pub(in crate::db) tags: Option<Vec<PostTagsTableRow>>, // replaces every Tag on the Post, if set
+}
+
+impl PostsByIdUpdate {
+    /// Replaces every Tag on the Post with these, when it is written. None leaves its Tags as they are.
+    pub(crate) fn with_tags(mut self, tags: Option<BTreeSet<Tag>>) -> Self {
+        self.tags = tags.map(|tags| tags.into_iter().map(|tag| PostTagsTableRow::from((&PostId(self.row.post_id), tag))).collect());
+        self
+    }
+
+    /// Whether the title, body or format changed, so that a new Revision must be recorded. Other
+    /// changes (like publishing) keep the same revision.
+    pub(in crate::db) fn is_edit(&self) -> bool {
+        self.row.revision != self.read_revision
+    }
+}
+
+impl From<Post> for PostsByIdUpdate {
+    fn from(value: Post) -> Self {
+        let read_revision = value.read_revision;
+        let mut row = PostsByIdTableRow::from(value);
+        let read_version = row.version;
+        row.version += 1;
+        Self { row, read_version, read_revision, tags: None }
+    }
+}
+
+/// Which Posts can be seen by whoever is listing them.
+#[derive(Clone, Debug, Default)]
+pub(crate) enum Visibility {
+    /// Every Post, whatever its status. Only admins should be able to see these.
+    #[default]
+    All,
+
+    /// Only published Posts. This is all that anonymous users can see.
+    Published,
+
+    /// Published Posts, plus every Post (including drafts) by this author.
+    PublishedOrBy(Uuid),
+}
+
+/// Conditions which Posts must meet to be listed. Conditions which are not set always match.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct PostsByIdFilter {
+    pub(crate) author_id: Option<Uuid>,
+    pub(crate) created_after: Option<DateTime<Utc>>, // exclusive
+    pub(crate) created_before: Option<DateTime<Utc>>, // exclusive
+    pub(crate) title_contains: Option<String>, // case-insensitive
+    pub(crate) tag: Option<String>, // a slug -- see model::tag::Tag
+    pub(crate) visibility: Visibility,
+}
+
+/// Where a Post is in a feed. Feeds list Posts newest first, by when they were published, and
+/// Posts published at the same moment by id.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct FeedPosition {
+    pub(in crate::db) published_at: DateTime<Utc>,
+    pub(in crate::db) post_id: Uuid,
+}
+
+impl FeedPosition {
+    /// A position older than every published Post, so the page before it holds the oldest Posts.
+    pub(crate) fn origin() -> Self {
+        Self { published_at: DateTime::UNIX_EPOCH, post_id: Uuid::nil() }
+    }
+}
+
+impl Display for FeedPosition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}_{}", self.published_at.to_rfc3339_opts(SecondsFormat::Micros, true), self.post_id)
+    }
+}
+
+impl FromStr for FeedPosition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("cannot parse {} as a feed position", s);
+        let (published_at, post_id) = s.split_once('_').ok_or_else(invalid)?;
+
+        Ok(FeedPosition {
+            published_at: DateTime::parse_from_rfc3339(published_at).map_err(|_| invalid())?.to_utc(),
+            post_id: post_id.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// A published Post, paged through by its position in a feed, rather than by its id.
+pub(in crate::db) struct FeedEntry(pub(in crate::db) PostsByIdTableRow);
+
+impl TableRow<FeedPosition> for FeedEntry {
+    fn primary_key(&self) -> FeedPosition {
+        // only published Posts are in feeds, and every published Post has been published
+        FeedPosition { published_at: self.0.published_at.unwrap_or(self.0.created_at), post_id: self.0.post_id }
+    }
+}
+
+/// Unwraps the Posts on a page of a feed.
+pub(in crate::db) fn feed_page(page: Page<FeedPosition, FeedEntry>) -> Page<FeedPosition, PostsByIdTableRow> {
+    Page { rows: page.rows.into_iter().map(|entry| entry.0).collect(), next: page.next, prev: page.prev }
+}
+
+/// A Post which matches a search query.
+#[derive(Clone, Debug, Serialize)]
+pub(crate) struct SearchResult {
+    #[serde(flatten)]
+    pub(crate) post: PostsByIdTableRow,
+
+    /// How well the Post matches the query. Higher is better. Ranks can only be compared with
+    /// other ranks from the same search.
+    pub(crate) rank: f32,
+
+    /// A short, HTML-escaped excerpt of the body, with matching words wrapped in <mark> tags.
+    pub(crate) snippet: String,
+}
+
+/// Inserting or updating a Post also records its latest Revision (see PostRevisionsTableLike), claims
+/// its slug (see PostsBySlugTableLike), and writes its Tags (see PostTagsTableLike), all at once.
+/// If another Post already has that slug, the first free slug with a numeric suffix is claimed
+/// instead, e.g. "hello-world-2". Deleting a Post also deletes its Revisions, Tags, Reactions,
+/// Comments and slugs.
+///
+/// Updates are only written if every Post still has the revision it was read with. Otherwise, none
+/// of them are written, so that two edits made at the same time (by different replicas) cannot
+/// both claim the same Revision.
 pub(crate) trait PostsByIdTableLike: Sync + Send {
-    fn insert(&mut self, row: Vec<PostsByIdTableRow>) -> Result<Vec<Uuid>, String>;
-    fn get(&self, key: &Uuid) -> Result<PostsByIdTableRow, String>;
-    fn list(&self, limit: usize) -> Result<Vec<PostsByIdTableRow>, String>;
-}
\ No newline at end of file
+    fn insert(&mut self, rows: Vec<PostsByIdTableRow>, tags: Vec<PostTagsTableRow>) -> Result<Vec<Uuid>, DbError>;
+    fn get(&self, key: &Uuid) -> Result<PostsByIdTableRow, DbError>;
+    fn list_page(&self, page: &PageRequest<Uuid>, limit: usize, filter: &PostsByIdFilter) -> Result<Page<Uuid, PostsByIdTableRow>, DbError>;
+    fn update(&mut self, updates: Vec<PostsByIdUpdate>) -> Result<Vec<Uuid>, DbError>;
+    fn delete(&mut self, keys: Vec<Uuid>) -> Result<Vec<Uuid>, DbError>;
+
+    /// Publishes at most `limit` Posts which were scheduled to be published at or before `now`,
+    /// and returns their ids. Posts which are being published by another replica are skipped.
+    fn publish_due(&mut self, now: DateTime<Utc>, limit: usize) -> Result<Vec<Uuid>, DbError>;
+
+    /// Counts how many Posts with each Tag are visible, sorted by Tag. Tags without any visible
+    /// Posts are not listed.
+    fn tag_counts(&self, visibility: &Visibility) -> Result<Vec<TagCount>, DbError>;
+
+    /// Finds at most `limit` visible Posts whose titles or bodies contain every word in the query,
+    /// best matches first. Matches in titles rank above matches in bodies.
+    fn search(&self, query: &str, visibility: &Visibility, limit: usize) -> Result<Vec<SearchResult>, DbError>;
+
+    /// Lists one page of the published Posts by every author that a User follows (see
+    /// FollowsTableLike), newest first. The next page holds older Posts.
+    fn feed(&self, follower_id: &Uuid, page: &PageRequest<FeedPosition>, limit: usize) -> Result<Page<FeedPosition, PostsByIdTableRow>, DbError>;
+
+    /// Lists one page of the published Posts by one author, or by every author if there is no
+    /// author_id, newest first, like feed(). The next page holds older Posts.
+    fn published(&self, author_id: Option<&Uuid>, page: &PageRequest<FeedPosition>, limit: usize) -> Result<Page<FeedPosition, PostsByIdTableRow>, DbError>;
+}
@@ -0,0 +1,51 @@
+use crate::db::error::DbError;
+use crate::db::postgres::tables::posts_by_slug::posts_by_slug;
+use crate::db::table::TableRow;
+use crate::model::post::{now, Slug};
+use chrono::{DateTime, Utc};
+use diesel::{Insertable, Queryable, Selectable};
+use serde::Serialize;
+use std::fmt::Debug;
+use uuid::Uuid;
+
+/// Each row links one slug to the Post which has (or had) that slug. A slug belongs to at most one
+/// Post, forever, so that old links never lead to the wrong Post.
+#[derive(Clone, Debug, Serialize, Insertable, Queryable, Selectable)]
+#[diesel(table_name = posts_by_slug)] // FIXME model should not depend on Postgres impl (diesel)
+#[diesel(primary_key(slug))]
+pub(crate) struct PostsBySlugTableRow {
+    pub(in crate::db) slug: String,
+    pub(in crate::db) post_id: Uuid,
+    pub(in crate::db) created_at: DateTime<Utc>,
+}
+
+impl PostsBySlugTableRow {
+    pub(in crate::db) fn new(slug: String, post_id: Uuid) -> Self {
+        Self { slug, post_id, created_at: now() }
+    }
+
+    pub(crate) fn post_id(&self) -> Uuid {
+        self.post_id
+    }
+}
+
+impl TableRow<String> for PostsBySlugTableRow {
+    fn primary_key(&self) -> String {
+        self.slug.clone()
+    }
+}
+
+/// Picks the first slug which is either free, or already belongs to this Post (e.g. when a title is
+/// changed back to what it was). `owner` returns the id of the Post which has a slug, if any.
+pub(in crate::db) fn first_free(wanted: &str, post_id: &Uuid, owner: impl Fn(&str) -> Option<Uuid>) -> String {
+    Slug(wanted.to_string()).candidates()
+        .find(|candidate| owner(candidate).is_none_or(|owner| owner == *post_id))
+        .expect("there are infinitely many candidate slugs")
+}
+
+// Slugs are claimed by the posts_by_id table, whenever a Post is inserted or updated, so that the
+// slug in the Post and the slugs in this table always agree -- see PostsByIdTableLike.
+pub(crate) trait PostsBySlugTableLike: Sync + Send {
+    /// Finds the Post which has, or used to have, a slug.
+    fn get(&self, slug: &str) -> Result<PostsBySlugTableRow, DbError>;
+}
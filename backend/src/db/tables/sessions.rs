@@ -0,0 +1,87 @@
+use crate::auth::User;
+use crate::db::error::DbError;
+use crate::db::postgres::tables::sessions::sessions;
+use crate::db::table::TableRow;
+use crate::model::post::now;
+use chrono::{DateTime, Utc};
+use diesel::{Insertable, Queryable, Selectable};
+use std::fmt::Debug;
+use uuid::Uuid;
+
+/// One logged-in session. The token itself is never stored, only its hash, so that anyone who can
+/// read this table still cannot use it to log in as someone else. The User's refresh token (which
+/// could be used to log in to the identity provider) is only stored encrypted with a key derived
+/// from the token, so it cannot be read without the token either -- see auth::sessions::postgres.
+#[derive(Clone, Debug, Insertable, Queryable, Selectable)]
+#[diesel(table_name = sessions)] // FIXME model should not depend on Postgres impl (diesel)
+#[diesel(primary_key(token_hash))]
+pub(crate) struct SessionsTableRow {
+    pub(in crate::db) token_hash: String,
+    pub(in crate::db) user_id: Uuid,
+    pub(in crate::db) username: String,
+    pub(in crate::db) roles: Vec<String>,
+    pub(in crate::db) display_name: Option<String>,
+    pub(in crate::db) encrypted_refresh_token: Option<String>,
+    pub(in crate::db) created_at: DateTime<Utc>,
+    pub(in crate::db) expires_at: DateTime<Utc>,
+}
+
+impl SessionsTableRow {
+    /// The User's own refresh token is not stored, only `encrypted_refresh_token`.
+    pub(crate) fn new(token_hash: String, user: User, encrypted_refresh_token: Option<String>) -> Self {
+        Self {
+            token_hash,
+            user_id: user.id,
+            username: user.name,
+            roles: user.roles,
+            display_name: user.display_name,
+            encrypted_refresh_token,
+            created_at: now(),
+            expires_at: DateTime::from_timestamp(user.expires_at as i64, 0).unwrap_or(DateTime::<Utc>::MAX_UTC),
+        }
+    }
+}
+
+impl TableRow<String> for SessionsTableRow {
+    fn primary_key(&self) -> String {
+        self.token_hash.clone()
+    }
+}
+
+impl SessionsTableRow {
+    /// The User logged in with this session (without their refresh token), and their encrypted
+    /// refresh token, if they have one.
+    pub(crate) fn into_user(self) -> (User, Option<String>) {
+        let user = User {
+            name: self.username,
+            id: self.user_id,
+            roles: self.roles,
+            expires_at: self.expires_at.timestamp().max(0) as u64,
+            display_name: self.display_name,
+            refresh_token: None,
+        };
+
+        (user, self.encrypted_refresh_token)
+    }
+}
+
+// Sessions are read and written by the session store (see auth::sessions), not by handlers.
+pub(crate) trait SessionsTableLike: Sync + Send {
+    fn insert(&mut self, row: SessionsTableRow) -> Result<(), DbError>;
+
+    /// Gets a session, whether or not it has expired.
+    fn get(&self, token_hash: &str) -> Result<SessionsTableRow, DbError>;
+
+    /// Deletes a session, returning it.
+    fn delete(&mut self, token_hash: &str) -> Result<SessionsTableRow, DbError>;
+
+    /// Deletes every session which expired before `now`, returning how many were deleted.
+    fn delete_expired(&mut self, now: DateTime<Utc>) -> Result<usize, DbError>;
+
+    /// Deletes all but the `keep` most recently created sessions of one User, returning how many
+    /// were deleted.
+    fn delete_oldest(&mut self, user_id: &Uuid, keep: usize) -> Result<usize, DbError>;
+
+    /// Counts the sessions which have not expired by `now`.
+    fn count_active(&self, now: DateTime<Utc>) -> Result<usize, DbError>;
+}
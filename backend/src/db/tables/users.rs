@@ -0,0 +1,73 @@
+use crate::db::error::DbError;
+use crate::db::postgres::tables::users::users;
+use crate::db::table::TableRow;
+use crate::model::user::{AvatarUrl, Bio, DisplayName, User, UserId};
+use chrono::{DateTime, Utc};
+use diesel::{AsChangeset, Insertable, Queryable, Selectable};
+use serde::Serialize;
+use std::fmt::Debug;
+use uuid::Uuid;
+
+#[derive(Clone, Debug, Serialize, Insertable, Queryable, Selectable, AsChangeset)]
+#[diesel(table_name = users)] // FIXME model should not depend on Postgres impl (diesel)
+#[diesel(primary_key(user_id))]
+#[diesel(treat_none_as_null = true)] // so that a bio or avatar can be removed
+pub(crate) struct UsersTableRow {
+    pub(crate) user_id: Uuid,
+    pub(crate) username: String,
+    pub(crate) display_name: String,
+    pub(crate) bio: Option<String>,
+    pub(crate) avatar_url: Option<String>,
+    pub(crate) created_at: DateTime<Utc>,
+    pub(crate) updated_at: DateTime<Utc>,
+    pub(crate) last_login_at: DateTime<Utc>,
+}
+
+impl TableRow<Uuid> for UsersTableRow {
+    fn primary_key(&self) -> Uuid {
+        self.user_id
+    }
+}
+
+impl From<User> for UsersTableRow {
+    fn from(value: User) -> Self {
+        Self {
+            user_id: value.user_id.0,
+            username: value.username,
+            display_name: value.display_name.0,
+            bio: value.bio.map(|bio| bio.0),
+            avatar_url: value.avatar_url.map(|avatar_url| avatar_url.0),
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+            last_login_at: value.last_login_at,
+        }
+    }
+}
+
+impl From<UsersTableRow> for User {
+    fn from(value: UsersTableRow) -> Self {
+        Self {
+            user_id: UserId(value.user_id),
+            username: value.username,
+            display_name: DisplayName(value.display_name),
+            bio: value.bio.map(Bio),
+            avatar_url: value.avatar_url.map(AvatarUrl),
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+            last_login_at: value.last_login_at,
+        }
+    }
+}
+
+pub(crate) trait UsersTableLike: Sync + Send {
+    /// Records that a User has logged in. New Users are inserted as they are. For existing Users,
+    /// only the username and the time of the login are updated, so that their profiles are kept.
+    fn record_login(&mut self, row: UsersTableRow) -> Result<Uuid, DbError>;
+
+    fn get(&self, key: &Uuid) -> Result<UsersTableRow, DbError>;
+
+    /// Gets every User with one of the given ids, in a single query. Ids without Users are skipped.
+    fn list_for_ids(&self, keys: &[Uuid]) -> Result<Vec<UsersTableRow>, DbError>;
+
+    fn update(&mut self, row: UsersTableRow) -> Result<Uuid, DbError>;
+}
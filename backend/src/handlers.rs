@@ -1,4 +1,15 @@
+pub(crate) mod authors;
+pub(crate) mod feed;
+pub(crate) mod feeds;
 pub(crate) mod misc;
+pub(crate) mod sitemap;
 pub(crate) mod posts;
 pub(crate) mod health;
-pub(crate) mod login;
\ No newline at end of file
+pub(crate) mod login;
+pub(crate) mod logout;
+pub(crate) mod media;
+pub(crate) mod metrics;
+pub(crate) mod pagination;
+pub(crate) mod tags;
+pub(crate) mod token;
+pub(crate) mod users;
\ No newline at end of file
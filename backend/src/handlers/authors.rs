@@ -0,0 +1,19 @@
+use crate::api_error::ApiError;
+use salvo::{Request, Response};
+use std::str::FromStr;
+use uuid::Uuid;
+
+pub(crate) mod follow;
+
+/// Parses the 'author_id' route parameter, rendering an error if it is not a UUID.
+pub(in crate::handlers) fn parse_author_id(req: &Request, res: &mut Response) -> Option<Uuid> {
+    let id: String = req.param::<String>("author_id").expect("request did not contain a 'author_id' param");
+
+    match Uuid::from_str(&id) {
+        Ok(key) => Some(key),
+        Err(_) => {
+            res.render(ApiError::bad_request(format!("cannot parse {} as UUID", id)));
+            None
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use crate::api_error::ApiError;
+use crate::db::tables::follows::FollowsTableRow;
+use crate::db::Database;
+use crate::handlers::authors::parse_author_id;
+use salvo::oapi::endpoint;
+use salvo::{Depot, Request, Response};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// Follow an author, so that their published Posts appear in your feed (see GET /feed).
+///
+/// Following an author twice has no further effect. Only authors who have logged in at least once
+/// (and so have profiles) can be followed, and users cannot follow themselves.
+#[endpoint(
+    parameters(
+        ("author_id" = String, Path, description = "id of the author to follow")
+    ),
+    responses(
+        (status_code = 200, description = "success response"),
+        (status_code = 400, description = "the id is not a UUID, or is the id of the logged-in user", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 401, description = "the user is not logged in", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 404, description = "the author does not exist", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 503, description = "the database is unavailable", body = ApiError, content_type = "application/problem+json")
+    )
+)]
+pub(crate) async fn follow(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    let Some(author_id) = parse_author_id(req, res) else { return };
+    let user_id = *depot.get::<Uuid>("token_user_id").expect("unknown user id");
+
+    if author_id == user_id {
+        res.render(ApiError::bad_request("users cannot follow themselves"));
+        return;
+    }
+
+    let state = depot.obtain::<Arc<Mutex<Database>>>().unwrap();
+    let mut db = state.lock().await;
+
+    if let Err(e) = db.users().get(&author_id) {
+        res.render(ApiError::from_db(&format!("error getting author {}", author_id), e));
+        return;
+    }
+
+    match db.follows().insert(FollowsTableRow::new(user_id, author_id)) {
+        Ok(true) => res.render(format!("followed author {}", author_id)),
+        Ok(false) => res.render(format!("already following author {}", author_id)),
+        Err(e) => res.render(ApiError::from_db("error following author", e)),
+    }
+}
+
+/// Stop following an author.
+#[endpoint(
+    parameters(
+        ("author_id" = String, Path, description = "id of the author to stop following")
+    ),
+    responses(
+        (status_code = 200, description = "success response"),
+        (status_code = 400, description = "the id is not a UUID", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 401, description = "the user is not logged in", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 404, description = "the user does not follow the author", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 503, description = "the database is unavailable", body = ApiError, content_type = "application/problem+json")
+    )
+)]
+pub(crate) async fn unfollow(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    let Some(author_id) = parse_author_id(req, res) else { return };
+    let user_id = *depot.get::<Uuid>("token_user_id").expect("unknown user id");
+
+    let state = depot.obtain::<Arc<Mutex<Database>>>().unwrap();
+    let mut db = state.lock().await;
+
+    match db.follows().delete(&user_id, &author_id) {
+        Ok(()) => res.render(format!("unfollowed author {}", author_id)),
+        Err(e) => res.render(ApiError::from_db(&format!("error unfollowing author {}", author_id), e)),
+    }
+}
@@ -0,0 +1 @@
+pub(crate) mod get;
\ No newline at end of file
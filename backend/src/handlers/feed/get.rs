@@ -0,0 +1,50 @@
+use crate::api_error::ApiError;
+use crate::db::Database;
+use crate::handlers::pagination;
+use crate::handlers::pagination::Paginated;
+use crate::handlers::posts::with_details;
+use salvo::oapi::endpoint;
+use salvo::prelude::Json;
+use salvo::{Depot, Request, Response};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// Returns one page of the logged-in user's feed: the published Posts of every author they follow,
+/// newest first.
+///
+/// Like GET /posts, the response has an `items` list, and opaque `next_cursor` and `prev_cursor`
+/// tokens. The next page holds older Posts, and the previous page holds newer ones.
+#[endpoint(
+    parameters(
+        ("limit" = Option<u32>, Query, description = "maximum number of Posts to return, from 1 to 100 (default 10)"),
+        ("cursor" = Option<String>, Query, description = "cursor from a previous response; omit to get the newest Posts")
+    ),
+    responses(
+        (status_code = 200, description = "success response"),
+        (status_code = 400, description = "the limit is out of range, or the cursor is malformed", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 401, description = "the user is not logged in", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 503, description = "the database is unavailable", body = ApiError, content_type = "application/problem+json")
+    )
+)]
+pub(crate) async fn many(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    let user_id = *depot.get::<Uuid>("token_user_id").expect("unknown user id");
+    let Some(limit) = pagination::requested_limit(req, res) else { return };
+    let Some(page) = pagination::requested_page(req, res) else { return };
+
+    let state = depot.obtain::<Arc<Mutex<Database>>>().unwrap();
+    let mut db = state.lock().await;
+
+    let posts = match db.posts_by_id().feed(&user_id, &page, limit) {
+        Ok(posts) => Paginated::from(posts),
+        Err(e) => {
+            res.render(ApiError::from_db("error getting feed", e));
+            return;
+        }
+    };
+
+    match posts.try_map(|posts| with_details(&mut db, posts)) {
+        Err(e) => res.render(ApiError::from_db("error getting Tags and Reactions of Posts", e)),
+        Ok(posts) => res.render(Json(posts)),
+    }
+}
@@ -0,0 +1,93 @@
+use crate::api_error::ApiError;
+use crate::db::page::PageRequest;
+use crate::db::tables::users::UsersTableRow;
+use crate::db::Database;
+use crate::model::post::Post;
+use chrono::{DateTime, Utc};
+use salvo::http::header::{CONTENT_TYPE, ETAG, IF_NONE_MATCH};
+use salvo::http::{HeaderValue, StatusCode};
+use salvo::{Request, Response};
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use uuid::Uuid;
+
+pub(crate) mod atom;
+pub(crate) mod rss;
+
+/// How many of the latest Posts are in each feed.
+const FEED_LENGTH: usize = 20;
+
+/// A published Post, as it appears in a feed.
+struct FeedItem {
+    post: Post,
+    author_name: String,
+}
+
+/// Escapes text so that it can be put anywhere in an XML document, in an element or an attribute.
+pub(in crate::handlers) fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;").replace('\'', "&apos;")
+}
+
+/// When a feed last changed: when its most recently updated Post was last updated. An empty feed
+/// never changes, so it is given a fixed time, rather than the current time, which would change
+/// its ETag on every request.
+fn updated(items: &[FeedItem]) -> DateTime<Utc> {
+    items.iter().map(|item| item.post.updated_at).max().unwrap_or(DateTime::UNIX_EPOCH)
+}
+
+/// Gets the latest published Posts (by one author, or by everyone), newest first, along with the
+/// names of their authors. Renders an error and returns None if the database fails.
+fn latest(db: &mut Database, author_id: Option<&Uuid>, res: &mut Response) -> Option<Vec<FeedItem>> {
+    let posts = match db.posts_by_id().published(author_id, &PageRequest::First, FEED_LENGTH) {
+        Ok(page) => page.rows,
+        Err(e) => {
+            res.render(ApiError::from_db("error getting published Posts", e));
+            return None;
+        }
+    };
+
+    let author_ids: Vec<Uuid> = posts.iter().map(|post| post.author_id()).collect();
+
+    let authors: HashMap<Uuid, String> = match db.users().list_for_ids(&author_ids) {
+        Ok(users) => users.into_iter().map(|UsersTableRow { user_id, display_name, .. }| (user_id, display_name)).collect(),
+        Err(e) => {
+            res.render(ApiError::from_db("error getting authors of published Posts", e));
+            return None;
+        }
+    };
+
+    Some(posts.into_iter().map(|row| {
+        let author_name = authors.get(&row.author_id()).cloned().unwrap_or_else(|| String::from("anonymous"));
+        FeedItem { post: Post::from(row), author_name }
+    }).collect())
+}
+
+/// Whether the client already has the version of a document with this ETag, according to its
+/// If-None-Match header.
+pub(in crate::handlers) fn is_cached(req: &Request, etag: &str) -> bool {
+    req.header::<String>(IF_NONE_MATCH).is_some_and(|header| {
+        header.split(',')
+            .map(|candidate| candidate.trim().trim_start_matches("W/"))
+            .any(|candidate| candidate == etag || candidate == "*")
+    })
+}
+
+/// Renders an XML document (a feed or a sitemap) with an ETag, so that clients can ask for it
+/// again with If-None-Match, and get an empty 304 Not Modified response if it has not changed.
+pub(in crate::handlers) fn render_xml(req: &Request, res: &mut Response, content_type: &'static str, xml: String) {
+    let mut hasher = DefaultHasher::new();
+    xml.hash(&mut hasher);
+    let etag = format!("\"{:016x}\"", hasher.finish());
+
+    if let Ok(value) = HeaderValue::from_str(&etag) {
+        res.headers_mut().insert(ETAG, value);
+    }
+
+    if is_cached(req, &etag) {
+        res.status_code(StatusCode::NOT_MODIFIED);
+        return;
+    }
+
+    res.headers_mut().insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+    res.body(xml);
+}
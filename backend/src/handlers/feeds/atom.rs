@@ -0,0 +1,101 @@
+use crate::api_error::ApiError;
+use crate::db::Database;
+use crate::handlers::authors::parse_author_id;
+use crate::handlers::feeds::{escape_xml, latest, render_xml, updated, FeedItem};
+use crate::model::site::Site;
+use chrono::{DateTime, SecondsFormat, Utc};
+use salvo::oapi::endpoint;
+use salvo::{Depot, Request, Response};
+use std::fmt::Write;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+const CONTENT_TYPE: &str = "application/atom+xml; charset=utf-8";
+
+fn timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Builds an Atom feed. See https://www.rfc-editor.org/rfc/rfc4287
+fn atom(site: &Site, id: &str, title: &str, items: &[FeedItem]) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+
+    // writing to a String cannot fail
+    let _ = writeln!(xml, "  <title>{}</title>", escape_xml(title));
+    let _ = writeln!(xml, "  <link href=\"{}/\"/>", escape_xml(&site.url));
+    let _ = writeln!(xml, "  <id>{}</id>", escape_xml(id));
+    let _ = writeln!(xml, "  <updated>{}</updated>", timestamp(updated(items)));
+
+    for FeedItem { post, author_name } in items {
+        xml.push_str("  <entry>\n");
+        let _ = writeln!(xml, "    <title>{}</title>", escape_xml(&post.title.0));
+        let _ = writeln!(xml, "    <link href=\"{}\"/>", escape_xml(&site.post_url(&post.slug.0)));
+        let _ = writeln!(xml, "    <id>urn:uuid:{}</id>", post.post_id.0);
+        let _ = writeln!(xml, "    <published>{}</published>", timestamp(post.published_at.unwrap_or(post.created_at)));
+        let _ = writeln!(xml, "    <updated>{}</updated>", timestamp(post.updated_at));
+        let _ = writeln!(xml, "    <author><name>{}</name></author>", escape_xml(author_name));
+        let _ = writeln!(xml, "    <content type=\"html\">{}</content>", escape_xml(&post.body_html));
+        xml.push_str("  </entry>\n");
+    }
+
+    xml.push_str("</feed>\n");
+    xml
+}
+
+/// Atom feed of the latest published Posts, by every author.
+///
+/// The response has an ETag. Send it back in an If-None-Match header to get an empty 304 response
+/// if the feed has not changed.
+#[endpoint(
+    responses(
+        (status_code = 200, description = "success response", content_type = "application/atom+xml"),
+        (status_code = 304, description = "the feed has not changed since the client last got it"),
+        (status_code = 503, description = "the database is unavailable", body = ApiError, content_type = "application/problem+json")
+    )
+)]
+pub(crate) async fn all(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    let site = depot.obtain::<Site>().expect("Site was not injected into the depot");
+    let state = depot.obtain::<Arc<Mutex<Database>>>().unwrap();
+    let mut db = state.lock().await;
+
+    let Some(items) = latest(&mut db, None, res) else { return };
+
+    render_xml(req, res, CONTENT_TYPE, atom(site, &format!("{}/", site.url), &site.title, &items));
+}
+
+/// Atom feed of the latest published Posts by one author.
+///
+/// The response has an ETag. Send it back in an If-None-Match header to get an empty 304 response
+/// if the feed has not changed.
+#[endpoint(
+    parameters(
+        ("author_id" = String, Path, description = "id of the author")
+    ),
+    responses(
+        (status_code = 200, description = "success response", content_type = "application/atom+xml"),
+        (status_code = 304, description = "the feed has not changed since the client last got it"),
+        (status_code = 400, description = "the id is not a UUID", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 404, description = "the author has no published Posts", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 503, description = "the database is unavailable", body = ApiError, content_type = "application/problem+json")
+    )
+)]
+pub(crate) async fn author(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    let Some(author_id) = parse_author_id(req, res) else { return };
+
+    let site = depot.obtain::<Site>().expect("Site was not injected into the depot");
+    let state = depot.obtain::<Arc<Mutex<Database>>>().unwrap();
+    let mut db = state.lock().await;
+
+    let Some(items) = latest(&mut db, Some(&author_id), res) else { return };
+
+    // Posts by authors who have never logged in are still published, under "anonymous", so only
+    // an author with nothing published has no feed
+    let Some(author_name) = items.first().map(|item| item.author_name.clone()) else {
+        res.render(ApiError::not_found(format!("author {} has no published Posts", author_id)));
+        return;
+    };
+
+    let id = format!("{}/authors/{}", site.url, author_id);
+    let title = format!("{}: {}", site.title, author_name);
+    render_xml(req, res, CONTENT_TYPE, atom(site, &id, &title, &items));
+}
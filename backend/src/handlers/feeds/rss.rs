@@ -0,0 +1,55 @@
+use crate::api_error::ApiError;
+use crate::db::Database;
+use crate::handlers::feeds::{escape_xml, latest, render_xml, updated, FeedItem};
+use crate::model::site::Site;
+use salvo::oapi::endpoint;
+use salvo::{Depot, Request, Response};
+use std::fmt::Write;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Builds an RSS 2.0 feed. See https://www.rssboard.org/rss-specification
+fn rss(site: &Site, items: &[FeedItem]) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<rss version=\"2.0\">\n  <channel>\n");
+
+    // writing to a String cannot fail
+    let _ = writeln!(xml, "    <title>{}</title>", escape_xml(&site.title));
+    let _ = writeln!(xml, "    <link>{}/</link>", escape_xml(&site.url));
+    let _ = writeln!(xml, "    <description>The latest Posts on {}</description>", escape_xml(&site.title));
+    let _ = writeln!(xml, "    <lastBuildDate>{}</lastBuildDate>", updated(items).to_rfc2822());
+
+    // RSS only allows email addresses in <author>, which we don't have, so authors are left out
+    for FeedItem { post, .. } in items {
+        xml.push_str("    <item>\n");
+        let _ = writeln!(xml, "      <title>{}</title>", escape_xml(&post.title.0));
+        let _ = writeln!(xml, "      <link>{}</link>", escape_xml(&site.post_url(&post.slug.0)));
+        let _ = writeln!(xml, "      <guid isPermaLink=\"false\">urn:uuid:{}</guid>", post.post_id.0);
+        let _ = writeln!(xml, "      <pubDate>{}</pubDate>", post.published_at.unwrap_or(post.created_at).to_rfc2822());
+        let _ = writeln!(xml, "      <description>{}</description>", escape_xml(&post.body_html));
+        xml.push_str("    </item>\n");
+    }
+
+    xml.push_str("  </channel>\n</rss>\n");
+    xml
+}
+
+/// RSS feed of the latest published Posts, by every author.
+///
+/// The response has an ETag. Send it back in an If-None-Match header to get an empty 304 response
+/// if the feed has not changed.
+#[endpoint(
+    responses(
+        (status_code = 200, description = "success response", content_type = "application/rss+xml"),
+        (status_code = 304, description = "the feed has not changed since the client last got it"),
+        (status_code = 503, description = "the database is unavailable", body = ApiError, content_type = "application/problem+json")
+    )
+)]
+pub(crate) async fn all(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    let site = depot.obtain::<Site>().expect("Site was not injected into the depot");
+    let state = depot.obtain::<Arc<Mutex<Database>>>().unwrap();
+    let mut db = state.lock().await;
+
+    let Some(items) = latest(&mut db, None, res) else { return };
+
+    render_xml(req, res, "application/rss+xml; charset=utf-8", rss(site, &items));
+}
@@ -1,3 +1,59 @@
 pub(crate) mod keycloak_token;
 
-pub(crate) mod username_and_password;
\ No newline at end of file
+pub(crate) mod username_and_password;
+
+use crate::auth::Session;
+use crate::auth_middleware::{CSRF_COOKIE, SESSION_COOKIE};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine as _;
+use chrono::{DateTime, Utc};
+use rand::prelude::*;
+use salvo::http::cookie::time::Duration;
+use salvo::http::cookie::{Cookie, SameSite};
+use salvo::prelude::Json;
+use salvo::{Request, Response};
+use serde::Serialize;
+
+/// What a browser is given when it logs in with ?cookie=true. The token itself is only ever sent
+/// in the HttpOnly session cookie, so scripts on the page cannot read it.
+#[derive(Serialize)]
+struct CookieSession {
+    expires_at: DateTime<Utc>,
+    csrf_token: String, // also in the CSRF cookie; send it in the x-csrf-token header with every POST, PUT, or DELETE
+}
+
+fn cookie(name: &'static str, value: String, http_only: bool, max_age: Duration) -> Cookie<'static> {
+    Cookie::build((name, value)).path("/").secure(true).http_only(http_only).same_site(SameSite::Strict).max_age(max_age).build()
+}
+
+/// Whether a login request asked for the session to be kept in cookies, rather than returned.
+pub(crate) fn wants_cookie(req: &Request) -> bool {
+    req.query::<bool>("cookie").unwrap_or(false)
+}
+
+/// Renders a new Session, either as JSON or, for browsers, as a session cookie and a CSRF cookie.
+pub(crate) fn render_session(session: Session, as_cookie: bool, res: &mut Response) {
+    if !as_cookie {
+        res.render(session);
+        return;
+    }
+
+    let max_age = Duration::seconds((session.expires_at - Utc::now()).num_seconds().max(0));
+
+    let mut random_bytes = [0u8; 32];
+    rand::rng().fill_bytes(&mut random_bytes);
+    let csrf_token = URL_SAFE_NO_PAD.encode(random_bytes);
+
+    res.add_cookie(cookie(SESSION_COOKIE, String::from(session.token.as_str()), true, max_age));
+    res.add_cookie(cookie(CSRF_COOKIE, csrf_token.clone(), false, max_age));
+    res.render(Json(CookieSession { expires_at: session.expires_at, csrf_token }));
+}
+
+/// Tells the browser to forget the session and CSRF cookies.
+pub(crate) fn clear_cookies(res: &mut Response) {
+    for (name, http_only) in [(SESSION_COOKIE, true), (CSRF_COOKIE, false)] {
+        let mut cookie = cookie(name, String::new(), http_only, Duration::ZERO);
+        cookie.make_removal();
+        res.add_cookie(cookie);
+    }
+}
@@ -1,4 +1,6 @@
+use crate::api_error::ApiError;
 use crate::auth::Authenticator;
+use crate::handlers::login::{render_session, wants_cookie};
 use reqwest::StatusCode;
 use salvo::oapi::endpoint;
 use salvo::{Depot, Request, Response};
@@ -10,14 +12,25 @@ fn extract(header_name: &str, req: &Request, res: &mut Response) -> Option<Strin
     match req.header::<String>(header_name) {
         Some(header) => Some(header),
         _ => {
-            res.status_code(StatusCode::BAD_REQUEST);
-            res.render(format!("missing or invalid {} header", header_name));
+            res.render(ApiError::bad_request(format!("missing or invalid {} header", header_name)));
             None
         }
     }
 }
 
-#[endpoint]
+/// Log in with tokens issued by Keycloak, and receive an authentication token.
+///
+/// As with POST /login, browsers should log in with ?cookie=true.
+#[endpoint(
+    parameters(
+        ("cookie" = Option<bool>, Query, description = "keep the session in a cookie, rather than returning the token")
+    ),
+    responses(
+        (status_code = 200, description = "success response, containing the authentication token and its expiry time"),
+        (status_code = 400, description = "one of the Keycloak headers is missing", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 401, description = "the Keycloak tokens are invalid", body = ApiError, content_type = "application/problem+json")
+    )
+)]
 pub(crate) async fn login(req: &mut Request, depot: &mut Depot, res: &mut Response) {
     let state = depot.obtain::<Arc<Mutex<Authenticator>>>().unwrap();
 
@@ -27,6 +40,9 @@ pub(crate) async fn login(req: &mut Request, depot: &mut Depot, res: &mut Respon
     let Some(id_token) = extract("x-keycloak-id-token", req, res) else { return };
     let Some(realm) = extract("x-keycloak-realm", req, res) else { return };
 
+    // optional, but without it the session cannot be refreshed (see POST /token/refresh)
+    let refresh_token = req.header::<String>("x-keycloak-refresh-token");
+
     let mut auth = state.lock().await;
 
     let auth = match auth.deref_mut() {
@@ -35,14 +51,11 @@ pub(crate) async fn login(req: &mut Request, depot: &mut Depot, res: &mut Respon
         Authenticator::Keycloak(auth) => auth,
     };
 
-    match auth.login_with_tokens(access_token.as_str(), id_token.as_str(), realm.as_str()).await {
-        Ok(auth_token) => {
+    match auth.login_with_tokens(access_token.as_str(), id_token.as_str(), refresh_token, realm.as_str()).await {
+        Ok(session) => {
             res.status_code(StatusCode::OK);
-            res.render(auth_token);
-        }
-        Err(e) => {
-            res.status_code(StatusCode::UNAUTHORIZED);
-            res.render(format!("error logging in: {}", e))
+            render_session(session, wants_cookie(req), res);
         }
+        Err(e) => res.render(ApiError::unauthorized(format!("error logging in: {}", e))),
     }
 }
\ No newline at end of file
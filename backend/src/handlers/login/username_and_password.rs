@@ -1,4 +1,7 @@
-use crate::auth::{Authenticator, AuthenticatorLike};
+use crate::api_error::ApiError;
+use crate::auth;
+use crate::auth::Authenticator;
+use crate::handlers::login::{render_session, wants_cookie};
 use reqwest::StatusCode;
 use salvo::oapi::endpoint;
 use salvo::{Depot, Request, Response};
@@ -12,29 +15,34 @@ struct Credentials {
     password: String,
 }
 
-#[endpoint]
+/// Log in with a username and password, and receive an authentication token.
+///
+/// Browsers should log in with ?cookie=true, to receive the token in an HttpOnly session cookie
+/// instead, along with a CSRF token which must be sent in the x-csrf-token header of every request
+/// which is not a GET.
+#[endpoint(
+    parameters(
+        ("cookie" = Option<bool>, Query, description = "keep the session in a cookie, rather than returning the token")
+    ),
+    responses(
+        (status_code = 200, description = "success response, containing the authentication token and its expiry time"),
+        (status_code = 400, description = "the request body is malformed", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 401, description = "the username or password is incorrect", body = ApiError, content_type = "application/problem+json")
+    )
+)]
 pub(crate) async fn login(req: &mut Request, depot: &mut Depot, res: &mut Response) {
     let state = depot.obtain::<Arc<Mutex<Authenticator>>>().unwrap();
 
     match req.parse_json::<Credentials>().await {
         Ok(credentials) => {
-            let mut auth = state.lock().await;
-            match auth.login(credentials.username, credentials.password).await {
-                Ok(auth_token) => {
+            match auth::login_with_password(state, credentials.username, credentials.password).await {
+                Ok(session) => {
                     res.status_code(StatusCode::OK);
-                    // TODO return auth token bundled with expiration time in a JSON blob
-                    //   so the user has visibility into when their token expires
-                    res.render(auth_token);
-                }
-                Err(e) => {
-                    res.status_code(StatusCode::UNAUTHORIZED);
-                    res.render(format!("error logging in: {}", e))
+                    render_session(session, wants_cookie(req), res);
                 }
+                Err(e) => res.render(ApiError::unauthorized(format!("error logging in: {}", e))),
             }
         }
-        Err(e) => {
-            res.status_code(StatusCode::BAD_REQUEST);
-            res.render(format!("error parsing request body: {}", e))
-        },
+        Err(e) => res.render(ApiError::bad_request(format!("error parsing request body: {}", e))),
     }
 }
\ No newline at end of file
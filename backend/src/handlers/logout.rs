@@ -0,0 +1,38 @@
+use crate::api_error::ApiError;
+use crate::auth::{Authenticator, AuthenticatorLike};
+use crate::auth_middleware::{token, TokenSource};
+use crate::handlers::login::clear_cookies;
+use salvo::oapi::endpoint;
+use salvo::{Depot, Request, Response};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Log out, so that the authentication token cannot be used again, even if it has not expired.
+///
+/// When Keycloak is used, this also ends the user's Keycloak session.
+#[endpoint(
+    responses(
+        (status_code = 200, description = "success response"),
+        (status_code = 401, description = "the token is missing or unrecognized", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 403, description = "the session cookie was sent without the matching x-csrf-token header", body = ApiError, content_type = "application/problem+json")
+    )
+)]
+pub(crate) async fn logout(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    let (token, source) = match token(req) {
+        Ok(Some(found)) => found,
+        Ok(None) => return res.render(ApiError::unauthorized("Missing authentication token")),
+        Err(e) => return res.render(e),
+    };
+
+    if source == TokenSource::Cookie {
+        clear_cookies(res);
+    }
+
+    let state = depot.obtain::<Arc<Mutex<Authenticator>>>().unwrap();
+    let mut auth = state.lock().await;
+
+    match auth.logout(token).await {
+        Ok(()) => res.render("logged out"),
+        Err(e) => res.render(ApiError::unauthorized(format!("error logging out: {}", e))),
+    }
+}
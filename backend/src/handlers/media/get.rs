@@ -0,0 +1,85 @@
+use crate::api_error::ApiError;
+use crate::db::Database;
+use crate::handlers::feeds::is_cached;
+use crate::handlers::media::parse_media_id;
+use crate::media::MediaStoreLike;
+use crate::model::media::Media;
+use salvo::http::header::{CACHE_CONTROL, CONTENT_TYPE, ETAG, X_CONTENT_TYPE_OPTIONS};
+use salvo::http::{HeaderValue, StatusCode};
+use salvo::oapi::endpoint;
+use salvo::{Depot, Request, Response};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Get the contents of an uploaded file.
+///
+/// Files never change once they are uploaded, so they can be cached forever. The ETag is the id of
+/// the file, so a client which sends it back in If-None-Match gets an empty 304 response.
+#[endpoint(
+    parameters(
+        ("id" = String, Path, description = "id of the Media to get")
+    ),
+    responses(
+        (status_code = 200, description = "success response"),
+        (status_code = 304, description = "the client already has this file"),
+        (status_code = 400, description = "the id is not a UUID", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 404, description = "the Media does not exist", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 503, description = "the database or the media store is unavailable", body = ApiError, content_type = "application/problem+json")
+    )
+)]
+pub(crate) async fn one(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    let Some(media_id) = parse_media_id(req, res) else { return };
+
+    let state = depot.obtain::<Arc<Mutex<Database>>>().unwrap();
+    let mut db = state.lock().await;
+
+    let media = match db.media().get(&media_id).map(Media::try_from) {
+        Ok(Ok(media)) => media,
+        Ok(Err(e)) => {
+            res.render(ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, format!("error reading Media {}: {}", media_id, e)));
+            return;
+        }
+        Err(e) => {
+            res.render(ApiError::from_db(&format!("error getting Media {}", media_id), e));
+            return;
+        }
+    };
+
+    drop(db);
+
+    let etag = format!("\"{}\"", media_id);
+    let headers = res.headers_mut();
+
+    if let Ok(value) = HeaderValue::from_str(&etag) {
+        headers.insert(ETAG, value);
+    }
+
+    headers.insert(CACHE_CONTROL, HeaderValue::from_static("public, max-age=31536000, immutable"));
+
+    if is_cached(req, &etag) {
+        res.status_code(StatusCode::NOT_MODIFIED);
+        return;
+    }
+
+    let store = depot.obtain::<Arc<dyn MediaStoreLike>>().expect("media store not configured");
+
+    match store.get(&media_id) {
+        Ok(bytes) => {
+            let headers = res.headers_mut();
+
+            if let Ok(value) = HeaderValue::from_str(&media.media_type.to_string()) {
+                headers.insert(CONTENT_TYPE, value);
+            }
+
+            // browsers must not guess a different type (e.g. HTML) from the contents
+            headers.insert(X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff"));
+            res.body(bytes);
+        }
+        Err(e) => {
+            // errors must not be cached forever
+            res.headers_mut().remove(CACHE_CONTROL);
+            res.headers_mut().remove(ETAG);
+            res.render(ApiError::from_media(&format!("error getting Media {}", media_id), e))
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use crate::api_error::ApiError;
+use crate::db::tables::media::MediaTableRow;
+use crate::db::Database;
+use crate::media::MediaStoreLike;
+use crate::model::media::{Limits, Media, MediaType, UploaderId};
+use salvo::http::body::Body;
+use salvo::http::{ReqBody, StatusCode};
+use salvo::hyper::body::Bytes;
+use salvo::oapi::endpoint;
+use salvo::{Depot, Request, Response};
+use std::future::poll_fn;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// How much bigger than the file itself a multipart request body may be, to leave room for the
+/// boundaries and headers around the file.
+const MULTIPART_OVERHEAD_BYTES: usize = 16 * 1024;
+
+/// Reads the whole request body into memory, unless it is longer than `max_size_bytes`. The limit is checked as the body streams in, so
+/// it also applies to bodies without a Content-Length (chunked, or sent over HTTP/2). Bodies which
+/// say up front that they are too large are rejected before any of them is read.
+///
+/// Renders an error, and returns None, if the body is too large or cannot be read.
+async fn read_body(req: &mut Request, max_size_bytes: usize, res: &mut Response) -> Option<()> {
+    let too_large = || ApiError::new(StatusCode::PAYLOAD_TOO_LARGE, format!("request bodies can be at most {} bytes", max_size_bytes));
+
+    if req.body_mut().size_hint().lower() > max_size_bytes as u64 {
+        res.render(too_large());
+        return None;
+    }
+
+    let mut body = req.take_body();
+    let mut bytes = vec![];
+
+    while let Some(frame) = poll_fn(|cx| Pin::new(&mut body).poll_frame(cx)).await {
+        match frame {
+            Ok(frame) => if let Ok(data) = frame.into_data() {
+                if bytes.len() + data.len() > max_size_bytes {
+                    res.render(too_large());
+                    return None;
+                }
+
+                bytes.extend_from_slice(&data);
+            },
+            Err(e) => {
+                res.render(ApiError::bad_request(format!("cannot read request body: {}", e)));
+                return None;
+            }
+        }
+    }
+
+    req.replace_body(ReqBody::Once(Bytes::from(bytes)));
+    Some(())
+}
+
+/// Upload a file, e.g. an image to show in a Post.
+///
+/// The file is sent as multipart/form-data, in a field named "file". Its type is worked out from
+/// its contents, not from its name or the type given in the request. Only PNG, JPEG, GIF and WebP
+/// images can be uploaded, up to the size set in config.toml.
+#[endpoint(
+    responses(
+        (status_code = 200, description = "success response"),
+        (status_code = 400, description = "the request body cannot be read, or has no file in the \"file\" field", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 401, description = "the user is not logged in", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 413, description = "the file is too large", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 415, description = "the file is not a PNG, JPEG, GIF or WebP image", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 503, description = "the database or the media store is unavailable", body = ApiError, content_type = "application/problem+json")
+    )
+)]
+pub(crate) async fn one(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    let limits = *depot.obtain::<Limits>().expect("media limits not configured");
+
+    if read_body(req, limits.max_size_bytes + MULTIPART_OVERHEAD_BYTES, res).await.is_none() { return };
+
+    let path = match req.try_file("file").await {
+        Ok(Some(file)) if file.size() as usize > limits.max_size_bytes => {
+            res.render(ApiError::new(StatusCode::PAYLOAD_TOO_LARGE, format!("files can be at most {} bytes", limits.max_size_bytes)));
+            return;
+        }
+        Ok(Some(file)) => file.path().clone(),
+        Ok(None) => {
+            res.render(ApiError::bad_request("expected a file in the \"file\" field"));
+            return;
+        }
+        Err(e) => {
+            res.render(ApiError::bad_request(format!("cannot parse multipart form: {}", e)));
+            return;
+        }
+    };
+
+    let bytes = match tokio::fs::read(&path).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            res.render(ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, format!("cannot read uploaded file: {}", e)));
+            return;
+        }
+    };
+
+    let Some(media_type) = MediaType::sniff(&bytes) else {
+        res.render(ApiError::new(StatusCode::UNSUPPORTED_MEDIA_TYPE, "only PNG, JPEG, GIF and WebP images can be uploaded"));
+        return;
+    };
+
+    let user_id = *depot.get::<Uuid>("token_user_id").expect("unknown user id");
+    let media = Media::new(UploaderId(user_id), media_type, bytes.len());
+    let media_id = media.media_id.0;
+
+    let store = depot.obtain::<Arc<dyn MediaStoreLike>>().expect("media store not configured");
+
+    if let Err(e) = store.put(&media_id, &bytes) {
+        res.render(ApiError::from_media("error storing Media", e));
+        return;
+    }
+
+    let state = depot.obtain::<Arc<Mutex<Database>>>().unwrap();
+    let mut db = state.lock().await;
+
+    match db.media().insert(MediaTableRow::from(media)) {
+        Ok(key) => res.render(format!("added new Media with id: {}", key)),
+        Err(e) => {
+            // don't leave behind a file which nothing refers to
+            if let Err(e) = store.delete(&media_id) {
+                log::warn!("unable to delete orphaned Media {}: {}", media_id, e);
+            }
+
+            res.render(ApiError::from_db("error adding Media", e))
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use crate::api_error::ApiError;
+use crate::auth::Authenticator;
+use salvo::http::header::CONTENT_TYPE;
+use salvo::http::{HeaderValue, StatusCode};
+use salvo::oapi::endpoint;
+use salvo::{Depot, Response};
+use std::fmt::Write;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Get metrics for monitoring, in the Prometheus text format. Only admins can get them.
+///
+/// See https://prometheus.io/docs/instrumenting/exposition_formats/
+#[endpoint(
+    responses(
+        (status_code = 200, description = "success response"),
+        (status_code = 401, description = "the user is not logged in", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 403, description = "the user is not an admin", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 503, description = "the session store is unavailable", body = ApiError, content_type = "application/problem+json")
+    )
+)]
+pub(crate) async fn metrics(depot: &mut Depot, res: &mut Response) {
+    let state = depot.obtain::<Arc<Mutex<Authenticator>>>().unwrap();
+
+    let unavailable = |e: String| ApiError::new(StatusCode::SERVICE_UNAVAILABLE, format!("error getting session metrics: {}", e));
+
+    // the Authenticator is only locked while the metrics are gathered, not while sessions in the
+    // database are counted
+    let sessions = match state.lock().await.session_metrics().await {
+        Ok(sessions) => sessions,
+        Err(e) => {
+            res.render(unavailable(e));
+            return;
+        }
+    };
+
+    let n_active = match sessions.n_active.count().await {
+        Ok(n_active) => n_active,
+        Err(e) => {
+            res.render(unavailable(e));
+            return;
+        }
+    };
+
+    let mut text = String::new();
+
+    let _ = writeln!(text, "# HELP subway_sessions_active Sessions which have not expired.");
+    let _ = writeln!(text, "# TYPE subway_sessions_active gauge");
+    let _ = writeln!(text, "subway_sessions_active {}", n_active);
+    let _ = writeln!(text, "# HELP subway_sessions_evicted_total Sessions ended because their user had too many.");
+    let _ = writeln!(text, "# TYPE subway_sessions_evicted_total counter");
+    let _ = writeln!(text, "subway_sessions_evicted_total {}", sessions.n_evicted);
+
+    res.headers_mut().insert(CONTENT_TYPE, HeaderValue::from_static("text/plain; version=0.0.4"));
+    res.body(text);
+}
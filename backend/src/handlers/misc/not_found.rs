@@ -1,19 +1,29 @@
+use crate::api_error::ApiError;
 use std::fs;
-use salvo::{handler, FlowCtrl, Response};
+use salvo::{handler, FlowCtrl, Request, Response};
+use salvo::http::header::ACCEPT;
 use salvo::http::StatusCode;
 use salvo::prelude::Text;
 
-/// Handler that takes the user to a default 404.html page.
+/// Handler that takes the user to a default 404.html page, or (if the client asked for JSON)
+/// returns a problem+json error.
 #[handler]
-pub(crate) async fn not_found(&self, res: &mut Response, ctrl: &mut FlowCtrl) {
+pub(crate) async fn not_found(&self, req: &mut Request, res: &mut Response, ctrl: &mut FlowCtrl) {
     if StatusCode::NOT_FOUND == res.status_code.unwrap_or(StatusCode::NOT_FOUND) {
-        // TODO performance improvement possible if we `include_str!` to embed this HTML file
-        //   directly in the binary, rather than reading it from the filesystem each time
-        let filename = "resources/404.html";
-        let contents = fs::read_to_string(filename).unwrap();
-        res.render(Text::Html(contents));
+        let wants_json = req.header::<String>(ACCEPT)
+            .is_some_and(|accept| accept.contains("application/json") || accept.contains("application/problem+json"));
+
+        if wants_json {
+            res.render(ApiError::not_found(format!("no resource found at {}", req.uri().path())));
+        } else {
+            // TODO performance improvement possible if we `include_str!` to embed this HTML file
+            //   directly in the binary, rather than reading it from the filesystem each time
+            let filename = "resources/404.html";
+            let contents = fs::read_to_string(filename).unwrap();
+            res.render(Text::Html(contents));
+        }
 
         // Skip remaining error handlers
         ctrl.skip_rest();
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,101 @@
+use crate::api_error::ApiError;
+use crate::db::page::{Page, PageRequest};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine as _;
+use salvo::{Request, Response};
+use serde::Serialize;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// A single page of items, returned by any endpoint which lists resources page-by-page.
+///
+/// Clients should treat the cursors as opaque. Pass one back in the `cursor` query parameter to
+/// get the next (or previous) page. A missing cursor means there is no page in that direction.
+#[derive(Serialize)]
+pub(crate) struct Paginated<T> {
+    items: Vec<T>,
+    next_cursor: Option<String>,
+    prev_cursor: Option<String>,
+}
+
+impl<PrimaryKey: Display, Row> From<Page<PrimaryKey, Row>> for Paginated<Row> {
+    fn from(page: Page<PrimaryKey, Row>) -> Self {
+        Self {
+            items: page.rows,
+            next_cursor: page.next.as_ref().map(encode),
+            prev_cursor: page.prev.as_ref().map(encode),
+        }
+    }
+}
+
+impl<T> Paginated<T> {
+    /// Replaces every item on this page, keeping the same cursors.
+    pub(crate) fn try_map<U, E>(self, f: impl FnOnce(Vec<T>) -> Result<Vec<U>, E>) -> Result<Paginated<U>, E> {
+        Ok(Paginated {
+            items: f(self.items)?,
+            next_cursor: self.next_cursor,
+            prev_cursor: self.prev_cursor,
+        })
+    }
+}
+
+// Cursors are just base64-encoded PageRequests. They are not signed, because they only tell us
+// where to start reading -- a forged cursor can only be used to request a page the client could
+// have requested anyway.
+
+/// Encodes a `PageRequest` as an opaque cursor, which can be passed back in a `cursor` query parameter.
+pub(crate) fn encode<PrimaryKey: Display>(page: &PageRequest<PrimaryKey>) -> String {
+    let raw = match page {
+        PageRequest::First => String::from("first"),
+        PageRequest::After(key) => format!("after:{}", key),
+        PageRequest::Before(key) => format!("before:{}", key),
+    };
+
+    URL_SAFE_NO_PAD.encode(raw)
+}
+
+/// Parses an opaque cursor (as returned in a `Paginated` response) back into a `PageRequest`.
+fn decode<PrimaryKey: FromStr>(cursor: &str) -> Option<PageRequest<PrimaryKey>> {
+    let bytes = URL_SAFE_NO_PAD.decode(cursor).ok()?;
+    let raw = String::from_utf8(bytes).ok()?;
+
+    match raw.split_once(':') {
+        None if raw == "first" => Some(PageRequest::First),
+        Some(("after", key)) => key.parse().ok().map(PageRequest::After),
+        Some(("before", key)) => key.parse().ok().map(PageRequest::Before),
+        _ => None,
+    }
+}
+
+/// How many items a page holds if the client does not say, and at most.
+const DEFAULT_LIMIT: usize = 10;
+const MAX_LIMIT: usize = 100;
+
+/// Reads the number of items requested with the `limit` query parameter (DEFAULT_LIMIT, if there is
+/// none), rendering an error and returning None if it is not between 1 and MAX_LIMIT.
+pub(crate) fn requested_limit(req: &Request, res: &mut Response) -> Option<usize> {
+    let Some(raw) = req.query::<String>("limit") else { return Some(DEFAULT_LIMIT) };
+
+    match raw.parse::<usize>() {
+        Ok(limit) if (1..=MAX_LIMIT).contains(&limit) => Some(limit),
+        _ => {
+            res.render(ApiError::bad_request(format!("'limit' must be between 1 and {}, but is {}", MAX_LIMIT, raw)));
+            None
+        }
+    }
+}
+
+/// Reads the page requested with the `cursor` query parameter (the first page, if there is none),
+/// rendering an error and returning None if the cursor is malformed.
+pub(crate) fn requested_page<PrimaryKey: FromStr>(req: &Request, res: &mut Response) -> Option<PageRequest<PrimaryKey>> {
+    match req.query::<String>("cursor") {
+        None => Some(PageRequest::First),
+        Some(cursor) => match decode(&cursor) {
+            Some(page) => Some(page),
+            None => {
+                res.render(ApiError::bad_request(format!("cannot parse {} as a cursor", cursor)));
+                None
+            }
+        }
+    }
+}
\ No newline at end of file
@@ -1,2 +1,216 @@
+use crate::api_error::{ApiError, FieldError};
+use crate::db::error::DbError;
+use crate::db::tables::posts_by_id::{PostsByIdTableLike, PostsByIdTableRow, Visibility};
+use crate::db::tables::users::UsersTableRow;
+use crate::db::Database;
+use crate::model::format::Format;
+use crate::model::post::{Body, Limits, Post, Status, Title, ValidationError};
+use crate::model::tag;
+use crate::model::tag::Tag;
+use salvo::{Depot, Request, Response};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::str::FromStr;
+use uuid::Uuid;
+
 pub(crate) mod post;
-pub(crate) mod get;
\ No newline at end of file
+pub(crate) mod get;
+pub(crate) mod put;
+pub(crate) mod patch;
+pub(crate) mod delete;
+pub(crate) mod publish;
+pub(crate) mod schedule;
+pub(crate) mod revisions;
+pub(crate) mod search;
+pub(crate) mod comments;
+pub(crate) mod reactions;
+
+/// Parses the 'id' route parameter as a Post id, rendering an error if it is not a UUID.
+fn parse_id(req: &Request, res: &mut Response) -> Option<Uuid> {
+    let id: String = req.param::<String>("id").expect("request did not contain a 'id' param");
+
+    match Uuid::from_str(&id) {
+        Ok(key) => Some(key),
+        Err(_) => {
+            res.render(ApiError::bad_request(format!("cannot parse {} as UUID", id)));
+            None
+        }
+    }
+}
+
+fn is_admin(depot: &Depot) -> bool {
+    depot.get::<Vec<String>>("token_user_roles")
+        .is_ok_and(|roles| roles.iter().any(|role| role == "admin"))
+}
+
+/// Only the author of a Post, or an admin, may modify or delete that Post.
+fn may_modify(depot: &Depot, post: &Post) -> bool {
+    let is_author = depot.get::<Uuid>("token_user_id")
+        .is_ok_and(|user_id| *user_id == post.author_id.0);
+
+    is_author || is_admin(depot)
+}
+
+/// Everyone may view a published Post. Only its author, or an admin, may view any other Post.
+fn may_view(depot: &Depot, post: &Post) -> bool {
+    post.status == Status::Published || may_modify(depot, post)
+}
+
+/// The Posts which the requesting user may view, as a filter. This must agree with may_view().
+pub(in crate::handlers) fn visibility(depot: &Depot) -> Visibility {
+    if is_admin(depot) {
+        Visibility::All
+    } else {
+        match depot.get::<Uuid>("token_user_id") {
+            Ok(user_id) => Visibility::PublishedOrBy(*user_id),
+            Err(_) => Visibility::Published,
+        }
+    }
+}
+
+/// Gets a Post, but only if the requesting user may view it (see may_view()). Otherwise, renders an
+/// error and returns None.
+pub(in crate::handlers) fn get_viewable(table: &dyn PostsByIdTableLike, id: &Uuid, depot: &Depot, res: &mut Response) -> Option<Post> {
+    match table.get(id) {
+        Err(e) => {
+            res.render(ApiError::from_db(&format!("error getting Post by id {}", id), e));
+            None
+        }
+        Ok(row) => {
+            let post = Post::from(row);
+
+            // pretend that the Post does not exist, rather than revealing that it is a draft
+            if !may_view(depot, &post) {
+                res.render(ApiError::not_found(format!("error getting Post by id: no visible Post with id {}", id)));
+                return None;
+            }
+
+            Some(post)
+        }
+    }
+}
+
+/// Checks that no Post is listed more than once in a bulk request, rendering an error listing the
+/// repeated ids if one is. Otherwise, the same Post would be changed twice in one request.
+fn check_unique(ids: &[Uuid], res: &mut Response) -> Option<()> {
+    let mut seen = HashSet::new();
+    let mut repeated: Vec<&Uuid> = ids.iter().filter(|id| !seen.insert(*id)).collect();
+    repeated.sort();
+    repeated.dedup();
+
+    match repeated.is_empty() {
+        true => Some(()),
+        false => {
+            res.render(ApiError::bad_request(format!("each Post can only be listed once, but these were repeated: {:?}", repeated)));
+            None
+        }
+    }
+}
+
+/// Gets every Post with one of the given ids, but only if all of them exist and the requesting
+/// user may modify all of them. Otherwise, renders an error and returns None.
+fn get_modifiable(
+    table: &dyn PostsByIdTableLike,
+    ids: &[Uuid],
+    depot: &Depot,
+    res: &mut Response,
+) -> Option<Vec<Post>> {
+    let mut posts = vec![];
+
+    for id in ids {
+        match table.get(id) {
+            Err(e) => {
+                res.render(ApiError::from_db(&format!("error getting Post by id {}", id), e));
+                return None;
+            }
+            Ok(row) => {
+                let post = Post::from(row);
+
+                if !may_modify(depot, &post) {
+                    res.render(ApiError::forbidden(format!("only the author or an admin can modify Post {}", id)));
+                    return None;
+                }
+
+                posts.push(post);
+            }
+        }
+    }
+
+    Some(posts)
+}
+
+/// Records a validation error (if there is one) against the field beneath `pointer`.
+pub(in crate::handlers) fn check<T>(result: Result<T, ValidationError>, pointer: &str, errors: &mut Vec<FieldError>) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(e) => {
+            errors.push(FieldError::new(format!("{}/{}", pointer, e.field), format!("{} {}", e.field, e.message)));
+            None
+        }
+    }
+}
+
+/// Validates the replacement Tags of one Post from a request body, if there are any. Absent or
+/// invalid Tags are returned as None, and invalid Tags are added to `errors`, like `validate` does.
+fn validate_tags(pointer: &str, tags: Option<Vec<String>>, errors: &mut Vec<FieldError>) -> Option<BTreeSet<Tag>> {
+    tags.and_then(|tags| check(tag::parse_all(&tags), pointer, errors))
+}
+
+/// Validates the title, body and / or format of one Post from a request body. Fields which are
+/// absent or invalid are returned as None, and each invalid field is added to `errors`, with a JSON
+/// pointer beneath `pointer` (e.g. "/0" for the first item in a list, or "" for the whole body).
+fn validate(
+    depot: &Depot,
+    pointer: &str,
+    title: Option<String>,
+    body: Option<String>,
+    format: Option<String>,
+    errors: &mut Vec<FieldError>,
+) -> (Option<Title>, Option<Body>, Option<Format>) {
+    let limits = depot.obtain::<Limits>().expect("Post limits were not injected into the depot");
+
+    let title = title.and_then(|title| check(Title::parse(title, limits), pointer, errors));
+    let body = body.and_then(|body| check(Body::parse(body, limits), pointer, errors));
+    let format = format.and_then(|format| check(Format::parse(&format), pointer, errors));
+
+    (title, body, format)
+}
+
+/// A Post, as it is returned to clients, along with its Tags and how many Reactions of each kind
+/// it has.
+#[derive(Serialize)]
+pub(in crate::handlers) struct PostResponse {
+    #[serde(flatten)]
+    post: PostsByIdTableRow,
+    author_display_name: Option<String>, // None if the author has never logged in
+    tags: Vec<String>,
+    reactions: BTreeMap<String, i64>,
+}
+
+/// Looks up the Tags, Reaction counts and author of every given Post, all at once (rather than
+/// once per Post), and attaches them to their Posts.
+pub(in crate::handlers) fn with_details(db: &mut Database, posts: Vec<PostsByIdTableRow>) -> Result<Vec<PostResponse>, DbError> {
+    let ids: Vec<Uuid> = posts.iter().map(|post| post.post_id()).collect();
+    let mut tags: HashMap<Uuid, Vec<String>> = HashMap::new();
+    let mut reactions: HashMap<Uuid, BTreeMap<String, i64>> = HashMap::new();
+
+    let author_ids: Vec<Uuid> = posts.iter().map(|post| post.author_id()).collect();
+    let authors: HashMap<Uuid, String> = db.users().list_for_ids(&author_ids)?.into_iter()
+        .map(|UsersTableRow { user_id, display_name, .. }| (user_id, display_name))
+        .collect();
+
+    for row in db.post_tags().list_for_posts(&ids)? {
+        tags.entry(row.post_id).or_default().push(row.tag);
+    }
+
+    for count in db.post_reactions().count_for_posts(&ids)? {
+        reactions.entry(count.post_id).or_default().insert(count.kind, count.count);
+    }
+
+    Ok(posts.into_iter().map(|post| {
+        let tags = tags.remove(&post.post_id()).unwrap_or_default();
+        let reactions = reactions.remove(&post.post_id()).unwrap_or_default();
+        let author_display_name = authors.get(&post.author_id()).cloned();
+        PostResponse { post, author_display_name, tags, reactions }
+    }).collect())
+}
\ No newline at end of file
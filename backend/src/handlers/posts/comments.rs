@@ -0,0 +1,21 @@
+use crate::api_error::ApiError;
+use salvo::{Request, Response};
+use std::str::FromStr;
+use uuid::Uuid;
+
+pub(crate) mod get;
+pub(crate) mod post;
+pub(crate) mod delete;
+
+/// Parses the 'comment_id' route parameter, rendering an error if it is not a UUID.
+fn parse_comment_id(req: &Request, res: &mut Response) -> Option<Uuid> {
+    let id: String = req.param::<String>("comment_id").expect("request did not contain a 'comment_id' param");
+
+    match Uuid::from_str(&id) {
+        Ok(key) => Some(key),
+        Err(_) => {
+            res.render(ApiError::bad_request(format!("cannot parse {} as UUID", id)));
+            None
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,57 @@
+use crate::api_error::ApiError;
+use crate::db::Database;
+use crate::handlers::posts::comments::parse_comment_id;
+use crate::handlers::posts::{get_viewable, may_modify, parse_id};
+use crate::model::comment::Comment;
+use salvo::oapi::endpoint;
+use salvo::{Depot, Request, Response};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// Delete one Comment, and every reply to it.
+///
+/// Only the author of the Comment, the author of the Post, or an admin, can delete it.
+#[endpoint(
+    parameters(
+        ("id" = String, Path, description = "id of the Post"),
+        ("comment_id" = String, Path, description = "id of the Comment to delete")
+    ),
+    responses(
+        (status_code = 200, description = "success response"),
+        (status_code = 400, description = "one of the ids is not a UUID", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 403, description = "the user is not the author of the Comment, the author of the Post, or an admin", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 404, description = "the Post or the Comment does not exist", body = ApiError, content_type = "application/problem+json"),
+    )
+)]
+pub(crate) async fn one(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    let Some(id) = parse_id(req, res) else { return };
+    let Some(comment_id) = parse_comment_id(req, res) else { return };
+
+    let state = depot.obtain::<Arc<Mutex<Database>>>().unwrap();
+    let mut db = state.lock().await;
+
+    let Some(post) = get_viewable(db.posts_by_id(), &id, depot, res) else { return };
+
+    let comment = match db.comments_by_post_id().get(&id, &comment_id) {
+        Ok(row) => Comment::from(row),
+        Err(e) => {
+            res.render(ApiError::from_db(&format!("error getting Comment {} on Post {}", comment_id, id), e));
+            return;
+        }
+    };
+
+    let is_comment_author = depot.get::<Uuid>("token_user_id")
+        .is_ok_and(|user_id| *user_id == comment.author_id.0);
+
+    // may_modify() allows the author of the Post, and admins
+    if !is_comment_author && !may_modify(depot, &post) {
+        res.render(ApiError::forbidden(format!("only the author of Comment {}, the author of the Post, or an admin can delete it", comment_id)));
+        return;
+    }
+
+    match db.comments_by_post_id().delete(&id, &comment_id) {
+        Ok((_, comment_id)) => res.render(format!("deleted Comment with id: {} (and any replies to it)", comment_id)),
+        Err(e) => res.render(ApiError::from_db("error deleting Comment from DB", e)),
+    }
+}
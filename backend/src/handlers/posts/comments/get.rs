@@ -0,0 +1,44 @@
+use crate::api_error::ApiError;
+use crate::db::Database;
+use crate::handlers::pagination;
+use crate::handlers::pagination::Paginated;
+use crate::handlers::posts::{get_viewable, parse_id};
+use salvo::oapi::endpoint;
+use salvo::prelude::Json;
+use salvo::{Depot, Request, Response};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Returns one page of the Comments on a Post, oldest first.
+///
+/// Replies are listed alongside every other Comment. Each reply has the id of the Comment it
+/// replies to as its `parent_id` (top-level Comments have none), so clients can arrange them into
+/// threads. Anyone who can see the Post can see its Comments.
+#[endpoint(
+    parameters(
+        ("id" = String, Path, description = "id of the Post"),
+        ("limit" = Option<u32>, Query, description = "maximum number of Comments to return, from 1 to 100 (default 10)"),
+        ("cursor" = Option<String>, Query, description = "cursor from a previous response; omit to get the first page")
+    ),
+    responses(
+        (status_code = 200, description = "success response"),
+        (status_code = 400, description = "the id is not a UUID, the limit is out of range, or the cursor is malformed", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 404, description = "the Post does not exist", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 503, description = "the database is unavailable", body = ApiError, content_type = "application/problem+json")
+    )
+)]
+pub(crate) async fn many(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    let Some(id) = parse_id(req, res) else { return };
+    let Some(limit) = pagination::requested_limit(req, res) else { return };
+    let Some(page) = pagination::requested_page(req, res) else { return };
+
+    let state = depot.obtain::<Arc<Mutex<Database>>>().unwrap();
+    let mut db = state.lock().await;
+
+    let Some(_) = get_viewable(db.posts_by_id(), &id, depot, res) else { return };
+
+    match db.comments_by_post_id().list_page(&id, &page, limit) {
+        Err(e) => res.render(ApiError::from_db(&format!("error listing Comments on Post {}", id), e)),
+        Ok(comments) => res.render(Json(Paginated::from(comments))),
+    }
+}
@@ -0,0 +1,76 @@
+use crate::api_error::ApiError;
+use crate::db::tables::comments_by_post_id::CommentsByPostIdTableRow;
+use crate::db::Database;
+use crate::handlers::posts::{get_viewable, parse_id, validate};
+use crate::model::comment::{Comment, CommentId};
+use crate::model::post::{AuthorId, PostId};
+use salvo::oapi::{endpoint, ToSchema};
+use salvo::{Depot, Request, Response};
+use serde::Deserialize;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// Fields required to create a Comment.
+#[derive(Deserialize, ToSchema)]
+struct ProtoComment {
+    body: String,
+
+    /// The id of the Comment (on the same Post) that this is a reply to, if any
+    #[serde(default)]
+    parent_id: Option<Uuid>,
+}
+
+/// Comment on a Post, or reply to another Comment on it.
+///
+/// Anyone who is logged in, and can see the Post, can comment on it.
+#[endpoint(
+    parameters(
+        ("id" = String, Path, description = "id of the Post to comment on")
+    ),
+    request_body(
+        content = ProtoComment,
+        description = "A JSON object containing the body of the Comment, and (optionally) the id of the Comment it replies to.",
+        content_type = "application/json",
+    ),
+    responses(
+        (status_code = 200, description = "success response"),
+        (status_code = 400, description = "the id is not a UUID, or the request body is malformed", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 401, description = "the user is not logged in", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 404, description = "the Post does not exist", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 422, description = "the body is invalid, or the parent Comment is not on this Post", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 503, description = "the database is unavailable", body = ApiError, content_type = "application/problem+json")
+    )
+)]
+pub(crate) async fn one(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    let Some(id) = parse_id(req, res) else { return };
+    let user_id = *depot.get::<Uuid>("token_user_id").expect("unknown user id");
+
+    let proto_comment = match req.parse_json::<ProtoComment>().await {
+        Ok(proto_comment) => proto_comment,
+        Err(e) => {
+            res.render(ApiError::bad_request(format!("error parsing request body: {}", e)));
+            return;
+        }
+    };
+
+    let mut errors = vec![];
+
+    let (_, Some(body), _) = validate(depot, "", None, Some(proto_comment.body), None, &mut errors) else {
+        res.render(ApiError::invalid("the Comment is invalid", errors));
+        return;
+    };
+
+    let state = depot.obtain::<Arc<Mutex<Database>>>().unwrap();
+    let mut db = state.lock().await;
+
+    let Some(_) = get_viewable(db.posts_by_id(), &id, depot, res) else { return };
+
+    let comment = Comment::new(PostId(id), AuthorId(user_id), proto_comment.parent_id.map(CommentId), body);
+
+    // replies to Comments which do not exist on this Post are rejected by the table
+    match db.comments_by_post_id().insert(vec![CommentsByPostIdTableRow::from(comment)]) {
+        Ok(keys) => res.render(format!("added new Comment to Post {} with ids: {:?}", id, keys.iter().map(|(_, comment_id)| comment_id).collect::<Vec<_>>())),
+        Err(e) => res.render(ApiError::from_db("error inserting new Comment into DB", e)),
+    }
+}
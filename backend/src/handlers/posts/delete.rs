@@ -0,0 +1,73 @@
+use crate::api_error::ApiError;
+use crate::db::Database;
+use crate::handlers::posts::{check_unique, get_modifiable, parse_id};
+use salvo::oapi::endpoint;
+use salvo::{Depot, Request, Response};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// Delete one Post.
+///
+/// Only the author of the Post, or an admin, can delete it.
+#[endpoint(
+    parameters(
+        ("id" = String, Path, description = "id of the Post to delete")
+    ),
+    responses(
+        (status_code = 200, description = "success response"),
+        (status_code = 403, description = "the user is neither the author of the Post nor an admin", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 404, description = "the Post does not exist", body = ApiError, content_type = "application/problem+json"),
+    )
+)]
+pub(crate) async fn one(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    let Some(id) = parse_id(req, res) else { return };
+
+    let state = depot.obtain::<Arc<Mutex<Database>>>().unwrap();
+    let mut db = state.lock().await;
+    let table = db.posts_by_id();
+
+    if get_modifiable(table, &[id], depot, res).is_none() { return };
+
+    match table.delete(vec![id]) {
+        Ok(uuids) => res.render(format!("deleted Posts with ids: {:?}", uuids)),
+        Err(e) => res.render(ApiError::from_db("error deleting Post from DB", e)),
+    }
+}
+
+/// Delete one or more Posts.
+///
+/// Only the author of a Post, or an admin, can delete it. If the user cannot delete even one of
+/// the listed Posts, none of them are deleted.
+#[endpoint(
+    request_body(
+        content = Vec<String>,
+        description = "A JSON list of the ids of the Posts to delete.",
+        content_type = "application/json",
+    ),
+    responses(
+        (status_code = 200, description = "success response"),
+        (status_code = 400, description = "the request body is malformed, or lists the same Post more than once", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 403, description = "the user is neither the author of every Post nor an admin", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 404, description = "one or more of the Posts do not exist", body = ApiError, content_type = "application/problem+json"),
+    )
+)]
+pub(crate) async fn many(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    match req.parse_json::<Vec<Uuid>>().await {
+        Ok(ids) => {
+            if check_unique(&ids, res).is_none() { return };
+
+            let state = depot.obtain::<Arc<Mutex<Database>>>().unwrap();
+            let mut db = state.lock().await;
+            let table = db.posts_by_id();
+
+            if get_modifiable(table, &ids, depot, res).is_none() { return };
+
+            match table.delete(ids) {
+                Ok(uuids) => res.render(format!("deleted Posts with ids: {:?}", uuids)),
+                Err(e) => res.render(ApiError::from_db("error deleting Posts from DB", e)),
+            }
+        }
+        Err(e) => res.render(ApiError::bad_request(format!("error parsing request body: {}", e))),
+    }
+}
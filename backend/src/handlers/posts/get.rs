@@ -1,7 +1,14 @@
-use crate::db::tables::posts_by_id::PostsByIdTableRow;
+use crate::api_error::ApiError;
+use crate::db::tables::posts_by_id::{PostsByIdFilter, PostsByIdTableRow};
 use crate::db::Database;
+use crate::handlers::pagination;
+use crate::handlers::pagination::Paginated;
+use crate::handlers::posts::{get_viewable, may_view, parse_id, visibility, with_details};
+use crate::model::post::Post;
+use crate::model::tag::Tag;
+use chrono::{DateTime, NaiveDate, Utc};
 use salvo::oapi::endpoint;
-use salvo::prelude::Json;
+use salvo::prelude::{Json, Redirect};
 use salvo::{Depot, Request, Response};
 use std::str::FromStr;
 use std::sync::Arc;
@@ -9,48 +16,178 @@ use tokio::sync::Mutex;
 use uuid::Uuid;
 
 /// Endpoint to GET one single Post by id.
-#[endpoint]
+///
+/// Anyone can get a published Post. Only its author, or an admin, can get any other Post.
+#[endpoint(
+    responses(
+        (status_code = 200, description = "success response"),
+        (status_code = 400, description = "the id is not a UUID", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 404, description = "the Post does not exist", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 503, description = "the database is unavailable", body = ApiError, content_type = "application/problem+json")
+    )
+)]
 pub(crate) async fn one(req: &mut Request, depot: &mut Depot, res: &mut Response) {
     let state = depot.obtain::<Arc<Mutex<Database>>>().unwrap();
     let mut db = state.lock().await;
-    let table = &db.posts_by_id();
 
-    let id: String = req.param::<String>("id").expect("request did not contain a 'id' param");
+    let Some(key) = parse_id(req, res) else { return };
+
+    match db.posts_by_id().get(&key) {
+        Err(e) => res.render(ApiError::from_db("error getting Post by id", e)),
+        Ok(row) => {
+            let post = Post::from(row);
+
+            // pretend that the Post does not exist, rather than revealing that it is a draft
+            if !may_view(depot, &post) {
+                res.render(ApiError::not_found(format!("error getting Post by id: no visible Post with id {}", key)));
+                return;
+            }
+
+            match with_details(&mut db, vec![PostsByIdTableRow::from(post)]) {
+                Err(e) => res.render(ApiError::from_db("error getting Tags and Reactions of Post", e)),
+                Ok(mut posts) => res.render(Json(posts.remove(0))),
+            }
+        }
+    }
+}
+
+/// Endpoint to GET one single Post by slug, e.g. "hello-world".
+///
+/// A Post keeps its old slugs when its title changes. Requests for an old slug are redirected to
+/// the Post's current slug.
+#[endpoint(
+    parameters(
+        ("slug" = String, Path, description = "current or former slug of the Post to get")
+    ),
+    responses(
+        (status_code = 200, description = "success response"),
+        (status_code = 308, description = "the slug is no longer the Post's slug; follow the Location header to its current slug"),
+        (status_code = 404, description = "the Post does not exist", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 503, description = "the database is unavailable", body = ApiError, content_type = "application/problem+json")
+    )
+)]
+pub(crate) async fn by_slug(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    let slug: String = req.param::<String>("slug").expect("request did not contain a 'slug' param");
+
+    let state = depot.obtain::<Arc<Mutex<Database>>>().unwrap();
+    let mut db = state.lock().await;
+
+    let post_id = match db.posts_by_slug().get(&slug) {
+        Ok(row) => row.post_id(),
+        Err(e) => {
+            res.render(ApiError::from_db(&format!("error getting Post by slug {}", slug), e));
+            return;
+        }
+    };
+
+    let Some(post) = get_viewable(db.posts_by_id(), &post_id, depot, res) else { return };
+
+    if post.slug.0 != slug {
+        res.render(Redirect::permanent(format!("/posts/by-slug/{}", post.slug.0).as_str()));
+        return;
+    }
+
+    match with_details(&mut db, vec![PostsByIdTableRow::from(post)]) {
+        Err(e) => res.render(ApiError::from_db("error getting Tags and Reactions of Post", e)),
+        Ok(mut posts) => res.render(Json(posts.remove(0))),
+    }
+}
+
+/// Parses a timestamp in RFC 3339 format (e.g. 2025-09-09T12:00:00Z), or a date (e.g. 2025-09-09),
+/// which is interpreted as midnight UTC at the start of that day.
+fn parse_timestamp(raw: &str) -> Option<DateTime<Utc>> {
+    match DateTime::parse_from_rfc3339(raw) {
+        Ok(timestamp) => Some(timestamp.to_utc()),
+        Err(_) => NaiveDate::parse_from_str(raw, "%Y-%m-%d").ok()
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .map(|timestamp| timestamp.and_utc()),
+    }
+}
 
-    match Uuid::from_str(&id) {
-        Err(_) => res.render(format!("cannot parse {} as UUID\n", id)),
-        Ok(key) => {
-            match table.get(&key) {
-                Err(e) => res.render(format!("error getting Post by id: {}", e)),
-                Ok(post) => res.render(Json(Into::<PostsByIdTableRow>::into(post))),
+/// Parses an optional query parameter. Returns None (after rendering an error) if the parameter
+/// is present but malformed, or Some(None) if the parameter is absent.
+fn parse_query<T>(
+    req: &Request,
+    res: &mut Response,
+    name: &str,
+    parse: fn(&str) -> Option<T>,
+    expected: &str,
+) -> Option<Option<T>> {
+    match req.query::<String>(name) {
+        None => Some(None),
+        Some(raw) => match parse(&raw) {
+            Some(value) => Some(Some(value)),
+            None => {
+                res.render(ApiError::bad_request(format!("cannot parse {} as {}", raw, expected)));
+                None
             }
         }
     }
 }
 
+/// Builds a filter from the query parameters, rendering an error if any of them are malformed.
+fn parse_filter(req: &Request, depot: &Depot, res: &mut Response) -> Option<PostsByIdFilter> {
+    let timestamp = "a date or an RFC 3339 timestamp";
+
+    Some(PostsByIdFilter {
+        author_id: parse_query(req, res, "author_id", |raw| Uuid::from_str(raw).ok(), "UUID")?,
+        created_after: parse_query(req, res, "created_after", parse_timestamp, timestamp)?,
+        created_before: parse_query(req, res, "created_before", parse_timestamp, timestamp)?,
+        title_contains: req.query::<String>("title_contains"),
+        tag: parse_query(req, res, "tag", |raw| Tag::parse(raw).ok().map(|tag| tag.0), "a tag")?,
+        visibility: visibility(depot),
+    })
+}
+
 // See https://docs.rs/salvo-oapi/0.84.0/salvo_oapi/endpoint/index.html#tuples
 //   for info on OpenAPI endpoint tuples for parameters
 
-/// Returns all Posts up to the specified limit.
+/// Returns one page of Posts, up to the specified limit.
+///
+/// Posts are returned oldest first, in a JSON object with an `items` list and opaque
+/// `next_cursor` and `prev_cursor` tokens. Pass either token back as the `cursor` query parameter
+/// (along with the same filters) to get the next or previous page.
 ///
-/// Posts are returned as a JSON-formatted list.
+/// Anonymous users only see published Posts. Logged-in users also see their own drafts and archived
+/// Posts, and admins see every Post.
+///
+/// Dates can be given as RFC 3339 timestamps (2025-09-09T12:00:00Z) or as plain dates (2025-09-09).
 #[endpoint(
     parameters(
-        ("limit" = u32, Query, description = "maximum number of Posts to return")
+        ("limit" = Option<u32>, Query, description = "maximum number of Posts to return, from 1 to 100 (default 10)"),
+        ("cursor" = Option<String>, Query, description = "cursor from a previous response; omit to get the first page"),
+        ("author_id" = Option<String>, Query, description = "only return Posts by this author"),
+        ("created_after" = Option<String>, Query, description = "only return Posts created after this time"),
+        ("created_before" = Option<String>, Query, description = "only return Posts created before this time"),
+        ("title_contains" = Option<String>, Query, description = "only return Posts whose titles contain this text (ignoring case)"),
+        ("tag" = Option<String>, Query, description = "only return Posts with this Tag")
     ),
     responses(
-        (status_code = 200, description = "success response")
+        (status_code = 200, description = "success response"),
+        (status_code = 400, description = "the limit is out of range, or the cursor or one of the filters is malformed", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 503, description = "the database is unavailable", body = ApiError, content_type = "application/problem+json")
     )
 )]
 pub(crate) async fn many(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    let Some(limit) = pagination::requested_limit(req, res) else { return };
+
+    let Some(page) = pagination::requested_page(req, res) else { return };
+
+    let Some(filter) = parse_filter(req, depot, res) else { return };
+
     let state = depot.obtain::<Arc<Mutex<Database>>>().unwrap();
     let mut db = state.lock().await;
-    let table = &db.posts_by_id();
 
-    let limit = req.query::<usize>("limit").unwrap_or(10);
+    let posts = match db.posts_by_id().list_page(&page, limit, &filter) {
+        Ok(posts) => Paginated::from(posts),
+        Err(e) => {
+            res.render(ApiError::from_db("error listing Posts", e));
+            return;
+        }
+    };
 
-    match table.list(limit) {
-        Err(e) => res.render(format!("error listing Posts: {}", e)),
+    match posts.try_map(|posts| with_details(&mut db, posts)) {
+        Err(e) => res.render(ApiError::from_db("error getting Tags and Reactions of Posts", e)),
         Ok(posts) => res.render(Json(posts)),
     }
 }
\ No newline at end of file
@@ -0,0 +1,147 @@
+use crate::api_error::ApiError;
+use crate::db::tables::posts_by_id::PostsByIdUpdate;
+use crate::db::Database;
+use crate::handlers::posts::{check_unique, get_modifiable, parse_id, validate, validate_tags};
+use crate::model::format::Format;
+use crate::model::post::{Body, Post, Title};
+use crate::model::tag::Tag;
+use salvo::oapi::{endpoint, ToSchema};
+use salvo::{Depot, Request, Response};
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// Fields which can be changed on a Post. Omitted fields are left unchanged.
+#[derive(Deserialize, ToSchema)]
+struct PostPatch {
+    title: Option<String>,
+    body: Option<String>,
+    format: Option<String>, // "plain" or "markdown"
+    tags: Option<Vec<String>>, // replaces every Tag on the Post
+}
+
+/// Fields which can be changed on a Post, when patching more than one Post at a time.
+#[derive(Deserialize, ToSchema)]
+struct IdentifiedPostPatch {
+    #[salvo(schema(value_type = String))]
+    post_id: Uuid,
+    title: Option<String>,
+    body: Option<String>,
+    format: Option<String>,
+    tags: Option<Vec<String>>,
+}
+
+fn apply(mut post: Post, (title, body, format): (Option<Title>, Option<Body>, Option<Format>), tags: Option<BTreeSet<Tag>>) -> PostsByIdUpdate {
+    post.edit(title, body, format);
+    PostsByIdUpdate::from(post).with_tags(tags)
+}
+
+/// Partially update one Post.
+///
+/// Only the author of the Post, or an admin, can update it.
+#[endpoint(
+    parameters(
+        ("id" = String, Path, description = "id of the Post to update")
+    ),
+    request_body(
+        content = PostPatch,
+        description = "A JSON object containing a new title, a new body, a new format, a new list of tags, or any combination of them.",
+        content_type = "application/json",
+    ),
+    responses(
+        (status_code = 200, description = "success response"),
+        (status_code = 403, description = "the user is neither the author of the Post nor an admin", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 404, description = "the Post does not exist", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 409, description = "the Post was changed by someone else at the same time", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 422, description = "the new title, body, format or tags are invalid", body = ApiError, content_type = "application/problem+json"),
+    )
+)]
+pub(crate) async fn one(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    let Some(id) = parse_id(req, res) else { return };
+
+    match req.parse_json::<PostPatch>().await {
+        Ok(patch) => {
+            let mut errors = vec![];
+            let fields = validate(depot, "", patch.title, patch.body, patch.format, &mut errors);
+            let tags = validate_tags("", patch.tags, &mut errors);
+
+            if !errors.is_empty() {
+                res.render(ApiError::invalid("the patch is invalid", errors));
+                return;
+            }
+
+            let state = depot.obtain::<Arc<Mutex<Database>>>().unwrap();
+            let mut db = state.lock().await;
+            let table = db.posts_by_id();
+
+            let Some(mut posts) = get_modifiable(table, &[id], depot, res) else { return };
+
+            match table.update(vec![apply(posts.remove(0), fields, tags)]) {
+                Ok(uuids) => res.render(format!("updated Posts with ids: {:?}", uuids)),
+                Err(e) => res.render(ApiError::from_db("error updating Post in DB", e)),
+            }
+        }
+        Err(e) => res.render(ApiError::bad_request(format!("error parsing request body: {}", e))),
+    }
+}
+
+/// Partially update one or more Posts.
+///
+/// Only the author of a Post, or an admin, can update it. If the user cannot update even one of
+/// the listed Posts, none of them are updated.
+#[endpoint(
+    request_body(
+        content = Vec<IdentifiedPostPatch>,
+        description = "A JSON list of objects, each containing a post id and a new title, a new body, a new format, a new list of tags, or any combination of them.",
+        content_type = "application/json",
+    ),
+    responses(
+        (status_code = 200, description = "success response"),
+        (status_code = 400, description = "the request body is malformed, or lists the same Post more than once", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 403, description = "the user is neither the author of every Post nor an admin", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 404, description = "one or more of the Posts do not exist", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 409, description = "one or more of the Posts were changed by someone else at the same time; no Posts were updated", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 422, description = "one or more of the new titles, bodies, formats or tags are invalid; no Posts were updated", body = ApiError, content_type = "application/problem+json"),
+    )
+)]
+pub(crate) async fn many(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    match req.parse_json::<Vec<IdentifiedPostPatch>>().await {
+        Ok(patches) => {
+            let ids: Vec<Uuid> = patches.iter().map(|patch| patch.post_id).collect();
+
+            if check_unique(&ids, res).is_none() { return };
+
+            let mut errors = vec![];
+
+            let fields: Vec<_> = patches.into_iter().enumerate().map(|(index, patch)| {
+                let pointer = format!("/{}", index);
+                let tags = validate_tags(&pointer, patch.tags, &mut errors);
+                (validate(depot, &pointer, patch.title, patch.body, patch.format, &mut errors), tags)
+            }).collect();
+
+            // if even one patch is invalid, no Posts are updated
+            if !errors.is_empty() {
+                res.render(ApiError::invalid("one or more patches are invalid", errors));
+                return;
+            }
+
+            let state = depot.obtain::<Arc<Mutex<Database>>>().unwrap();
+            let mut db = state.lock().await;
+            let table = db.posts_by_id();
+
+            let Some(posts) = get_modifiable(table, &ids, depot, res) else { return };
+
+            let rows = posts.into_iter().zip(fields)
+                .map(|(post, (fields, tags))| apply(post, fields, tags))
+                .collect();
+
+            match table.update(rows) {
+                Ok(uuids) => res.render(format!("updated Posts with ids: {:?}", uuids)),
+                Err(e) => res.render(ApiError::from_db("error updating Posts in DB", e)),
+            }
+        }
+        Err(e) => res.render(ApiError::bad_request(format!("error parsing request body: {}", e))),
+    }
+}
@@ -1,7 +1,12 @@
+use crate::api_error::ApiError;
+use crate::model::format::Format;
 use crate::model::post;
 use crate::model::post::Post;
+use crate::model::tag;
+use crate::db::tables::post_tags::PostTagsTableRow;
 use crate::db::tables::posts_by_id::PostsByIdTableRow;
 use crate::db::Database;
+use crate::handlers::posts::{check, validate};
 use salvo::oapi::{endpoint, ToSchema};
 use salvo::{Depot, Request, Response};
 use serde::Deserialize;
@@ -14,6 +19,13 @@ use uuid::Uuid;
 struct ProtoPost {
     title: String,
     body: String,
+
+    /// "plain" (the default) or "markdown"
+    format: Option<String>,
+
+    /// Tags are normalized to slugs, e.g. "Rust Programming" becomes "rust-programming"
+    #[serde(default)]
+    tags: Vec<String>,
 }
 
 /// Create one or more Posts.
@@ -21,11 +33,15 @@ struct ProtoPost {
 #[endpoint(
     request_body(
         content = Vec<ProtoPost>,
-        description = "A JSON list of blog posts, each containing an author id, a title, and a body.",
+        description = "A JSON list of blog posts, each containing a title, a body, and (optionally) a format and a list of tags.",
         content_type = "application/json",
     ),
     responses(
-        (status_code = 200, description = "success response")
+        (status_code = 200, description = "success response"),
+        (status_code = 400, description = "the request body is malformed", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 401, description = "the user is not logged in", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 422, description = "one or more Posts are invalid; no Posts were created", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 503, description = "the database is unavailable", body = ApiError, content_type = "application/problem+json")
     )
 )]
 pub(crate) async fn many(req: &mut Request, depot: &mut Depot, res: &mut Response) {
@@ -34,24 +50,41 @@ pub(crate) async fn many(req: &mut Request, depot: &mut Depot, res: &mut Respons
 
     match req.parse_json::<Vec<ProtoPost>>().await {
         Ok(proto_posts) => {
+            let mut errors = vec![];
+
+            let fields: Vec<_> = proto_posts.into_iter().enumerate().map(|(index, proto_post)| {
+                let pointer = format!("/{}", index);
+                let tags = check(tag::parse_all(&proto_post.tags), &pointer, &mut errors);
+                let format = proto_post.format.or(Some(Format::default().to_string()));
+                let (title, body, format) = validate(depot, &pointer, Some(proto_post.title), Some(proto_post.body), format, &mut errors);
+                (title, body, format, tags)
+            }).collect();
+
+            // if even one Post is invalid, none of them are created
+            if !errors.is_empty() {
+                res.render(ApiError::invalid("one or more Posts are invalid", errors));
+                return;
+            }
+
+            let mut posts = vec![];
+            let mut tags = vec![];
+
+            for fields in fields {
+                // every field was validated above, so none of them are missing
+                if let (Some(title), Some(body), Some(format), Some(post_tags)) = fields {
+                    let post = Post::new(post::AuthorId(*user_id), title, body, format);
+                    tags.extend(post_tags.into_iter().map(|tag| PostTagsTableRow::from((&post.post_id, tag))));
+                    posts.push(PostsByIdTableRow::from(post));
+                }
+            }
+
             let mut db = state.lock().await;
-            let table = &mut db.posts_by_id();
-
-            match table.insert(
-                proto_posts.into_iter().map(|proto_post| {
-                    <PostsByIdTableRow as From<Post>>::from(
-                        Post::new(
-                            post::AuthorId(user_id.clone()),
-                            post::Title(proto_post.title),
-                            post::Body(proto_post.body),
-                        )
-                    )
-                }).collect()
-            ) {
+
+            match db.posts_by_id().insert(posts, tags) {
                 Ok(uuids) => res.render(format!("added new Post to table with ids: {:?}", uuids)),
-                Err(e) => res.render(format!("error inserting new Posts into DB: {}", e)),
+                Err(e) => res.render(ApiError::from_db("error inserting new Posts into DB", e)),
             }
         },
-        Err(e) => res.render(format!("error parsing request body: {}", e)),
+        Err(e) => res.render(ApiError::bad_request(format!("error parsing request body: {}", e))),
     }
 }
\ No newline at end of file
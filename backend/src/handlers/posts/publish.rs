@@ -0,0 +1,79 @@
+use crate::api_error::ApiError;
+use crate::db::tables::posts_by_id::PostsByIdUpdate;
+use crate::db::Database;
+use crate::handlers::posts::{get_modifiable, parse_id};
+use salvo::http::StatusCode;
+use salvo::oapi::endpoint;
+use salvo::{Depot, Request, Response};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Publish one Post, making it visible to everyone.
+///
+/// Drafts and archived Posts can be published. Publishing a published Post does nothing. Only the
+/// author of the Post, or an admin, can publish it.
+#[endpoint(
+    parameters(
+        ("id" = String, Path, description = "id of the Post to publish")
+    ),
+    responses(
+        (status_code = 200, description = "success response"),
+        (status_code = 403, description = "the user is neither the author of the Post nor an admin", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 404, description = "the Post does not exist", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 409, description = "the Post was changed by someone else at the same time", body = ApiError, content_type = "application/problem+json"),
+    )
+)]
+pub(crate) async fn publish(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    let Some(id) = parse_id(req, res) else { return };
+
+    let state = depot.obtain::<Arc<Mutex<Database>>>().unwrap();
+    let mut db = state.lock().await;
+    let table = db.posts_by_id();
+
+    let Some(mut posts) = get_modifiable(table, &[id], depot, res) else { return };
+
+    let mut post = posts.remove(0);
+    post.publish();
+
+    match table.update(vec![PostsByIdUpdate::from(post)]) {
+        Ok(uuids) => res.render(format!("published Posts with ids: {:?}", uuids)),
+        Err(e) => res.render(ApiError::from_db("error publishing Post", e)),
+    }
+}
+
+/// Unpublish one Post, hiding it from everyone except its author (and admins).
+///
+/// Unpublished Posts are archived. Unpublishing an archived Post does nothing, but drafts cannot
+/// be unpublished. Only the author of the Post, or an admin, can unpublish it.
+#[endpoint(
+    parameters(
+        ("id" = String, Path, description = "id of the Post to unpublish")
+    ),
+    responses(
+        (status_code = 200, description = "success response"),
+        (status_code = 403, description = "the user is neither the author of the Post nor an admin", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 404, description = "the Post does not exist", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 409, description = "the Post is a draft, or was changed by someone else at the same time", body = ApiError, content_type = "application/problem+json"),
+    )
+)]
+pub(crate) async fn unpublish(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    let Some(id) = parse_id(req, res) else { return };
+
+    let state = depot.obtain::<Arc<Mutex<Database>>>().unwrap();
+    let mut db = state.lock().await;
+    let table = db.posts_by_id();
+
+    let Some(mut posts) = get_modifiable(table, &[id], depot, res) else { return };
+
+    let mut post = posts.remove(0);
+
+    if let Err(e) = post.unpublish() {
+        res.render(ApiError::new(StatusCode::CONFLICT, format!("error unpublishing Post: {}", e)));
+        return;
+    }
+
+    match table.update(vec![PostsByIdUpdate::from(post)]) {
+        Ok(uuids) => res.render(format!("unpublished Posts with ids: {:?}", uuids)),
+        Err(e) => res.render(ApiError::from_db("error unpublishing Post", e)),
+    }
+}
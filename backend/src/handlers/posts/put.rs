@@ -0,0 +1,146 @@
+use crate::api_error::ApiError;
+use crate::db::tables::posts_by_id::PostsByIdUpdate;
+use crate::db::Database;
+use crate::handlers::posts::{check_unique, get_modifiable, parse_id, validate, validate_tags};
+use salvo::oapi::{endpoint, ToSchema};
+use salvo::{Depot, Request, Response};
+use serde::Deserialize;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// Fields required to replace a Post.
+#[derive(Deserialize, ToSchema)]
+struct ReplacementPost {
+    title: String,
+    body: String,
+
+    /// "plain" or "markdown"; the format is left unchanged if this is omitted
+    format: Option<String>,
+
+    /// replaces every Tag on the Post; the Tags are left unchanged if this is omitted
+    tags: Option<Vec<String>>,
+}
+
+/// Fields required to replace a Post, when replacing more than one Post at a time.
+#[derive(Deserialize, ToSchema)]
+struct IdentifiedReplacementPost {
+    #[salvo(schema(value_type = String))]
+    post_id: Uuid,
+    title: String,
+    body: String,
+    format: Option<String>,
+    tags: Option<Vec<String>>,
+}
+
+/// Replace the title and body (and optionally the format and Tags) of one Post.
+///
+/// Only the author of the Post, or an admin, can replace it.
+#[endpoint(
+    parameters(
+        ("id" = String, Path, description = "id of the Post to replace")
+    ),
+    request_body(
+        content = ReplacementPost,
+        description = "A JSON blog post containing a title, a body, and (optionally) a format and a list of tags.",
+        content_type = "application/json",
+    ),
+    responses(
+        (status_code = 200, description = "success response"),
+        (status_code = 403, description = "the user is neither the author of the Post nor an admin", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 404, description = "the Post does not exist", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 409, description = "the Post was changed by someone else at the same time", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 422, description = "the new title, body, format or tags are invalid", body = ApiError, content_type = "application/problem+json"),
+    )
+)]
+pub(crate) async fn one(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    let Some(id) = parse_id(req, res) else { return };
+
+    match req.parse_json::<ReplacementPost>().await {
+        Ok(replacement) => {
+            let mut errors = vec![];
+            let (title, body, format) = validate(depot, "", Some(replacement.title), Some(replacement.body), replacement.format, &mut errors);
+            let tags = validate_tags("", replacement.tags, &mut errors);
+
+            if !errors.is_empty() {
+                res.render(ApiError::invalid("the replacement Post is invalid", errors));
+                return;
+            }
+
+            let state = depot.obtain::<Arc<Mutex<Database>>>().unwrap();
+            let mut db = state.lock().await;
+            let table = db.posts_by_id();
+
+            let Some(mut posts) = get_modifiable(table, &[id], depot, res) else { return };
+
+            let mut post = posts.remove(0);
+            post.edit(title, body, format);
+
+            match table.update(vec![PostsByIdUpdate::from(post).with_tags(tags)]) {
+                Ok(uuids) => res.render(format!("updated Posts with ids: {:?}", uuids)),
+                Err(e) => res.render(ApiError::from_db("error updating Post in DB", e)),
+            }
+        }
+        Err(e) => res.render(ApiError::bad_request(format!("error parsing request body: {}", e))),
+    }
+}
+
+/// Replace the titles and bodies (and optionally the formats and Tags) of one or more Posts.
+///
+/// Only the author of a Post, or an admin, can replace it. If the user cannot replace even one of
+/// the listed Posts, none of them are replaced.
+#[endpoint(
+    request_body(
+        content = Vec<IdentifiedReplacementPost>,
+        description = "A JSON list of blog posts, each containing a post id, a title, a body, and (optionally) a format and a list of tags.",
+        content_type = "application/json",
+    ),
+    responses(
+        (status_code = 200, description = "success response"),
+        (status_code = 400, description = "the request body is malformed, or lists the same Post more than once", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 403, description = "the user is neither the author of every Post nor an admin", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 404, description = "one or more of the Posts do not exist", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 409, description = "one or more of the Posts were changed by someone else at the same time; no Posts were updated", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 422, description = "one or more of the new titles, bodies, formats or tags are invalid; no Posts were updated", body = ApiError, content_type = "application/problem+json"),
+    )
+)]
+pub(crate) async fn many(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    match req.parse_json::<Vec<IdentifiedReplacementPost>>().await {
+        Ok(replacements) => {
+            let ids: Vec<Uuid> = replacements.iter().map(|replacement| replacement.post_id).collect();
+
+            if check_unique(&ids, res).is_none() { return };
+
+            let mut errors = vec![];
+
+            let fields: Vec<_> = replacements.into_iter().enumerate().map(|(index, replacement)| {
+                let pointer = format!("/{}", index);
+                let tags = validate_tags(&pointer, replacement.tags, &mut errors);
+                (validate(depot, &pointer, Some(replacement.title), Some(replacement.body), replacement.format, &mut errors), tags)
+            }).collect();
+
+            // if even one replacement is invalid, no Posts are replaced
+            if !errors.is_empty() {
+                res.render(ApiError::invalid("one or more replacement Posts are invalid", errors));
+                return;
+            }
+
+            let state = depot.obtain::<Arc<Mutex<Database>>>().unwrap();
+            let mut db = state.lock().await;
+            let table = db.posts_by_id();
+
+            let Some(posts) = get_modifiable(table, &ids, depot, res) else { return };
+
+            let rows = posts.into_iter().zip(fields).map(|(mut post, ((title, body, format), tags))| {
+                post.edit(title, body, format);
+                PostsByIdUpdate::from(post).with_tags(tags)
+            }).collect();
+
+            match table.update(rows) {
+                Ok(uuids) => res.render(format!("updated Posts with ids: {:?}", uuids)),
+                Err(e) => res.render(ApiError::from_db("error updating Posts in DB", e)),
+            }
+        }
+        Err(e) => res.render(ApiError::bad_request(format!("error parsing request body: {}", e))),
+    }
+}
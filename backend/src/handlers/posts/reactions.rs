@@ -0,0 +1,21 @@
+use crate::api_error::ApiError;
+use crate::model::reaction::{ReactionKind, ReactionKinds};
+use salvo::{Depot, Request, Response};
+
+pub(crate) mod put;
+pub(crate) mod delete;
+
+/// Parses the 'kind' route parameter, rendering an error if it is not one of the configured kinds
+/// of reaction.
+fn parse_kind(req: &Request, depot: &Depot, res: &mut Response) -> Option<ReactionKind> {
+    let kind: String = req.param::<String>("kind").expect("request did not contain a 'kind' param");
+    let kinds = depot.obtain::<ReactionKinds>().expect("reaction kinds were not injected into the depot");
+
+    let parsed = kinds.parse(&kind);
+
+    if parsed.is_none() {
+        res.render(ApiError::bad_request(format!("unknown kind of reaction {}; expected one of: {}", kind, kinds.all().join(", "))));
+    }
+
+    parsed
+}
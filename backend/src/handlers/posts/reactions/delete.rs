@@ -0,0 +1,41 @@
+use crate::api_error::ApiError;
+use crate::db::Database;
+use crate::handlers::posts::reactions::parse_kind;
+use crate::handlers::posts::{get_viewable, parse_id};
+use salvo::oapi::endpoint;
+use salvo::{Depot, Request, Response};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// Remove your reaction from a Post.
+///
+/// Users can only remove their own reactions.
+#[endpoint(
+    parameters(
+        ("id" = String, Path, description = "id of the Post"),
+        ("kind" = String, Path, description = "kind of reaction to remove, e.g. like")
+    ),
+    responses(
+        (status_code = 200, description = "success response"),
+        (status_code = 400, description = "the id is not a UUID, or the kind of reaction is unknown", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 401, description = "the user is not logged in", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 404, description = "the Post does not exist, or the user has not reacted to it in this way", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 503, description = "the database is unavailable", body = ApiError, content_type = "application/problem+json")
+    )
+)]
+pub(crate) async fn one(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    let Some(id) = parse_id(req, res) else { return };
+    let Some(kind) = parse_kind(req, depot, res) else { return };
+    let user_id = *depot.get::<Uuid>("token_user_id").expect("unknown user id");
+
+    let state = depot.obtain::<Arc<Mutex<Database>>>().unwrap();
+    let mut db = state.lock().await;
+
+    let Some(_) = get_viewable(db.posts_by_id(), &id, depot, res) else { return };
+
+    match db.post_reactions().delete(&id, &user_id, &kind.0) {
+        Ok(()) => res.render(format!("removed {} reaction from Post {}", kind.0, id)),
+        Err(e) => res.render(ApiError::from_db(&format!("error removing {} reaction from Post {}", kind.0, id), e)),
+    }
+}
@@ -0,0 +1,49 @@
+use crate::api_error::ApiError;
+use crate::db::tables::post_reactions::PostReactionsTableRow;
+use crate::db::Database;
+use crate::handlers::posts::reactions::parse_kind;
+use crate::handlers::posts::{get_viewable, parse_id};
+use crate::model::post::PostId;
+use crate::model::reaction::Reaction;
+use salvo::oapi::endpoint;
+use salvo::{Depot, Request, Response};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// React to a Post.
+///
+/// Each user can add at most one reaction of each kind to each Post, so reacting twice in the same
+/// way has no further effect. Anyone who is logged in, and can see the Post, can react to it.
+#[endpoint(
+    parameters(
+        ("id" = String, Path, description = "id of the Post to react to"),
+        ("kind" = String, Path, description = "kind of reaction, e.g. like")
+    ),
+    responses(
+        (status_code = 200, description = "success response"),
+        (status_code = 400, description = "the id is not a UUID, or the kind of reaction is unknown", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 401, description = "the user is not logged in", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 404, description = "the Post does not exist", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 503, description = "the database is unavailable", body = ApiError, content_type = "application/problem+json")
+    )
+)]
+pub(crate) async fn one(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    let Some(id) = parse_id(req, res) else { return };
+    let Some(kind) = parse_kind(req, depot, res) else { return };
+    let user_id = *depot.get::<Uuid>("token_user_id").expect("unknown user id");
+
+    let state = depot.obtain::<Arc<Mutex<Database>>>().unwrap();
+    let mut db = state.lock().await;
+
+    let Some(_) = get_viewable(db.posts_by_id(), &id, depot, res) else { return };
+
+    let reaction = Reaction::new(PostId(id), user_id, kind);
+    let kind = reaction.kind.0.clone();
+
+    match db.post_reactions().insert(PostReactionsTableRow::from(reaction)) {
+        Ok(true) => res.render(format!("added {} reaction to Post {}", kind, id)),
+        Ok(false) => res.render(format!("already added {} reaction to Post {}", kind, id)),
+        Err(e) => res.render(ApiError::from_db("error adding reaction to Post", e)),
+    }
+}
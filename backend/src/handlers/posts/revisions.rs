@@ -0,0 +1,43 @@
+use crate::api_error::ApiError;
+use crate::db::tables::posts_by_id::PostsByIdTableLike;
+use crate::handlers::posts::may_modify;
+use crate::model::post::Post;
+use salvo::{Depot, Request, Response};
+use uuid::Uuid;
+
+pub(crate) mod get;
+pub(crate) mod restore;
+
+/// Parses the 'n' route parameter as a revision number, rendering an error if it is not one.
+fn parse_revision(req: &Request, res: &mut Response) -> Option<i32> {
+    let n: String = req.param::<String>("n").expect("request did not contain a 'n' param");
+
+    match n.parse::<i32>() {
+        Ok(revision) if revision > 0 => Some(revision),
+        _ => {
+            res.render(ApiError::bad_request(format!("cannot parse {} as a revision number", n)));
+            None
+        }
+    }
+}
+
+/// Only the author of a Post, or an admin, may see its history, because earlier revisions may have
+/// been drafts. Renders an error and returns None if the requesting user may not.
+fn get_with_history(table: &dyn PostsByIdTableLike, id: &Uuid, depot: &Depot, res: &mut Response) -> Option<Post> {
+    match table.get(id) {
+        Err(e) => {
+            res.render(ApiError::from_db(&format!("error getting Post by id {}", id), e));
+            None
+        }
+        Ok(row) => {
+            let post = Post::from(row);
+
+            if !may_modify(depot, &post) {
+                res.render(ApiError::forbidden(format!("only the author or an admin can see the revisions of Post {}", id)));
+                return None;
+            }
+
+            Some(post)
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use crate::api_error::ApiError;
+use crate::db::Database;
+use crate::handlers::pagination;
+use crate::handlers::pagination::Paginated;
+use crate::handlers::posts::parse_id;
+use crate::handlers::posts::revisions::{get_with_history, parse_revision};
+use salvo::oapi::endpoint;
+use salvo::prelude::Json;
+use salvo::{Depot, Request, Response};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Returns one Revision of a Post.
+///
+/// Only the author of the Post, or an admin, can see its Revisions.
+#[endpoint(
+    parameters(
+        ("id" = String, Path, description = "id of the Post"),
+        ("n" = i32, Path, description = "number of the Revision, starting from 1")
+    ),
+    responses(
+        (status_code = 200, description = "success response"),
+        (status_code = 400, description = "the id is not a UUID, or the revision is not a positive number", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 403, description = "the user is neither the author of the Post nor an admin", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 404, description = "the Post or the Revision does not exist", body = ApiError, content_type = "application/problem+json"),
+    )
+)]
+pub(crate) async fn one(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    let Some(id) = parse_id(req, res) else { return };
+    let Some(n) = parse_revision(req, res) else { return };
+
+    let state = depot.obtain::<Arc<Mutex<Database>>>().unwrap();
+    let mut db = state.lock().await;
+
+    let Some(_) = get_with_history(db.posts_by_id(), &id, depot, res) else { return };
+
+    match db.post_revisions().get(&id, n) {
+        Err(e) => res.render(ApiError::from_db(&format!("error getting revision {} of Post {}", n, id), e)),
+        Ok(row) => res.render(Json(row)),
+    }
+}
+
+/// Returns one page of the Revisions of a Post, oldest first.
+///
+/// A new Revision is recorded every time the title or body of a Post changes. The latest Revision
+/// is always the same as the Post itself. Only the author of the Post, or an admin, can see its
+/// Revisions.
+#[endpoint(
+    parameters(
+        ("id" = String, Path, description = "id of the Post"),
+        ("limit" = Option<u32>, Query, description = "maximum number of Revisions to return, from 1 to 100 (default 10)"),
+        ("cursor" = Option<String>, Query, description = "cursor from a previous response; omit to get the first page")
+    ),
+    responses(
+        (status_code = 200, description = "success response"),
+        (status_code = 400, description = "the id is not a UUID, the limit is out of range, or the cursor is malformed", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 403, description = "the user is neither the author of the Post nor an admin", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 404, description = "the Post does not exist", body = ApiError, content_type = "application/problem+json"),
+    )
+)]
+pub(crate) async fn many(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    let Some(id) = parse_id(req, res) else { return };
+    let Some(limit) = pagination::requested_limit(req, res) else { return };
+    let Some(page) = pagination::requested_page(req, res) else { return };
+
+    let state = depot.obtain::<Arc<Mutex<Database>>>().unwrap();
+    let mut db = state.lock().await;
+
+    let Some(_) = get_with_history(db.posts_by_id(), &id, depot, res) else { return };
+
+    match db.post_revisions().list_page(&id, &page, limit) {
+        Err(e) => res.render(ApiError::from_db(&format!("error listing revisions of Post {}", id), e)),
+        Ok(revisions) => res.render(Json(Paginated::from(revisions))),
+    }
+}
@@ -0,0 +1,53 @@
+use crate::api_error::ApiError;
+use crate::db::tables::posts_by_id::PostsByIdUpdate;
+use crate::db::Database;
+use crate::handlers::posts::{get_modifiable, parse_id};
+use crate::handlers::posts::revisions::parse_revision;
+use crate::model::post::Revision;
+use salvo::oapi::endpoint;
+use salvo::{Depot, Request, Response};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Restore the title and body of a Post from one of its earlier Revisions.
+///
+/// Restoring records a new Revision, so it can itself be undone. Only the author of the Post, or
+/// an admin, can restore it.
+#[endpoint(
+    parameters(
+        ("id" = String, Path, description = "id of the Post to restore"),
+        ("n" = i32, Path, description = "number of the Revision to restore")
+    ),
+    responses(
+        (status_code = 200, description = "success response"),
+        (status_code = 400, description = "the id is not a UUID, or the revision is not a positive number", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 403, description = "the user is neither the author of the Post nor an admin", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 404, description = "the Post or the Revision does not exist", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 409, description = "the Post was changed by someone else at the same time", body = ApiError, content_type = "application/problem+json"),
+    )
+)]
+pub(crate) async fn restore(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    let Some(id) = parse_id(req, res) else { return };
+    let Some(n) = parse_revision(req, res) else { return };
+
+    let state = depot.obtain::<Arc<Mutex<Database>>>().unwrap();
+    let mut db = state.lock().await;
+
+    let Some(mut posts) = get_modifiable(db.posts_by_id(), &[id], depot, res) else { return };
+
+    let revision = match db.post_revisions().get(&id, n) {
+        Ok(row) => Revision::from(row),
+        Err(e) => {
+            res.render(ApiError::from_db(&format!("error getting revision {} of Post {}", n, id), e));
+            return;
+        }
+    };
+
+    let mut post = posts.remove(0);
+    post.restore(revision);
+
+    match db.posts_by_id().update(vec![PostsByIdUpdate::from(post)]) {
+        Ok(uuids) => res.render(format!("restored revision {} of Posts with ids: {:?}", n, uuids)),
+        Err(e) => res.render(ApiError::from_db("error restoring Post", e)),
+    }
+}
@@ -0,0 +1,67 @@
+use crate::api_error::ApiError;
+use crate::db::tables::posts_by_id::PostsByIdUpdate;
+use crate::db::Database;
+use crate::handlers::posts::{get_modifiable, parse_id};
+use chrono::{DateTime, Utc};
+use salvo::http::StatusCode;
+use salvo::oapi::{endpoint, ToSchema};
+use salvo::{Depot, Request, Response};
+use serde::Deserialize;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// When to publish a Post.
+#[derive(Deserialize, ToSchema)]
+struct Schedule {
+    /// an RFC 3339 timestamp, or null to cancel the schedule
+    #[salvo(schema(value_type = Option<String>))]
+    publish_at: Option<DateTime<Utc>>,
+}
+
+/// Schedule one Post to be published automatically at a later time.
+///
+/// Drafts and archived Posts can be scheduled. A Post which is already published cannot be. Only
+/// the author of the Post, or an admin, can schedule it.
+#[endpoint(
+    parameters(
+        ("id" = String, Path, description = "id of the Post to schedule")
+    ),
+    request_body(
+        content = Schedule,
+        description = "A JSON object containing the time at which to publish the Post.",
+        content_type = "application/json",
+    ),
+    responses(
+        (status_code = 200, description = "success response"),
+        (status_code = 400, description = "the request body is malformed", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 403, description = "the user is neither the author of the Post nor an admin", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 404, description = "the Post does not exist", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 409, description = "the Post is already published, or was changed by someone else at the same time", body = ApiError, content_type = "application/problem+json"),
+    )
+)]
+pub(crate) async fn schedule(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    let Some(id) = parse_id(req, res) else { return };
+
+    match req.parse_json::<Schedule>().await {
+        Ok(requested) => {
+            let state = depot.obtain::<Arc<Mutex<Database>>>().unwrap();
+            let mut db = state.lock().await;
+            let table = db.posts_by_id();
+
+            let Some(mut posts) = get_modifiable(table, &[id], depot, res) else { return };
+
+            let mut post = posts.remove(0);
+
+            if let Err(e) = post.schedule(requested.publish_at) {
+                res.render(ApiError::new(StatusCode::CONFLICT, format!("error scheduling Post: {}", e)));
+                return;
+            }
+
+            match table.update(vec![PostsByIdUpdate::from(post)]) {
+                Ok(uuids) => res.render(format!("scheduled Posts with ids: {:?}", uuids)),
+                Err(e) => res.render(ApiError::from_db("error scheduling Post", e)),
+            }
+        }
+        Err(e) => res.render(ApiError::bad_request(format!("error parsing request body: {}", e))),
+    }
+}
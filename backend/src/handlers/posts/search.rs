@@ -0,0 +1,73 @@
+use crate::api_error::ApiError;
+use crate::db::Database;
+use crate::handlers::pagination;
+use crate::handlers::posts::{visibility, with_details, PostResponse};
+use salvo::oapi::endpoint;
+use salvo::prelude::Json;
+use salvo::{Depot, Request, Response};
+use serde::Serialize;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// A Post which matches a search query, along with its Tags.
+#[derive(Serialize)]
+struct SearchResponse {
+    #[serde(flatten)]
+    post: PostResponse,
+    rank: f32,
+    snippet: String,
+}
+
+/// Endpoint to search the titles and bodies of Posts.
+///
+/// Returns the Posts which contain every word in the query, best matches first, in a JSON list.
+/// Matches in titles rank above matches in bodies. Each Post has a `rank` (higher is better) and a
+/// `snippet` of its body, which is HTML-escaped, with the matching words wrapped in `<mark>` tags.
+///
+/// Like GET /posts, anonymous users only see published Posts, logged-in users also see their own
+/// drafts and archived Posts, and admins see every Post.
+#[endpoint(
+    parameters(
+        ("q" = String, Query, description = "the words to search for"),
+        ("limit" = Option<u32>, Query, description = "maximum number of Posts to return, from 1 to 100 (default 10)")
+    ),
+    responses(
+        (status_code = 200, description = "success response"),
+        (status_code = 400, description = "the query is missing or empty, or the limit is out of range", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 503, description = "the database is unavailable", body = ApiError, content_type = "application/problem+json")
+    )
+)]
+pub(crate) async fn search(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    let Some(limit) = pagination::requested_limit(req, res) else { return };
+
+    let Some(query) = req.query::<String>("q").filter(|q| !q.trim().is_empty()) else {
+        res.render(ApiError::bad_request("the 'q' query parameter is required"));
+        return;
+    };
+
+    let state = depot.obtain::<Arc<Mutex<Database>>>().unwrap();
+    let mut db = state.lock().await;
+
+    let results = match db.posts_by_id().search(&query, &visibility(depot), limit) {
+        Ok(results) => results,
+        Err(e) => {
+            res.render(ApiError::from_db("error searching Posts", e));
+            return;
+        }
+    };
+
+    let (posts, scores): (Vec<_>, Vec<_>) = results.into_iter()
+        .map(|result| (result.post, (result.rank, result.snippet)))
+        .unzip();
+
+    match with_details(&mut db, posts) {
+        Err(e) => res.render(ApiError::from_db("error getting Tags and Reactions of Posts", e)),
+        Ok(posts) => {
+            let results: Vec<SearchResponse> = posts.into_iter().zip(scores)
+                .map(|(post, (rank, snippet))| SearchResponse { post, rank, snippet })
+                .collect();
+
+            res.render(Json(results))
+        }
+    }
+}
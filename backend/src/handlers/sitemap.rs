@@ -0,0 +1,211 @@
+use crate::api_error::ApiError;
+use crate::db::error::DbError;
+use crate::db::page::PageRequest;
+use crate::db::tables::posts_by_id::FeedPosition;
+use crate::db::Database;
+use crate::handlers::feeds::{escape_xml, render_xml};
+use crate::handlers::pagination;
+use crate::model::post::Post;
+use crate::model::site::Site;
+use chrono::{DateTime, SecondsFormat, Utc};
+use salvo::oapi::endpoint;
+use salvo::{Depot, Request, Response};
+use std::fmt::{Display, Formatter, Write};
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// The most URLs which one sitemap may list. See https://www.sitemaps.org/protocol.html
+const MAX_URLS: usize = 50_000;
+
+const CONTENT_TYPE: &str = "application/xml; charset=utf-8";
+
+/// One URL in a sitemap, or one sitemap in a sitemap index.
+struct Entry {
+    loc: String,
+    lastmod: DateTime<Utc>,
+}
+
+/// Where a numbered sitemap starts. Its number is part of its cursor, so that a cursor only locates
+/// the sitemap it was listed with.
+struct SitemapStart {
+    number: usize,
+    after: FeedPosition,
+}
+
+impl Display for SitemapStart {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}_{}", self.number, self.after)
+    }
+}
+
+impl FromStr for SitemapStart {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("cannot parse {} as the start of a sitemap", s);
+        let (number, after) = s.split_once('_').ok_or_else(invalid)?;
+
+        Ok(SitemapStart {
+            number: number.parse().map_err(|_| invalid())?,
+            after: after.parse()?,
+        })
+    }
+}
+
+/// Gets the Posts in one sitemap: the oldest MAX_URLS published Posts after `after`, oldest first,
+/// and where the next sitemap starts, if there is one. The database is only locked while they are
+/// read, not while the sitemap is written.
+async fn sitemap_posts(state: &Mutex<Database>, after: FeedPosition) -> Result<(Vec<Post>, Option<FeedPosition>), DbError> {
+    // the page before a position holds the Posts published just after it, newest first
+    let page = state.lock().await.posts_by_id().published(None, &PageRequest::Before(after), MAX_URLS)?;
+
+    let next = match page.prev {
+        Some(PageRequest::Before(position)) => Some(position),
+        _ => None,
+    };
+
+    Ok((page.rows.into_iter().rev().map(Post::from).collect(), next))
+}
+
+fn entry(site: &Site, post: &Post) -> Entry {
+    Entry { loc: site.post_url(&post.slug.0), lastmod: post.updated_at }
+}
+
+/// Builds a sitemap (`tag` = "url") or a sitemap index (`tag` = "sitemap").
+fn sitemap(root: &str, tag: &str, entries: &[Entry]) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+
+    // writing to a String cannot fail
+    let _ = writeln!(xml, "<{} xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">", root);
+
+    for Entry { loc, lastmod } in entries {
+        let lastmod = lastmod.to_rfc3339_opts(SecondsFormat::Secs, true);
+        let _ = writeln!(xml, "  <{tag}><loc>{}</loc><lastmod>{}</lastmod></{tag}>", escape_xml(loc), lastmod);
+    }
+
+    let _ = writeln!(xml, "</{}>", root);
+    xml
+}
+
+/// Sitemap of every published Post, with when it was last modified.
+///
+/// If there are more Posts than fit in one sitemap (50,000), this is a sitemap index instead,
+/// listing numbered sitemaps at /sitemaps/1.xml, /sitemaps/2.xml?cursor=..., etc. Sitemaps are
+/// numbered oldest first, so new Posts are only ever added to the last one, and the others do not
+/// change. Like feeds, sitemaps have ETags, and support If-None-Match.
+///
+/// Sitemaps link to Posts on the site configured in config.toml, so the site should serve this
+/// sitemap (and the numbered sitemaps) itself, e.g. through a reverse proxy.
+#[endpoint(
+    responses(
+        (status_code = 200, description = "success response", content_type = "application/xml"),
+        (status_code = 304, description = "the sitemap has not changed since the client last got it"),
+        (status_code = 503, description = "the database is unavailable", body = ApiError, content_type = "application/problem+json")
+    )
+)]
+pub(crate) async fn index(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    let site = depot.obtain::<Site>().expect("Site was not injected into the depot");
+    let state = depot.obtain::<Arc<Mutex<Database>>>().unwrap();
+
+    // each numbered sitemap after the first is located by a cursor, so it can be read without
+    // reading every sitemap before it
+    let mut after = FeedPosition::origin();
+    let mut sitemaps = vec![];
+
+    loop {
+        let (posts, next) = match sitemap_posts(state, after).await {
+            Ok(found) => found,
+            Err(e) => {
+                res.render(ApiError::from_db("error getting published Posts", e));
+                return;
+            }
+        };
+
+        // if every Post fits in one sitemap, this is that sitemap
+        if sitemaps.is_empty() && next.is_none() {
+            let urls: Vec<Entry> = posts.iter().map(|post| entry(site, post)).collect();
+            render_xml(req, res, CONTENT_TYPE, sitemap("urlset", "url", &urls));
+            return;
+        }
+
+        let number = sitemaps.len() + 1;
+
+        let loc = match number {
+            1 => format!("{}/sitemaps/1.xml", site.url),
+            _ => format!("{}/sitemaps/{}.xml?cursor={}", site.url, number, pagination::encode(&PageRequest::Before(SitemapStart { number, after }))),
+        };
+
+        if let Some(lastmod) = posts.iter().map(|post| post.updated_at).max() {
+            sitemaps.push(Entry { loc, lastmod });
+        }
+
+        match next {
+            Some(next) => after = next,
+            None => break,
+        }
+    }
+
+    render_xml(req, res, CONTENT_TYPE, sitemap("sitemapindex", "sitemap", &sitemaps));
+}
+
+/// One of the numbered sitemaps listed by the sitemap index at /sitemap.xml, e.g. "2.xml". Each
+/// lists up to 50,000 published Posts, oldest first. Every sitemap after the first needs the
+/// cursor it is listed with in the index.
+#[endpoint(
+    parameters(
+        ("file" = String, Path, description = "number of the sitemap, followed by .xml"),
+        ("cursor" = Option<String>, Query, description = "where the sitemap starts, as listed in the sitemap index (not needed for the first sitemap)")
+    ),
+    responses(
+        (status_code = 200, description = "success response", content_type = "application/xml"),
+        (status_code = 304, description = "the sitemap has not changed since the client last got it"),
+        (status_code = 400, description = "the cursor is malformed", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 404, description = "there is no such sitemap", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 503, description = "the database is unavailable", body = ApiError, content_type = "application/problem+json")
+    )
+)]
+pub(crate) async fn numbered(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    let file: String = req.param::<String>("file").expect("request did not contain a 'file' param");
+
+    let number = file.strip_suffix(".xml")
+        .and_then(|number| number.parse::<usize>().ok())
+        .filter(|number| *number > 0);
+
+    let Some(number) = number else {
+        res.render(ApiError::not_found(format!("there is no sitemap named {}", file)));
+        return;
+    };
+
+    let Some(start) = pagination::requested_page::<SitemapStart>(req, res) else { return };
+
+    // only the first sitemap starts without a cursor, and every other cursor only locates the
+    // sitemap it was listed with in the index
+    let after = match (number, start) {
+        (1, PageRequest::First) => FeedPosition::origin(),
+        (_, PageRequest::Before(start)) if start.number == number => start.after,
+        _ => {
+            res.render(ApiError::not_found(format!("there is no sitemap named {} at that cursor", file)));
+            return;
+        }
+    };
+
+    let site = depot.obtain::<Site>().expect("Site was not injected into the depot");
+    let state = depot.obtain::<Arc<Mutex<Database>>>().unwrap();
+
+    let posts = match sitemap_posts(state, after).await {
+        Ok((posts, _)) => posts,
+        Err(e) => {
+            res.render(ApiError::from_db("error getting published Posts", e));
+            return;
+        }
+    };
+
+    if posts.is_empty() {
+        res.render(ApiError::not_found(format!("there is no sitemap named {} at that cursor", file)));
+        return;
+    }
+
+    let urls: Vec<Entry> = posts.iter().map(|post| entry(site, post)).collect();
+    render_xml(req, res, CONTENT_TYPE, sitemap("urlset", "url", &urls));
+}
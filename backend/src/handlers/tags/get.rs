@@ -0,0 +1,28 @@
+use crate::api_error::ApiError;
+use crate::db::Database;
+use crate::handlers::posts::visibility;
+use salvo::oapi::endpoint;
+use salvo::prelude::Json;
+use salvo::{Depot, Response};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Returns every Tag, sorted by name, along with how many Posts have it.
+///
+/// Only Posts which the requesting user can see are counted, so anonymous users only see the Tags
+/// of published Posts.
+#[endpoint(
+    responses(
+        (status_code = 200, description = "success response"),
+        (status_code = 503, description = "the database is unavailable", body = ApiError, content_type = "application/problem+json")
+    )
+)]
+pub(crate) async fn many(depot: &mut Depot, res: &mut Response) {
+    let state = depot.obtain::<Arc<Mutex<Database>>>().unwrap();
+    let mut db = state.lock().await;
+
+    match db.posts_by_id().tag_counts(&visibility(depot)) {
+        Err(e) => res.render(ApiError::from_db("error counting Tags", e)),
+        Ok(counts) => res.render(Json(counts)),
+    }
+}
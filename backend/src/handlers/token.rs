@@ -0,0 +1 @@
+pub(crate) mod refresh;
\ No newline at end of file
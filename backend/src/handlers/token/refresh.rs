@@ -0,0 +1,35 @@
+use crate::api_error::ApiError;
+use crate::auth::{Authenticator, AuthenticatorLike};
+use crate::auth_middleware::{token, TokenSource};
+use crate::handlers::login::render_session;
+use salvo::oapi::endpoint;
+use salvo::{Depot, Request, Response};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Swap an unexpired authentication token for a new one, which expires later.
+///
+/// The old token cannot be used again. When Keycloak is used, this also refreshes the user's
+/// Keycloak session, which rotates its refresh token. A session kept in cookies stays in (new) cookies.
+#[endpoint(
+    responses(
+        (status_code = 200, description = "success response, containing the new authentication token and its expiry time"),
+        (status_code = 401, description = "the token is missing, unrecognized, or expired, or the session cannot be refreshed", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 403, description = "the session cookie was sent without the matching x-csrf-token header", body = ApiError, content_type = "application/problem+json")
+    )
+)]
+pub(crate) async fn refresh(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    let (token, source) = match token(req) {
+        Ok(Some(found)) => found,
+        Ok(None) => return res.render(ApiError::unauthorized("Missing authentication token")),
+        Err(e) => return res.render(e),
+    };
+
+    let state = depot.obtain::<Arc<Mutex<Authenticator>>>().unwrap();
+    let mut auth = state.lock().await;
+
+    match auth.refresh(token).await {
+        Ok(session) => render_session(session, source == TokenSource::Cookie, res),
+        Err(e) => res.render(ApiError::unauthorized(format!("error refreshing session: {}", e))),
+    }
+}
@@ -0,0 +1,46 @@
+use crate::api_error::ApiError;
+use crate::db::tables::users::UsersTableRow;
+use chrono::{DateTime, Utc};
+use salvo::{Request, Response};
+use serde::Serialize;
+use std::str::FromStr;
+use uuid::Uuid;
+
+pub(crate) mod get;
+pub(crate) mod patch;
+
+/// Parses the 'id' route parameter as a User id, rendering an error if it is not a UUID.
+fn parse_id(req: &Request, res: &mut Response) -> Option<Uuid> {
+    let id: String = req.param::<String>("id").expect("request did not contain a 'id' param");
+
+    match Uuid::from_str(&id) {
+        Ok(key) => Some(key),
+        Err(_) => {
+            res.render(ApiError::bad_request(format!("cannot parse {} as UUID", id)));
+            None
+        }
+    }
+}
+
+/// The parts of a User's profile which anyone can see. Usernames are only shown to their Users,
+/// because they are used to log in.
+#[derive(Serialize)]
+struct PublicProfile {
+    user_id: Uuid,
+    display_name: String,
+    bio: Option<String>,
+    avatar_url: Option<String>,
+    created_at: DateTime<Utc>,
+}
+
+impl From<UsersTableRow> for PublicProfile {
+    fn from(value: UsersTableRow) -> Self {
+        Self {
+            user_id: value.user_id,
+            display_name: value.display_name,
+            bio: value.bio,
+            avatar_url: value.avatar_url,
+            created_at: value.created_at,
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use crate::api_error::ApiError;
+use crate::db::Database;
+use crate::handlers::users::{parse_id, PublicProfile};
+use salvo::oapi::endpoint;
+use salvo::prelude::Json;
+use salvo::{Depot, Request, Response};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// Endpoint to GET the public profile of one User by id.
+///
+/// Users are recorded the first time they log in, so authors who have never logged in do not have
+/// profiles.
+#[endpoint(
+    parameters(
+        ("id" = String, Path, description = "id of the User")
+    ),
+    responses(
+        (status_code = 200, description = "success response"),
+        (status_code = 400, description = "the id is not a UUID", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 404, description = "the User does not exist", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 503, description = "the database is unavailable", body = ApiError, content_type = "application/problem+json")
+    )
+)]
+pub(crate) async fn one(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    let Some(id) = parse_id(req, res) else { return };
+
+    let state = depot.obtain::<Arc<Mutex<Database>>>().unwrap();
+    let mut db = state.lock().await;
+
+    match db.users().get(&id) {
+        Err(e) => res.render(ApiError::from_db("error getting User by id", e)),
+        Ok(row) => res.render(Json(PublicProfile::from(row))),
+    }
+}
+
+/// Endpoint to GET the full profile of the logged-in User, including their username.
+#[endpoint(
+    responses(
+        (status_code = 200, description = "success response"),
+        (status_code = 401, description = "the user is not logged in", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 503, description = "the database is unavailable", body = ApiError, content_type = "application/problem+json")
+    )
+)]
+pub(crate) async fn me(depot: &mut Depot, res: &mut Response) {
+    let user_id = *depot.get::<Uuid>("token_user_id").expect("unknown user id");
+
+    let state = depot.obtain::<Arc<Mutex<Database>>>().unwrap();
+    let mut db = state.lock().await;
+
+    match db.users().get(&user_id) {
+        Err(e) => res.render(ApiError::from_db("error getting logged-in User", e)),
+        Ok(row) => res.render(Json(row)),
+    }
+}
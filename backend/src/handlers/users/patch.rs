@@ -0,0 +1,85 @@
+use crate::api_error::{ApiError, FieldError};
+use crate::db::tables::users::UsersTableRow;
+use crate::db::Database;
+use crate::handlers::posts::check;
+use crate::model::post::ValidationError;
+use crate::model::user::{AvatarUrl, Bio, DisplayName, User};
+use salvo::oapi::{endpoint, ToSchema};
+use salvo::{Depot, Request, Response};
+use serde::Deserialize;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// Fields which can be changed on a profile. Omitted fields are left unchanged, and an empty bio
+/// or avatar URL removes it.
+#[derive(Deserialize, ToSchema)]
+struct ProfilePatch {
+    display_name: Option<String>,
+    bio: Option<String>,
+    avatar_url: Option<String>,
+}
+
+/// Parses an optional field which can be removed by setting it to an empty string.
+fn removable<T>(raw: Option<String>, parse: fn(String) -> Result<T, ValidationError>, errors: &mut Vec<FieldError>) -> Option<Option<T>> {
+    match raw {
+        None => None,
+        Some(raw) if raw.trim().is_empty() => Some(None),
+        Some(raw) => check(parse(raw), "", errors).map(Some),
+    }
+}
+
+/// Partially update the profile of the logged-in User.
+#[endpoint(
+    request_body(
+        content = ProfilePatch,
+        description = "A JSON object containing a new display name, bio, and / or avatar URL.",
+        content_type = "application/json",
+    ),
+    responses(
+        (status_code = 200, description = "success response"),
+        (status_code = 400, description = "the request body is malformed", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 401, description = "the user is not logged in", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 422, description = "one or more fields are invalid", body = ApiError, content_type = "application/problem+json"),
+        (status_code = 503, description = "the database is unavailable", body = ApiError, content_type = "application/problem+json")
+    )
+)]
+pub(crate) async fn me(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    let user_id = *depot.get::<Uuid>("token_user_id").expect("unknown user id");
+
+    let patch = match req.parse_json::<ProfilePatch>().await {
+        Ok(patch) => patch,
+        Err(e) => {
+            res.render(ApiError::bad_request(format!("error parsing request body: {}", e)));
+            return;
+        }
+    };
+
+    let mut errors = vec![];
+    let display_name = patch.display_name.and_then(|name| check(DisplayName::parse(name), "", &mut errors));
+    let bio = removable(patch.bio, Bio::parse, &mut errors);
+    let avatar_url = removable(patch.avatar_url, AvatarUrl::parse, &mut errors);
+
+    if !errors.is_empty() {
+        res.render(ApiError::invalid("the patch is invalid", errors));
+        return;
+    }
+
+    let state = depot.obtain::<Arc<Mutex<Database>>>().unwrap();
+    let mut db = state.lock().await;
+
+    let mut user = match db.users().get(&user_id) {
+        Ok(row) => User::from(row),
+        Err(e) => {
+            res.render(ApiError::from_db("error getting logged-in User", e));
+            return;
+        }
+    };
+
+    user.edit(display_name, bio, avatar_url);
+
+    match db.users().update(UsersTableRow::from(user)) {
+        Ok(id) => res.render(format!("updated User with id: {}", id)),
+        Err(e) => res.render(ApiError::from_db("error updating User in DB", e)),
+    }
+}
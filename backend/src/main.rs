@@ -1,25 +1,36 @@
+mod api_error;
 mod model;
 mod handlers;
 mod auth_middleware;
 mod config;
 mod auth;
 mod db;
+mod media;
+mod scheduler;
 
+use crate::api_error::REQUEST_ID_HEADER;
+use crate::auth::sessions::SessionStore;
 use crate::auth::Authenticator;
 use crate::auth_middleware::Auth;
 use crate::config::Config;
 use crate::db::Database;
+use crate::media::MediaStoreLike;
 use env_logger::Builder;
 use log::LevelFilter;
 use salvo::catcher::Catcher;
 use salvo::conn::rustls::{Keycert, RustlsConfig};
 use salvo::cors::Cors;
+use salvo::http::header::HeaderName;
 use salvo::http::Method;
+use salvo::oapi::security::{Http, HttpAuthScheme, SecurityScheme};
+use salvo::oapi::SecurityRequirement;
 use salvo::prelude::*;
 use salvo_extra::affix_state;
+use salvo_extra::request_id::RequestId;
 use std::str::FromStr;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::{watch, Mutex};
 // There should be no endpoint definitions here. The purpose of main.rs is just to wire up the
 // endpoint implementations, which themselves live in different files.
 
@@ -33,6 +44,29 @@ fn read_file(path: &str) -> Result<String, Box<dyn std::error::Error>> {
     Ok(buffer)
 }
 
+/// Resolves when the process is asked to stop, with Ctrl+C or (on Unix) SIGTERM.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c().await.expect("unable to listen for Ctrl+C");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("unable to listen for SIGTERM")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
 #[tokio::main]
 async fn main() {
 
@@ -88,8 +122,11 @@ async fn main() {
     // TODO (best practices) research and implement best practices for CORS here
     let cors = Cors::new()
         .allow_origin(&config.cors_allowlist) // Allow specific origins
-        .allow_methods(vec![Method::GET, Method::POST, Method::PUT, Method::DELETE]) // Allow specific HTTP methods
-        // .allow_headers(vec!["Content-Type".into(), "Authorization".into()]) // Allow specific headers
+        .allow_methods(vec![Method::GET, Method::POST, Method::PUT, Method::PATCH, Method::DELETE]) // Allow specific HTTP methods
+        .allow_headers(vec![
+            "content-type", "authorization", "x-token", auth_middleware::CSRF_HEADER,
+            "x-keycloak-access-token", "x-keycloak-id-token", "x-keycloak-refresh-token", "x-keycloak-realm",
+        ]) // Allow the headers browsers need to authenticate
         .allow_credentials(true) // Allow sending of cookies and authentication headers
         .max_age(86400) // Cache preflight requests for 24 hours
         .into_handler();
@@ -122,6 +159,33 @@ async fn main() {
     //     POST /posts     => handlers/posts/post.rs (fn many(), fn one())
     //     etc.
     //
+    //   Actions which do not fit any of the methods above are POSTed to a custom method on the
+    //   resource, and are handled in a file named after the action
+    //
+    //     POST /posts/:id:publish   => handlers/posts/publish.rs (fn publish())
+    //     POST /posts/:id:unpublish => handlers/posts/publish.rs (fn unpublish())
+    //     POST /posts/:id:schedule  => handlers/posts/schedule.rs (fn schedule())
+    //
+    //   ...and the same goes for actions on a whole collection, which need not be POSTed
+    //
+    //     GET  /posts:search?q=rust => handlers/posts/search.rs (fn search())
+    //
+    //   Resources which only exist within another resource are nested beneath it, in both the
+    //   path and the handlers directory
+    //
+    //     GET    /posts/:id/revisions               => handlers/posts/revisions/get.rs (fn many())
+    //     POST   /posts/:id/revisions/:n:restore    => handlers/posts/revisions/restore.rs (fn restore())
+    //     DELETE /posts/:id/comments/:comment_id    => handlers/posts/comments/delete.rs (fn one())
+    //     PUT    /posts/:id/reactions/:kind         => handlers/posts/reactions/put.rs (fn one())
+    //     PUT    /authors/:author_id/follow         => handlers/authors/follow.rs (fn follow())
+    //
+    //   Documents which are not JSON are named like files, and live together in the handlers
+    //   directory, whatever resource they belong to
+    //
+    //     GET    /feeds/atom.xml                    => handlers/feeds/atom.rs (fn all())
+    //     GET    /authors/:author_id/feeds/atom.xml => handlers/feeds/atom.rs (fn author())
+    //     GET    /sitemap.xml                       => handlers/sitemap.rs (fn index())
+    //
     //   Not all endpoints will necessarily be implemented for each model type.
     //
     //   The :id parameter (or whatever it is) should always be the primary key of the table which
@@ -141,20 +205,217 @@ async fn main() {
         _ => Database::InMemory(db::in_memory::Database::new()),
     };
 
+    let db = Arc::new(Mutex::new(db));
+
+    // publishes scheduled Posts in the background, until the server shuts down
+    let (shutdown_sender, shutdown_receiver) = watch::channel(false);
+    let scheduler = tokio::spawn(scheduler::run(
+        Arc::clone(&db),
+        Duration::from_secs(config.scheduler.interval_seconds),
+        shutdown_receiver.clone(),
+    ));
+
+    let limits = model::post::Limits {
+        title_max_length: config.posts.title_max_length,
+        body_max_length: config.posts.body_max_length,
+    };
+
+    let reaction_kinds = model::reaction::ReactionKinds::new(&config.posts.reaction_kinds);
+
+    let media_limits = model::media::Limits { max_size_bytes: config.media.max_size_bytes };
+
+    // the contents of uploaded files are kept outside of the database
+    let media_store: Arc<dyn MediaStoreLike> = match config.media.store.as_str() {
+        "local" => match media::local::Impl::new(&config.media.path) {
+            Ok(store) => Arc::new(store),
+            Err(e) => panic!("{}", e),
+        },
+        "in-memory" => Arc::new(media::in_memory::Impl::new()),
+        _ => panic!("unsupported media store: {}", config.media.store),
+    };
+
+    let site = model::site::Site::new(&config.site.title, &config.site.url);
+
+    let sessions = match (config.auth.session_store.as_str(), config.db.mode.as_str()) {
+        ("in-memory", _) => SessionStore::InMemory(auth::sessions::in_memory::Impl::new()),
+        ("postgres", "docker") => SessionStore::Postgres(auth::sessions::postgres::Impl::new(Arc::clone(&db))),
+        ("postgres", _) => panic!("the \"postgres\" session store requires the \"docker\" db mode"),
+        (session_store, _) => panic!("unsupported session store: {}", session_store),
+    };
+
+    // the Authenticator records Users in the database when they log in
+    let authenticator = match Authenticator::new(config.auth.mode.as_str(), config.auth.users_path.as_str(), Arc::clone(&db), sessions, config.auth.max_sessions_per_user) {
+        Ok(authenticator) => Arc::new(Mutex::new(authenticator)),
+        Err(e) => panic!("unable to configure authentication: {}", e),
+    };
+
+    // removes expired sessions in the background, until the server shuts down
+    let sweeper = tokio::spawn(auth::sweeper::run(
+        Arc::clone(&authenticator),
+        Duration::from_secs(config.auth.sweep_interval_seconds),
+        shutdown_receiver,
+    ));
+
     let public_router = Router::new()
-        .hoop(affix_state::inject(Arc::new(Mutex::new(db))))
-        .hoop(affix_state::inject(Arc::new(Mutex::new(Authenticator::new(config.auth.mode.as_str()))))) // add auth to state
+        .hoop(affix_state::inject(db))
+        .hoop(affix_state::inject(limits))
+        .hoop(affix_state::inject(reaction_kinds))
+        .hoop(affix_state::inject(site))
+        .hoop(affix_state::inject(media_limits))
+        .hoop(affix_state::inject(media_store))
+        .hoop(affix_state::inject(authenticator)) // add auth to state
         // TODO preface all of these with /v0/ before pushing to production for the first time
         .push(Router::with_path("hello").get(handlers::misc::hello::hello))
-        .push(Router::with_path("posts").get(handlers::posts::get::many))
-        .push(Router::with_path("posts/{id}").get(handlers::posts::get::one))
+        .push(
+            // logged-in users can see (and count the Tags of) more Posts than anonymous users -- see
+            // handlers/posts.rs
+            Router::new()
+                .hoop(Auth::optional())
+                .push(Router::with_path("posts").get(handlers::posts::get::many))
+                .push(Router::with_path("posts:search").get(handlers::posts::search::search))
+                .push(Router::with_path("posts/{id}").get(handlers::posts::get::one))
+                // before posts/{id}/..., so that slugs like "revisions" are not mistaken for those
+                .push(Router::with_path("posts/by-slug/{slug}").get(handlers::posts::get::by_slug))
+                .push(Router::with_path("posts/{id}/comments").get(handlers::posts::comments::get::many))
+                .push(Router::with_path("tags").get(handlers::tags::get::many))
+        )
+        .push(
+            // "me" is matched before "{id}", which would otherwise try (and fail) to parse it
+            Router::with_path("users/me")
+                .hoop(Auth::new(&["user"]))
+                .get(handlers::users::get::me)
+                .patch(handlers::users::patch::me)
+        )
+        .push(Router::with_path("users/{id}").get(handlers::users::get::one))
+        .push(Router::with_path("feeds/atom.xml").get(handlers::feeds::atom::all))
+        .push(Router::with_path("feeds/rss.xml").get(handlers::feeds::rss::all))
+        .push(Router::with_path("authors/{author_id}/feeds/atom.xml").get(handlers::feeds::atom::author))
+        .push(Router::with_path("sitemap.xml").get(handlers::sitemap::index))
+        .push(Router::with_path("sitemaps/{file}").get(handlers::sitemap::numbered))
+        .push(Router::with_path("media/{id}").get(handlers::media::get::one))
         .push(Router::with_path("health").get(handlers::health::check))
         ;
 
+    // login flows
+    let login_router = {
+        let router = Router::new()
+            .push(Router::with_path("login").post(handlers::login::username_and_password::login))
+            .push(Router::with_path("logout").post(handlers::logout::logout))
+            .push(Router::with_path("token/refresh").post(handlers::token::refresh::refresh));
+
+        // TODO parse auth.mode string to an AuthMode _once_, above, and panic up there instead of down here
+        match config.auth.mode.as_str() {
+            "keycloak" => router
+                .push(Router::with_path("login-keycloak").get(handlers::login::keycloak_token::login)),
+            "in-memory" => router,
+            _ => panic!("unsupported authentication mode: {}", config.auth.mode),
+        }
+    };
+
+    let private_router = Router::new()
+        .push(
+            Router::with_path("posts")
+                .hoop(Auth::new(&["user"]))
+                .post(handlers::posts::post::many)
+                .put(handlers::posts::put::many)
+                .patch(handlers::posts::patch::many)
+                .delete(handlers::posts::delete::many)
+        )
+        .push(
+            // only the author of a Post, or an admin, can modify it -- see handlers/posts.rs
+            Router::with_path("posts/{id}")
+                .hoop(Auth::new(&["user"]))
+                .put(handlers::posts::put::one)
+                .patch(handlers::posts::patch::one)
+                .delete(handlers::posts::delete::one)
+        )
+        .push(
+            Router::with_path("posts/{id}:publish")
+                .hoop(Auth::new(&["user"]))
+                .post(handlers::posts::publish::publish)
+        )
+        .push(
+            Router::with_path("posts/{id}:unpublish")
+                .hoop(Auth::new(&["user"]))
+                .post(handlers::posts::publish::unpublish)
+        )
+        .push(
+            Router::with_path("posts/{id}:schedule")
+                .hoop(Auth::new(&["user"]))
+                .post(handlers::posts::schedule::schedule)
+        )
+        .push(
+            // only the author of a Post, or an admin, can see its revisions
+            Router::with_path("posts/{id}/revisions")
+                .hoop(Auth::new(&["user"]))
+                .get(handlers::posts::revisions::get::many)
+                .push(Router::with_path("{n}").get(handlers::posts::revisions::get::one))
+                .push(Router::with_path("{n}:restore").post(handlers::posts::revisions::restore::restore))
+        )
+        .push(
+            // users can only add and remove their own reactions
+            Router::with_path("posts/{id}/reactions/{kind}")
+                .hoop(Auth::new(&["user"]))
+                .put(handlers::posts::reactions::put::one)
+                .delete(handlers::posts::reactions::delete::one)
+        )
+        .push(
+            // anyone who can see a Post can comment on it, but only the author of a Comment,
+            // the author of its Post, or an admin, can delete it -- see handlers/posts/comments
+            Router::with_path("posts/{id}/comments")
+                .hoop(Auth::new(&["user"]))
+                .post(handlers::posts::comments::post::one)
+                .push(Router::with_path("{comment_id}").delete(handlers::posts::comments::delete::one))
+        )
+        .push(
+            // users follow and unfollow authors as themselves
+            Router::with_path("authors/{author_id}/follow")
+                .hoop(Auth::new(&["user"]))
+                .put(handlers::authors::follow::follow)
+                .delete(handlers::authors::follow::unfollow)
+        )
+        .push(
+            Router::with_path("feed")
+                .hoop(Auth::new(&["user"]))
+                .get(handlers::feed::get::many)
+        )
+        .push(
+            Router::with_path("media")
+                .hoop(Auth::new(&["user"]))
+                .post(handlers::media::post::one)
+        )
+        .push(
+            // metrics say how busy the server is, so only admins can see them
+            Router::with_path("metrics")
+                .hoop(Auth::new(&["admin"]))
+                .get(handlers::metrics::metrics)
+        )
+        .push(
+            // this is an admin-only route
+            Router::with_path("/admin-only")
+                .hoop(Auth::new(&["admin"]))
+                .get(handlers::misc::admin_only::admin_only)
+        )
+        .push(
+            // this is a user-only route
+            Router::with_path("/user-only")
+                .hoop(Auth::new(&["user"]))
+                .get(handlers::misc::user_only::user_only)
+        );
+
     log::debug!("created router");
 
+    // lets the Swagger UI "Authorize" button send a token with every request. The empty requirement
+    // means that a token is optional -- Auth decides which routes need one
+    let bearer = SecurityScheme::Http(Http::new(HttpAuthScheme::Bearer).description("a token from POST /login"));
+
     // TODO consider replacing env!("CARGO_PKG_VERSION") with clap's crate_version macro
-    let doc = OpenApi::new("test api", env!("CARGO_PKG_VERSION")).merge_router(&public_router);
+    let doc = OpenApi::new("test api", env!("CARGO_PKG_VERSION"))
+        .add_security_scheme("bearer", bearer)
+        .security([SecurityRequirement::new("bearer", Vec::<String>::new()), SecurityRequirement::default()])
+        .merge_router(&public_router)
+        .merge_router(&login_router)
+        .merge_router(&private_router);
 
     let public_router_with_openapi = public_router
         .push(doc.into_router("/api-doc/openapi.json"))
@@ -164,41 +425,39 @@ async fn main() {
     let catcher = Catcher::default().hoop(handlers::misc::not_found::not_found);
 
     log::debug!("added OpenAPI docs UI to router");
-    Server::new(acceptor).serve(
+
+    let server = Server::new(acceptor);
+    let handle = server.handle();
+
+    // stop accepting new connections, and finish handling in-flight requests, before shutting down
+    tokio::spawn(async move {
+        shutdown_signal().await;
+        log::info!("shutting down subway-backend...");
+        // TODO (config) move this timeout to config
+        handle.stop_graceful(Duration::from_secs(30));
+    });
+
+    server.serve(
         Service::new(
             public_router_with_openapi
                 .hoop(cors) // Apply the CORS middleware globally
-                .push({ // login flows
-
-                    let router = Router::new()
-                        .push(Router::with_path("login").post(handlers::login::username_and_password::login));
-
-                    // TODO parse auth.mode string to an AuthMode _once_, above, and panic up there instead of down here
-                    match config.auth.mode.as_str() {
-                        "keycloak" => router
-                            .push(Router::with_path("login-keycloak").get(handlers::login::keycloak_token::login)),
-                        "in-memory" => router,
-                        _ => panic!("unsupported authentication mode: {}", config.auth.mode),
-                    }
-
-                })
-                .push(
-                    Router::with_path("posts")
-                        .hoop(Auth::new(&["user"]))
-                        .post(handlers::posts::post::many)
-                )
-                .push(
-                    // this is an admin-only route
-                    Router::with_path("/admin-only")
-                        .hoop(Auth::new(&["admin"]))
-                        .get(handlers::misc::admin_only::admin_only)
-                )
-                .push(
-                    // this is a user-only route
-                    Router::with_path("/user-only")
-                        .hoop(Auth::new(&["user"]))
-                        .get(handlers::misc::user_only::user_only)
-                )
-        ).catcher(catcher)
+                .push(login_router)
+                .push(private_router)
+        )
+            // give every request (even unmatched ones) an id, which is included in error responses
+            .hoop(RequestId::new().header_name(HeaderName::from_static(REQUEST_ID_HEADER)))
+            .catcher(catcher)
     ).await;
+
+    let _ = shutdown_sender.send(true);
+
+    if let Err(e) = scheduler.await {
+        log::error!("scheduler did not stop cleanly: {}", e);
+    }
+
+    if let Err(e) = sweeper.await {
+        log::error!("session sweeper did not stop cleanly: {}", e);
+    }
+
+    log::info!("stopped subway-backend");
 }
\ No newline at end of file
@@ -0,0 +1,40 @@
+use std::fmt::{Display, Formatter};
+use uuid::Uuid;
+
+pub(crate) mod in_memory;
+pub(crate) mod local;
+
+/// Everything that can go wrong when reading or writing the contents of uploaded files.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum MediaError {
+    /// There is no file with the requested id.
+    NotFound(String),
+
+    /// The file could not be read or written, e.g. because the disk is full.
+    Unavailable(String),
+}
+
+impl Display for MediaError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MediaError::NotFound(message) => write!(f, "not found: {}", message),
+            MediaError::Unavailable(message) => write!(f, "media store unavailable: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for MediaError {}
+
+/// Stores the contents of uploaded files (see model::media::Media), by id. What is known about
+/// each file (who uploaded it, what type it is...) is stored in the database, in the media table.
+///
+/// Like the Database, every store sits behind this trait, so that stores can be swapped out by
+/// config. See ADR-005.
+pub(crate) trait MediaStoreLike: Sync + Send {
+    /// Stores the contents of a file. Files are never changed once they are stored.
+    fn put(&self, media_id: &Uuid, bytes: &[u8]) -> Result<(), MediaError>;
+
+    fn get(&self, media_id: &Uuid) -> Result<Vec<u8>, MediaError>;
+
+    fn delete(&self, media_id: &Uuid) -> Result<(), MediaError>;
+}
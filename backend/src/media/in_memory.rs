@@ -0,0 +1,38 @@
+use crate::media::{MediaError, MediaStoreLike};
+use std::collections::HashMap;
+use std::sync::RwLock;
+use uuid::Uuid;
+
+/// Keeps files in memory, so they are lost when the server stops. Useful for tests.
+pub(crate) struct Impl {
+    files: RwLock<HashMap<Uuid, Vec<u8>>>,
+}
+
+impl Impl {
+    pub(crate) fn new() -> Self {
+        Self { files: RwLock::new(HashMap::new()) }
+    }
+}
+
+fn poisoned<T>(_: T) -> MediaError {
+    MediaError::Unavailable(String::from("lock poisoned"))
+}
+
+impl MediaStoreLike for Impl {
+    fn put(&self, media_id: &Uuid, bytes: &[u8]) -> Result<(), MediaError> {
+        self.files.write().map_err(poisoned)?.insert(*media_id, bytes.to_vec());
+        Ok(())
+    }
+
+    fn get(&self, media_id: &Uuid) -> Result<Vec<u8>, MediaError> {
+        self.files.read().map_err(poisoned)?.get(media_id).cloned()
+            .ok_or_else(|| MediaError::NotFound(format!("no file with id {}", media_id)))
+    }
+
+    fn delete(&self, media_id: &Uuid) -> Result<(), MediaError> {
+        match self.files.write().map_err(poisoned)?.remove(media_id) {
+            Some(_) => Ok(()),
+            None => Err(MediaError::NotFound(format!("no file with id {}", media_id))),
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use crate::media::{MediaError, MediaStoreLike};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+/// Keeps each file on the local disk, in one directory, named by its id.
+pub(crate) struct Impl {
+    directory: PathBuf,
+}
+
+impl Impl {
+    /// Creates the directory, if it does not exist yet.
+    pub(crate) fn new(directory: &str) -> Result<Self, String> {
+        fs::create_dir_all(directory)
+            .map_err(|e| format!("unable to create media directory {}: {}", directory, e))?;
+
+        Ok(Self { directory: PathBuf::from(directory) })
+    }
+
+    fn path(&self, media_id: &Uuid) -> PathBuf {
+        self.directory.join(media_id.to_string())
+    }
+}
+
+fn to_media_error(media_id: &Uuid, e: std::io::Error) -> MediaError {
+    match e.kind() {
+        ErrorKind::NotFound => MediaError::NotFound(format!("no file with id {}", media_id)),
+        _ => MediaError::Unavailable(format!("file with id {}: {}", media_id, e)),
+    }
+}
+
+impl MediaStoreLike for Impl {
+    fn put(&self, media_id: &Uuid, bytes: &[u8]) -> Result<(), MediaError> {
+        // write to a temporary file first, so that a half-written file is never served
+        let temporary = self.directory.join(format!("{}.partial", media_id));
+
+        fs::write(&temporary, bytes)
+            .and_then(|_| fs::rename(&temporary, self.path(media_id)))
+            .map_err(|e| to_media_error(media_id, e))
+    }
+
+    fn get(&self, media_id: &Uuid) -> Result<Vec<u8>, MediaError> {
+        fs::read(self.path(media_id)).map_err(|e| to_media_error(media_id, e))
+    }
+
+    fn delete(&self, media_id: &Uuid) -> Result<(), MediaError> {
+        fs::remove_file(self.path(media_id)).map_err(|e| to_media_error(media_id, e))
+    }
+}
@@ -1 +1,8 @@
-pub(crate) mod post;
\ No newline at end of file
+pub(crate) mod comment;
+pub(crate) mod format;
+pub(crate) mod media;
+pub(crate) mod post;
+pub(crate) mod reaction;
+pub(crate) mod site;
+pub(crate) mod tag;
+pub(crate) mod user;
\ No newline at end of file
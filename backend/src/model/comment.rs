@@ -0,0 +1,36 @@
+use crate::model::post::{now, AuthorId, Body, PostId};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+pub(crate) struct CommentId(pub(crate) Uuid);
+
+// We use newtypes here, so that the ids of Comments, their Posts and their authors cannot be
+// swapped accidentally. Comment bodies are validated just like Post bodies (see Body::parse()).
+pub(crate) struct Comment {
+    pub(crate) comment_id: CommentId,
+    pub(crate) post_id: PostId,
+    pub(crate) author_id: AuthorId,
+    pub(crate) parent_id: Option<CommentId>, // the Comment this is a reply to, if any
+    pub(crate) body: Body,
+    pub(crate) created_at: DateTime<Utc>,
+}
+
+// We add a new() function to avoid exposing the 'id' field to mutation
+impl Comment {
+    pub(crate) fn new(
+        post_id: PostId,
+        author_id: AuthorId,
+        parent_id: Option<CommentId>,
+        body: Body,
+    ) -> Self {
+        Self {
+            // version 7 UUIDs start with a timestamp, so Comments sorted by id are sorted by age
+            comment_id: CommentId(Uuid::now_v7()),
+            post_id,
+            author_id,
+            parent_id,
+            body,
+            created_at: now(),
+        }
+    }
+}
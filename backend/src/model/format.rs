@@ -0,0 +1,70 @@
+use crate::model::post::ValidationError;
+use pulldown_cmark::{html, Options, Parser};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// How the body of a Post is written, and so how it is rendered as HTML.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum Format {
+    /// The body is shown exactly as it was written. Blank lines separate paragraphs.
+    #[default]
+    Plain,
+
+    /// The body is CommonMark. See https://commonmark.org/
+    Markdown,
+}
+
+impl Display for Format {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Format::Plain => write!(f, "plain"),
+            Format::Markdown => write!(f, "markdown"),
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "plain" => Ok(Format::Plain),
+            "markdown" => Ok(Format::Markdown),
+            _ => Err(format!("unknown Post format: {}", s)),
+        }
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;").replace('\'', "&#39;")
+}
+
+impl Format {
+    /// Validates a format chosen by a user.
+    pub(crate) fn parse(raw: &str) -> Result<Self, ValidationError> {
+        raw.parse().map_err(|_| ValidationError {
+            field: "format",
+            message: format!("must be one of \"{}\" or \"{}\", but is \"{}\"", Format::Plain, Format::Markdown, raw),
+        })
+    }
+
+    /// Renders a body written in this format as HTML which is safe to show in a browser.
+    ///
+    /// Markdown may contain raw HTML, so whatever CommonMark renders is passed through ammonia,
+    /// which keeps only allowlisted tags and attributes. Scripts, styles, event handlers (like
+    /// onclick) and javascript: URLs never make it through. Plain text is simply escaped.
+    pub(crate) fn render(&self, body: &str) -> String {
+        match self {
+            // this must agree with the backfill in the migration which added posts_by_id.body_html
+            Format::Plain => {
+                let escaped = escape_html(&body.replace("\r\n", "\n"));
+                format!("<p>{}</p>", escaped.replace("\n\n", "</p><p>").replace('\n', "<br>"))
+            }
+            Format::Markdown => {
+                let mut unsafe_html = String::new();
+                html::push_html(&mut unsafe_html, Parser::new_ext(body, Options::empty()));
+                ammonia::clean(&unsafe_html)
+            }
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use crate::model::post::now;
+use chrono::{DateTime, Utc};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// The largest file which users may upload, in bytes. See config.toml.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Limits {
+    pub(crate) max_size_bytes: usize,
+}
+
+pub(crate) struct MediaId(pub(crate) Uuid);
+pub(crate) struct UploaderId(pub(crate) Uuid);
+
+/// The kinds of file which can be uploaded. Only images, for now.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum MediaType {
+    Png,
+    Jpeg,
+    Gif,
+    Webp,
+}
+
+impl Display for MediaType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MediaType::Png => write!(f, "image/png"),
+            MediaType::Jpeg => write!(f, "image/jpeg"),
+            MediaType::Gif => write!(f, "image/gif"),
+            MediaType::Webp => write!(f, "image/webp"),
+        }
+    }
+}
+
+impl FromStr for MediaType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "image/png" => Ok(MediaType::Png),
+            "image/jpeg" => Ok(MediaType::Jpeg),
+            "image/gif" => Ok(MediaType::Gif),
+            "image/webp" => Ok(MediaType::Webp),
+            _ => Err(format!("unknown media type: {}", s)),
+        }
+    }
+}
+
+impl MediaType {
+    /// Works out what kind of file this is from its first few bytes (its "magic number"), rather
+    /// than trusting the type or the file name given by the uploader. Returns None if the file is
+    /// not one of the kinds which can be uploaded.
+    pub(crate) fn sniff(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, ..] => Some(MediaType::Png),
+            [0xFF, 0xD8, 0xFF, ..] => Some(MediaType::Jpeg),
+            [b'G', b'I', b'F', b'8', b'7' | b'9', b'a', ..] => Some(MediaType::Gif),
+            [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => Some(MediaType::Webp),
+            _ => None,
+        }
+    }
+}
+
+/// An uploaded file, e.g. an image to show in a Post. Its contents are kept in a media store (see
+/// media.rs), and never change.
+pub(crate) struct Media {
+    pub(crate) media_id: MediaId,
+    pub(crate) uploader_id: UploaderId,
+    pub(crate) media_type: MediaType,
+    pub(crate) size_bytes: i64,
+    pub(crate) created_at: DateTime<Utc>,
+}
+
+// We add a new() function to avoid exposing the 'id' field to mutation
+impl Media {
+    pub(crate) fn new(uploader_id: UploaderId, media_type: MediaType, size_bytes: usize) -> Self {
+        Self {
+            media_id: MediaId(Uuid::new_v4()),
+            uploader_id,
+            media_type,
+            size_bytes: i64::try_from(size_bytes).unwrap_or(i64::MAX),
+            created_at: now(),
+        }
+    }
+}
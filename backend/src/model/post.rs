@@ -1,9 +1,147 @@
+use crate::model::format::Format;
+use chrono::{DateTime, SubsecRound, Utc};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
 use uuid::Uuid;
 
 pub(crate) struct PostId(pub(crate) Uuid);
 pub(crate) struct AuthorId(pub(crate) Uuid);
 pub(crate) struct Title(pub(crate) String);
 pub(crate) struct Body(pub(crate) String);
+pub(crate) struct Slug(pub(crate) String);
+
+/// The longest slug, in characters, before any suffix is added to make it unique.
+const SLUG_MAX_LENGTH: usize = 80;
+
+/// The longest titles and bodies which users may write, in characters. See config.toml.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Limits {
+    pub(crate) title_max_length: usize,
+    pub(crate) body_max_length: usize,
+}
+
+/// Why the value of one field of a Post was rejected.
+#[derive(Debug)]
+pub(crate) struct ValidationError {
+    pub(crate) field: &'static str,
+    pub(crate) message: String,
+}
+
+/// Trims whitespace from user-provided text, then checks that it is not empty, not too long, and
+/// does not contain any control characters (other than those allowed).
+pub(crate) fn validate(field: &'static str, raw: String, max_length: usize, allowed: &[char]) -> Result<String, ValidationError> {
+    let invalid = |message: String| ValidationError { field, message };
+    let trimmed = raw.trim();
+
+    if trimmed.is_empty() {
+        return Err(invalid(String::from("must not be empty")));
+    }
+
+    let length = trimmed.chars().count();
+
+    if length > max_length {
+        return Err(invalid(format!("must be at most {} characters long, but is {}", max_length, length)));
+    }
+
+    if trimmed.chars().any(|c| c.is_control() && !allowed.contains(&c)) {
+        return Err(invalid(String::from("must not contain control characters")));
+    }
+
+    Ok(trimmed.to_string())
+}
+
+impl Title {
+    /// Validates a title written by a user. Titles must fit on one line.
+    pub(crate) fn parse(raw: String, limits: &Limits) -> Result<Self, ValidationError> {
+        validate("title", raw, limits.title_max_length, &[]).map(Title)
+    }
+}
+
+impl Body {
+    /// Validates a body written by a user. Bodies may contain line breaks and tabs.
+    pub(crate) fn parse(raw: String, limits: &Limits) -> Result<Self, ValidationError> {
+        validate("body", raw, limits.body_max_length, &['\n', '\r', '\t']).map(Body)
+    }
+}
+
+impl Slug {
+    /// Turns a title into a URL-safe slug, e.g. "Hello, World!" becomes "hello-world". Slugs only
+    /// contain lowercase ASCII letters and digits, separated by single hyphens. Titles without any
+    /// of those (e.g. "???") become "post".
+    ///
+    /// This must agree with the backfill in the migration which created posts_by_slug.
+    pub(crate) fn from_title(title: &Title) -> Self {
+        let slug = title.0.to_lowercase()
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .collect::<Vec<_>>()
+            .join("-");
+
+        let slug: String = slug.chars().take(SLUG_MAX_LENGTH).collect();
+        let slug = slug.trim_end_matches('-');
+
+        if slug.is_empty() {
+            Slug(String::from("post"))
+        } else {
+            Slug(slug.to_string())
+        }
+    }
+
+    /// Every slug which a Post with this slug could be given, in order of preference: the slug
+    /// itself, then the slug with a numeric suffix, e.g. "hello-world", "hello-world-2", ...
+    pub(crate) fn candidates(&self) -> impl Iterator<Item = String> + '_ {
+        std::iter::once(self.0.clone()).chain((2..).map(|n| format!("{}-{}", self.0, n)))
+    }
+}
+
+/// Where a Post is in its lifecycle. Only published Posts are visible to everyone.
+///
+///   Draft --publish--> Published --unpublish--> Archived
+///                          ^                       |
+///                          +--------publish--------+
+///
+/// Drafts and archived Posts can also be scheduled, so that they are published automatically at
+/// some later time. See scheduler.rs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Status {
+    /// The Post has never been published. Only its author (and admins) can see it.
+    Draft,
+
+    /// The Post is visible to everyone.
+    Published,
+
+    /// The Post was published, then unpublished. Only its author (and admins) can see it.
+    Archived,
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Status::Draft => write!(f, "draft"),
+            Status::Published => write!(f, "published"),
+            Status::Archived => write!(f, "archived"),
+        }
+    }
+}
+
+impl FromStr for Status {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "draft" => Ok(Status::Draft),
+            "published" => Ok(Status::Published),
+            "archived" => Ok(Status::Archived),
+            _ => Err(format!("unknown Post status: {}", s)),
+        }
+    }
+}
+
+// Postgres only stores timestamps to the microsecond, so we truncate them here, too. Otherwise, the
+// in-memory database would keep more precision than Postgres, and compare timestamps differently.
+pub(crate) fn now() -> DateTime<Utc> {
+    Utc::now().trunc_subsecs(6)
+}
 
 // We use newtypes here, so that a UUID post_id and a UUID author_id cannot be swapped accidentally
 pub(crate) struct Post {
@@ -11,25 +149,260 @@ pub(crate) struct Post {
     pub(crate) author_id: AuthorId,
     pub(crate) title: Title,
     pub(crate) body: Body,
+    pub(crate) created_at: DateTime<Utc>,
+    pub(crate) updated_at: DateTime<Utc>,
+    pub(crate) status: Status,
+    pub(crate) published_at: Option<DateTime<Utc>>, // when the Post was first published
+    pub(crate) publish_at: Option<DateTime<Utc>>, // when the Post is scheduled to be published
+    pub(crate) revision: i32, // how many times the title, body and / or format have been set
+    pub(crate) read_revision: i32, // the revision when this Post was read from the database (0 if it never was)
+    pub(crate) format: Format,
+    pub(crate) body_html: String, // the body rendered in its format, cached so it isn't rendered on every read
+    pub(crate) slug: Slug, // made unique when the Post is saved -- see PostsBySlugTableLike
+    pub(crate) version: i32, // how many times the Post has been written, by any change at all
 }
 
-// We add a new() function to avoid
-//  - exposing the 'id' field to mutation
-//  - users creating 'title's of unbounded length
+/// The title, body and format of a Post, as they were after one edit. Revisions are numbered from 1, and
+/// the latest Revision is always the same as the Post itself.
+pub(crate) struct Revision {
+    pub(crate) post_id: PostId,
+    pub(crate) revision: i32,
+    pub(crate) title: Title,
+    pub(crate) body: Body,
+    pub(crate) created_at: DateTime<Utc>,
+    pub(crate) format: Format,
+}
+
+// We add a new() function to avoid exposing the 'id' field to mutation. Titles and bodies written
+// by users should be validated with Title::parse() and Body::parse() before they get here.
 impl Post {
     pub(crate) fn new(
         author_id: AuthorId,
         title: Title,
         body: Body,
+        format: Format,
     ) -> Self {
-
-        // TODO add title validation
+        let now = now();
+        let body_html = format.render(&body.0);
+        let slug = Slug::from_title(&title);
 
         Self {
-            post_id: PostId(Uuid::new_v4()),
+            // version 7 UUIDs start with a timestamp, so Posts listed by id are listed oldest first
+            // (apart from Posts created before they were used, which have random version 4 ids)
+            post_id: PostId(Uuid::now_v7()),
             author_id,
             title,
             body,
+            created_at: now,
+            updated_at: now,
+            status: Status::Draft,
+            published_at: None,
+            publish_at: None,
+            revision: 1,
+            read_revision: 0,
+            format,
+            body_html,
+            slug,
+            version: 1,
+        }
+    }
+
+    /// Replaces the title, the body and / or the format of this Post, and records when that
+    /// happened. Every edit which changes something creates a new Revision -- an edit which changes
+    /// nothing does nothing. Changing the title also changes the slug, but the old slug still leads
+    /// to this Post.
+    pub(crate) fn edit(&mut self, title: Option<Title>, body: Option<Body>, format: Option<Format>) {
+        let title = title.filter(|title| title.0 != self.title.0);
+        let body = body.filter(|body| body.0 != self.body.0);
+        let format = format.filter(|format| *format != self.format);
+
+        if title.is_none() && body.is_none() && format.is_none() {
+            return;
         }
+
+        if let Some(title) = title {
+            self.slug = Slug::from_title(&title);
+            self.title = title;
+        }
+
+        let rerender = body.is_some() || format.is_some();
+
+        if let Some(body) = body {
+            self.body = body;
+        }
+
+        if let Some(format) = format {
+            self.format = format;
+        }
+
+        if rerender {
+            self.body_html = self.format.render(&self.body.0);
+        }
+
+        self.revision += 1;
+        self.updated_at = now();
+    }
+
+    /// Replaces the title, body and format of this Post with those from an earlier Revision. This
+    /// creates a new Revision (unless nothing changes), so restoring can itself be undone.
+    pub(crate) fn restore(&mut self, revision: Revision) {
+        self.edit(Some(revision.title), Some(revision.body), Some(revision.format));
+    }
+
+    /// The latest Revision of this Post.
+    pub(crate) fn latest_revision(&self) -> Revision {
+        Revision {
+            post_id: PostId(self.post_id.0),
+            revision: self.revision,
+            title: Title(self.title.0.clone()),
+            body: Body(self.body.0.clone()),
+            created_at: self.updated_at,
+            format: self.format,
+        }
+    }
+
+    /// Makes this Post visible to everyone, cancelling any schedule. Publishing a published Post
+    /// does nothing.
+    pub(crate) fn publish(&mut self) {
+        if self.status != Status::Published {
+            let now = now();
+            self.status = Status::Published;
+            self.published_at = self.published_at.or(Some(now));
+            self.publish_at = None;
+            self.updated_at = now;
+        }
+    }
+
+    /// Schedules this Post to be published at the given time, or cancels the schedule if there is
+    /// no time. A published Post cannot be scheduled.
+    pub(crate) fn schedule(&mut self, publish_at: Option<DateTime<Utc>>) -> Result<(), String> {
+        if self.status == Status::Published {
+            return Err(String::from("a published Post cannot be scheduled"));
+        }
+
+        self.publish_at = publish_at.map(|at| at.trunc_subsecs(6));
+        self.updated_at = now();
+        Ok(())
+    }
+
+    /// Publishes this Post if it was scheduled to be published at or before `now`. Returns whether
+    /// the Post was published.
+    pub(crate) fn publish_if_due(&mut self, now: DateTime<Utc>) -> bool {
+        match self.publish_at {
+            Some(publish_at) if publish_at <= now && self.status != Status::Published => {
+                self.status = Status::Published;
+                // the Post was due to go live at publish_at, even if the scheduler was late
+                self.published_at = self.published_at.or(Some(publish_at));
+                self.publish_at = None;
+                self.updated_at = now;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Hides this Post from everyone except its author (and admins). Unpublishing an archived Post
+    /// does nothing, but a draft cannot be unpublished, because it was never published.
+    pub(crate) fn unpublish(&mut self) -> Result<(), String> {
+        match self.status {
+            Status::Draft => Err(String::from("a draft cannot be unpublished, because it was never published")),
+            Status::Archived => Ok(()),
+            Status::Published => {
+                self.status = Status::Archived;
+                self.updated_at = now();
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AuthorId, Body, Post, Status, Title};
+    use crate::model::format::Format;
+    use chrono::{Duration, Utc};
+    use uuid::Uuid;
+
+    fn post(title: &str, body: &str) -> Post {
+        Post::new(AuthorId(Uuid::now_v7()), Title(String::from(title)), Body(String::from(body)), Format::Plain)
+    }
+
+    #[test]
+    fn only_edits_which_change_something_create_revisions() {
+        let mut post = post("Hello World", "hello");
+        assert_eq!(post.revision, 1);
+
+        post.edit(Some(Title(String::from("Hello World"))), Some(Body(String::from("hello"))), Some(Format::Plain));
+        assert_eq!(post.revision, 1);
+
+        post.edit(Some(Title(String::from("Goodbye World"))), None, None);
+        assert_eq!(post.revision, 2);
+        assert_eq!(post.slug.0, "goodbye-world");
+        assert_eq!(post.body.0, "hello");
+
+        post.edit(None, None, Some(Format::Markdown));
+        assert_eq!(post.revision, 3);
+        assert_eq!(post.format, Format::Markdown);
+    }
+
+    #[test]
+    fn restoring_a_revision_creates_a_new_revision() {
+        let mut post = post("Hello World", "hello");
+        let first = post.latest_revision();
+
+        post.edit(Some(Title(String::from("Goodbye World"))), Some(Body(String::from("goodbye"))), Some(Format::Markdown));
+        post.restore(first);
+
+        assert_eq!(post.revision, 3);
+        assert_eq!(post.title.0, "Hello World");
+        assert_eq!(post.body.0, "hello");
+        assert_eq!(post.format, Format::Plain);
+        assert_eq!(post.body_html, Format::Plain.render("hello"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn publishing_cancels_the_schedule_and_keeps_when_the_post_was_first_published() {
+        let mut post = post("Hello World", "hello");
+        post.schedule(Some(Utc::now() + Duration::days(1))).unwrap();
+
+        post.publish();
+        let published_at = post.published_at;
+        assert_eq!(post.status, Status::Published);
+        assert!(published_at.is_some());
+        assert_eq!(post.publish_at, None);
+        assert!(post.schedule(Some(Utc::now())).is_err());
+
+        post.unpublish().unwrap();
+        assert_eq!(post.status, Status::Archived);
+        post.publish();
+        assert_eq!(post.published_at, published_at);
+
+        // publishing does not change the title, body or format, so it is not a new Revision
+        assert_eq!(post.revision, 1);
+    }
+
+    #[test]
+    fn drafts_cannot_be_unpublished() {
+        let mut post = post("Hello World", "hello");
+        assert!(post.unpublish().is_err());
+        assert_eq!(post.status, Status::Draft);
+    }
+
+    #[test]
+    fn scheduled_posts_are_only_published_once_they_are_due() {
+        let mut post = post("Hello World", "hello");
+        let publish_at = super::now() + Duration::hours(1);
+        post.schedule(Some(publish_at)).unwrap();
+
+        assert!(!post.publish_if_due(publish_at - Duration::seconds(1)));
+        assert_eq!(post.status, Status::Draft);
+
+        // a late scheduler still publishes the Post as of when it was due
+        assert!(post.publish_if_due(publish_at + Duration::minutes(5)));
+        assert_eq!(post.status, Status::Published);
+        assert_eq!(post.published_at, Some(publish_at));
+        assert_eq!(post.publish_at, None);
+
+        assert!(!post.publish_if_due(publish_at + Duration::minutes(10)));
+    }
+}
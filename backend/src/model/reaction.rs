@@ -0,0 +1,53 @@
+use crate::model::post::{now, PostId};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// Every user can always "like" a Post. Other kinds of reaction are configured in config.toml.
+const LIKE: &str = "like";
+
+/// One kind of reaction, like "like" or "heart". Each user can add at most one reaction of each
+/// kind to each Post.
+pub(crate) struct ReactionKind(pub(crate) String);
+
+/// The kinds of reaction which users may add to Posts.
+#[derive(Clone, Debug)]
+pub(crate) struct ReactionKinds(Vec<String>);
+
+impl ReactionKinds {
+    /// Always includes "like", plus any other configured kinds. Kinds are compared ignoring case.
+    pub(crate) fn new(configured: &[String]) -> Self {
+        let mut kinds = vec![String::from(LIKE)];
+
+        for kind in configured.iter().map(|kind| kind.trim().to_lowercase()) {
+            if !kind.is_empty() && !kinds.contains(&kind) {
+                kinds.push(kind);
+            }
+        }
+
+        Self(kinds)
+    }
+
+    /// Returns the matching kind of reaction, or None if there is no such kind.
+    pub(crate) fn parse(&self, raw: &str) -> Option<ReactionKind> {
+        let raw = raw.to_lowercase();
+        self.0.iter().find(|kind| **kind == raw).map(|kind| ReactionKind(kind.clone()))
+    }
+
+    pub(crate) fn all(&self) -> &[String] {
+        &self.0
+    }
+}
+
+/// One user's reaction to one Post.
+pub(crate) struct Reaction {
+    pub(crate) post_id: PostId,
+    pub(crate) user_id: Uuid,
+    pub(crate) kind: ReactionKind,
+    pub(crate) created_at: DateTime<Utc>,
+}
+
+impl Reaction {
+    pub(crate) fn new(post_id: PostId, user_id: Uuid, kind: ReactionKind) -> Self {
+        Self { post_id, user_id, kind, created_at: now() }
+    }
+}
\ No newline at end of file
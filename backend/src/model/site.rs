@@ -0,0 +1,17 @@
+/// The blog, as readers see it. Feeds and sitemaps link readers back to the Site.
+#[derive(Clone, Debug)]
+pub(crate) struct Site {
+    pub(crate) title: String,
+    pub(crate) url: String, // without a trailing slash
+}
+
+impl Site {
+    pub(crate) fn new(title: &str, url: &str) -> Self {
+        Self { title: title.to_string(), url: url.trim_end_matches('/').to_string() }
+    }
+
+    /// Where readers can read a Post, by its slug. See model::post::Slug.
+    pub(crate) fn post_url(&self, slug: &str) -> String {
+        format!("{}/posts/{}", self.url, slug)
+    }
+}
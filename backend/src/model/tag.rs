@@ -0,0 +1,62 @@
+use crate::model::post::ValidationError;
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+
+/// The longest tag, in characters, after it has been normalized.
+const MAX_LENGTH: usize = 50;
+
+/// The most tags which one Post can have.
+const MAX_TAGS_PER_POST: usize = 20;
+
+/// A label which groups related Posts together. Tags are always slugs: lowercase letters and
+/// digits, separated by single hyphens, like "rust-programming".
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct Tag(pub(crate) String);
+
+impl Tag {
+    /// Normalizes a tag written by a user into a slug, e.g. "  Rust Programming! " becomes
+    /// "rust-programming". Two tags which normalize to the same slug are the same tag.
+    pub(crate) fn parse(raw: &str) -> Result<Self, ValidationError> {
+        let invalid = |message: String| ValidationError { field: "tags", message };
+
+        let slug = raw.to_lowercase()
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .collect::<Vec<_>>()
+            .join("-");
+
+        if slug.is_empty() {
+            return Err(invalid(format!("contains \"{}\", which has no letters or digits", raw)));
+        }
+
+        let length = slug.chars().count();
+
+        if length > MAX_LENGTH {
+            return Err(invalid(format!("contains \"{}\", which is longer than {} characters", slug, MAX_LENGTH)));
+        }
+
+        Ok(Tag(slug))
+    }
+}
+
+impl Display for Tag {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+
+/// Normalizes every tag written by a user for one Post. Tags which normalize to the same slug are
+/// only kept once.
+pub(crate) fn parse_all(raw: &[String]) -> Result<BTreeSet<Tag>, ValidationError> {
+    let tags = raw.iter().map(|tag| Tag::parse(tag)).collect::<Result<BTreeSet<_>, _>>()?;
+
+    if tags.len() > MAX_TAGS_PER_POST {
+        return Err(ValidationError {
+            field: "tags",
+            message: format!("must contain at most {} different tags, but contains {}", MAX_TAGS_PER_POST, tags.len()),
+        });
+    }
+
+    Ok(tags)
+}
\ No newline at end of file
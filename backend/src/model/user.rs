@@ -0,0 +1,98 @@
+use crate::model::post::{now, validate, ValidationError};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// The longest display name, in characters.
+const DISPLAY_NAME_MAX_LENGTH: usize = 100;
+
+/// The longest bio, in characters.
+const BIO_MAX_LENGTH: usize = 2000;
+
+/// The longest avatar URL, in characters.
+const AVATAR_URL_MAX_LENGTH: usize = 2000;
+
+pub(crate) struct UserId(pub(crate) Uuid);
+pub(crate) struct DisplayName(pub(crate) String);
+pub(crate) struct Bio(pub(crate) String);
+pub(crate) struct AvatarUrl(pub(crate) String);
+
+impl DisplayName {
+    /// Validates a display name written by a user. Display names must fit on one line.
+    pub(crate) fn parse(raw: String) -> Result<Self, ValidationError> {
+        validate("display_name", raw, DISPLAY_NAME_MAX_LENGTH, &[]).map(DisplayName)
+    }
+}
+
+impl Bio {
+    /// Validates a bio written by a user. Bios may contain line breaks.
+    pub(crate) fn parse(raw: String) -> Result<Self, ValidationError> {
+        validate("bio", raw, BIO_MAX_LENGTH, &['\n', '\r']).map(Bio)
+    }
+}
+
+impl AvatarUrl {
+    /// Validates the URL of an avatar image. Only http(s) URLs are allowed, so that clients can
+    /// safely put them in <img> tags.
+    pub(crate) fn parse(raw: String) -> Result<Self, ValidationError> {
+        let url = validate("avatar_url", raw, AVATAR_URL_MAX_LENGTH, &[])?;
+
+        if !(url.starts_with("https://") || url.starts_with("http://")) || url.contains(char::is_whitespace) {
+            return Err(ValidationError { field: "avatar_url", message: String::from("must be an http or https URL") });
+        }
+
+        Ok(AvatarUrl(url))
+    }
+}
+
+// We use newtypes here, so that profile fields cannot be swapped accidentally. A User is recorded
+// the first time they log in. Their username comes from the Authenticator, and is updated every
+// time they log in, but everything else can be changed by the User.
+pub(crate) struct User {
+    pub(crate) user_id: UserId,
+    pub(crate) username: String,
+    pub(crate) display_name: DisplayName,
+    pub(crate) bio: Option<Bio>,
+    pub(crate) avatar_url: Option<AvatarUrl>,
+    pub(crate) created_at: DateTime<Utc>,
+    pub(crate) updated_at: DateTime<Utc>, // when the profile was last changed by the User
+    pub(crate) last_login_at: DateTime<Utc>,
+}
+
+impl User {
+    /// A User who has just logged in for the first time. Display names from the Authenticator are
+    /// trusted, but if there isn't one, the username is used instead.
+    pub(crate) fn new(user_id: UserId, username: String, display_name: Option<String>) -> Self {
+        let now = now();
+        let display_name = display_name.and_then(|name| DisplayName::parse(name).ok())
+            .unwrap_or_else(|| DisplayName(username.clone()));
+
+        Self {
+            user_id,
+            username,
+            display_name,
+            bio: None,
+            avatar_url: None,
+            created_at: now,
+            updated_at: now,
+            last_login_at: now,
+        }
+    }
+
+    /// Replaces any of the profile fields which are given. A bio or avatar URL given as None is
+    /// removed.
+    pub(crate) fn edit(&mut self, display_name: Option<DisplayName>, bio: Option<Option<Bio>>, avatar_url: Option<Option<AvatarUrl>>) {
+        if let Some(display_name) = display_name {
+            self.display_name = display_name;
+        }
+
+        if let Some(bio) = bio {
+            self.bio = bio;
+        }
+
+        if let Some(avatar_url) = avatar_url {
+            self.avatar_url = avatar_url;
+        }
+
+        self.updated_at = now();
+    }
+}
@@ -0,0 +1,51 @@
+use crate::db::Database;
+use chrono::{SubsecRound, Utc};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{watch, Mutex};
+use tokio::time::MissedTickBehavior;
+
+// how many Posts are published per database transaction
+const BATCH_SIZE: usize = 100;
+
+/// Publishes scheduled Posts as they become due, every `interval`, until `shutdown` changes.
+///
+/// Several replicas can run this at once. See PostsByIdTableLike::publish_due().
+pub(crate) async fn run(db: Arc<Mutex<Database>>, interval: Duration, mut shutdown: watch::Receiver<bool>) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    log::info!("started scheduler, publishing due Posts every {:?}", interval);
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => publish_due(&db).await,
+            _ = shutdown.changed() => break,
+        }
+    }
+
+    log::info!("stopped scheduler");
+}
+
+async fn publish_due(db: &Arc<Mutex<Database>>) {
+    loop {
+        // lock the database for one batch at a time, so request handlers are not kept waiting
+        let result = db.lock().await.posts_by_id().publish_due(Utc::now().trunc_subsecs(6), BATCH_SIZE);
+
+        match result {
+            Ok(ids) => {
+                if !ids.is_empty() {
+                    log::info!("published scheduled Posts with ids: {:?}", ids);
+                }
+
+                if ids.len() < BATCH_SIZE {
+                    return;
+                }
+            }
+            Err(e) => {
+                log::error!("error publishing scheduled Posts: {}", e);
+                return;
+            }
+        }
+    }
+}